@@ -6,6 +6,8 @@ use super::parser::is_json_semantically_complete;
 
 pub struct ToolCallAccumulator {
     calls: std::collections::HashMap<String, (String, String)>,
+    /// Call ids in the order the model sent them
+    order: Vec<String>,
     last_id: Option<String>,
     displays: std::collections::HashMap<String, ToolCallDisplay>,
     has_tool_calls: bool,
@@ -17,6 +19,7 @@ impl ToolCallAccumulator {
     pub fn new() -> Self {
         Self {
             calls: std::collections::HashMap::new(),
+            order: Vec::new(),
             last_id: None,
             displays: std::collections::HashMap::new(),
             has_tool_calls: false,
@@ -45,6 +48,9 @@ impl ToolCallAccumulator {
             id.clone()
         };
 
+        if !self.calls.contains_key(&key) {
+            self.order.push(key.clone());
+        }
         let entry = self
             .calls
             .entry(key.clone())
@@ -82,9 +88,11 @@ impl ToolCallAccumulator {
 
     pub fn into_tool_calls(self) -> Vec<ToolCall> {
         let has_tool_calls = self.has_tool_calls;
+        let mut calls = self.calls;
 
-        self.calls
+        self.order
             .into_iter()
+            .filter_map(|id| calls.remove(&id).map(|call| (id, call)))
             .filter_map(|(id, (name, arguments))| {
                 // Filter out empty tool calls
                 if name.is_empty() || arguments.is_empty() {
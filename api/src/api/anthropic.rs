@@ -0,0 +1,482 @@
+use anyhow::Result;
+use futures::StreamExt;
+use reqwest::{Client, RequestBuilder};
+use serde::Serialize;
+use serde_json::Value;
+
+use config::Config;
+use history::{Message, TokenUsage};
+use tools;
+
+use super::stream::SseLineStream;
+use super::types::{ChunkStream, StreamChunk};
+
+/// Anthropic API version sent with every request
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// The Messages API requires max_tokens, use a generous default for agent turns
+const DEFAULT_MAX_TOKENS: u32 = 8192;
+
+/// Messages API request
+#[derive(Debug, Serialize)]
+pub struct MessagesRequest {
+    pub model: String,
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    pub messages: Vec<AnthropicMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<AnthropicTool>,
+    pub stream: bool,
+}
+
+/// Message in Anthropic format (only "user" and "assistant" roles)
+#[derive(Debug, Serialize)]
+pub struct AnthropicMessage {
+    pub role: String,
+    pub content: Vec<ContentBlock>,
+}
+
+/// Content block of a message
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text { text: String },
+    ToolUse { id: String, name: String, input: Value },
+    ToolResult { tool_use_id: String, content: String },
+}
+
+/// Tool definition in Anthropic format
+#[derive(Debug, Serialize)]
+pub struct AnthropicTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+/// Add Anthropic authentication headers to a request
+pub fn authorize(request: RequestBuilder, config: &Config) -> RequestBuilder {
+    request
+        .header("x-api-key", &config.api_key)
+        .header("anthropic-version", ANTHROPIC_VERSION)
+}
+
+/// Build a Messages API request from the shared message history
+pub fn build_request(
+    model: &str,
+    messages: &[Message],
+    tools: Vec<tools::Tool>,
+    stream: bool,
+    max_tokens: Option<u32>,
+) -> MessagesRequest {
+    let (system, messages) = convert_messages(messages);
+
+    MessagesRequest {
+        model: model.to_string(),
+        max_tokens: max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        system,
+        messages,
+        tools: tools
+            .into_iter()
+            .map(|t| AnthropicTool {
+                name: t.function.name,
+                description: t.function.description,
+                input_schema: t.function.parameters,
+            })
+            .collect(),
+        stream,
+    }
+}
+
+/// Convert OpenAI-style history into a system prompt and Anthropic messages
+///
+/// System messages are hoisted into the top-level system prompt, tool results
+/// become user messages, and consecutive messages of the same role are merged
+/// because the Messages API requires alternating roles.
+pub fn convert_messages(messages: &[Message]) -> (Option<String>, Vec<AnthropicMessage>) {
+    let mut system_parts = Vec::new();
+    let mut converted: Vec<AnthropicMessage> = Vec::new();
+
+    for msg in messages {
+        let (role, blocks) = match msg.role.as_str() {
+            "system" => {
+                if !msg.content.trim().is_empty() {
+                    system_parts.push(msg.content.clone());
+                }
+                continue;
+            }
+            "assistant" => {
+                let mut blocks = Vec::new();
+                if !msg.content.trim().is_empty() {
+                    blocks.push(ContentBlock::Text {
+                        text: msg.content.clone(),
+                    });
+                }
+                for tc in msg.tool_calls.iter().flatten() {
+                    let input = serde_json::from_str(&tc.function.arguments)
+                        .unwrap_or_else(|_| Value::Object(Default::default()));
+                    blocks.push(ContentBlock::ToolUse {
+                        id: tc.id.clone(),
+                        name: tc.function.name.clone(),
+                        input,
+                    });
+                }
+                ("assistant", blocks)
+            }
+            "tool" => (
+                "user",
+                vec![ContentBlock::ToolResult {
+                    tool_use_id: msg.tool_call_id.clone().unwrap_or_default(),
+                    content: msg.content.clone(),
+                }],
+            ),
+            _ => {
+                let mut blocks = Vec::new();
+                if !msg.content.trim().is_empty() {
+                    blocks.push(ContentBlock::Text {
+                        text: msg.content.clone(),
+                    });
+                }
+                ("user", blocks)
+            }
+        };
+
+        if blocks.is_empty() {
+            continue;
+        }
+
+        match converted.last_mut() {
+            Some(last) if last.role == role => last.content.extend(blocks),
+            _ => converted.push(AnthropicMessage {
+                role: role.to_string(),
+                content: blocks,
+            }),
+        }
+    }
+
+    let system = if system_parts.is_empty() {
+        None
+    } else {
+        Some(system_parts.join("\n\n"))
+    };
+
+    (system, converted)
+}
+
+/// Stateful parser for Anthropic SSE events
+///
+/// Tool call ids only appear in `content_block_start`, so the parser tracks the
+/// block currently being streamed to finish tools that take no input.
+#[derive(Debug, Default)]
+pub struct EventParser {
+    current_tool: Option<String>,
+    tool_has_input: bool,
+    /// Usage reported in `message_start`, completed by `message_delta`
+    usage: TokenUsage,
+}
+
+impl EventParser {
+    /// Parse a single SSE line into zero or more chunks
+    pub fn parse_line(&mut self, line: &str) -> Vec<Result<StreamChunk>> {
+        let Some(data) = line.trim().strip_prefix("data:") else {
+            return Vec::new();
+        };
+        // SseLineStream only yields complete lines, errors here are unknown payloads
+        let Ok(event) = serde_json::from_str::<Value>(data.trim()) else {
+            return Vec::new();
+        };
+
+        let mut chunks: Vec<_> = self.parse_event(&event).into_iter().collect();
+        if let Some(usage) = self.parse_usage(&event) {
+            chunks.push(Ok(StreamChunk::Usage(usage)));
+        }
+        chunks
+    }
+
+    /// Collect token usage, returned once the output token count is known
+    fn parse_usage(&mut self, event: &Value) -> Option<TokenUsage> {
+        match event["type"].as_str()? {
+            "message_start" => {
+                let usage = &event["message"]["usage"];
+                // Cached prompt tokens are reported, and priced, separately from input_tokens
+                let count = |key: &str| usage[key].as_u64().unwrap_or(0);
+                self.usage = TokenUsage {
+                    prompt_tokens: count("input_tokens"),
+                    cache_read_tokens: count("cache_read_input_tokens"),
+                    cache_write_tokens: count("cache_creation_input_tokens"),
+                    ..TokenUsage::default()
+                };
+                None
+            }
+            "message_delta" => {
+                let output_tokens = event["usage"]["output_tokens"].as_u64()?;
+                Some(TokenUsage {
+                    completion_tokens: output_tokens,
+                    ..self.usage
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse a decoded event payload
+    pub fn parse_event(&mut self, event: &Value) -> Option<Result<StreamChunk>> {
+        match event["type"].as_str()? {
+            "content_block_start" => {
+                let block = &event["content_block"];
+                if block["type"].as_str() != Some("tool_use") {
+                    return None;
+                }
+                let id = block["id"].as_str().unwrap_or("").to_string();
+                let name = block["name"].as_str().unwrap_or("").to_string();
+                self.current_tool = Some(id.clone());
+                self.tool_has_input = false;
+                Some(Ok(StreamChunk::ToolCall {
+                    id,
+                    name,
+                    arguments: String::new(),
+                }))
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str()? {
+                    "text_delta" => {
+                        let text = delta["text"].as_str()?;
+                        (!text.is_empty()).then(|| Ok(StreamChunk::Content(text.to_string())))
+                    }
+                    "thinking_delta" => {
+                        let thinking = delta["thinking"].as_str()?;
+                        (!thinking.is_empty())
+                            .then(|| Ok(StreamChunk::Reasoning(thinking.to_string())))
+                    }
+                    "input_json_delta" => {
+                        let partial = delta["partial_json"].as_str()?;
+                        if partial.is_empty() {
+                            return None;
+                        }
+                        self.tool_has_input = true;
+                        Some(Ok(StreamChunk::ToolCall {
+                            id: self.current_tool.clone().unwrap_or_default(),
+                            name: String::new(),
+                            arguments: partial.to_string(),
+                        }))
+                    }
+                    _ => None,
+                }
+            }
+            "content_block_stop" => {
+                let id = self.current_tool.take()?;
+                if self.tool_has_input {
+                    return None;
+                }
+                // Tools without parameters never stream input, the accumulator
+                // drops calls with empty arguments
+                Some(Ok(StreamChunk::ToolCall {
+                    id,
+                    name: String::new(),
+                    arguments: "{}".to_string(),
+                }))
+            }
+            "message_delta" => {
+                let reason = event["delta"]["stop_reason"].as_str()?;
+                let reason = match reason {
+                    "end_turn" | "stop_sequence" => "stop",
+                    "max_tokens" => "length",
+                    "tool_use" => "tool_calls",
+                    other => other,
+                };
+                Some(Ok(StreamChunk::FinishReason(reason.to_string())))
+            }
+            "message_stop" => Some(Ok(StreamChunk::Done)),
+            "error" => {
+                let error = &event["error"];
+                Some(Err(anyhow::anyhow!(
+                    "API error {}: {}",
+                    error["type"].as_str().unwrap_or("unknown"),
+                    error["message"].as_str().unwrap_or("")
+                )))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Stream a Messages API response
+pub async fn chat_stream(
+    client: &Client,
+    config: &Config,
+    messages: Vec<Message>,
+    tools: Vec<tools::Tool>,
+) -> Result<ChunkStream> {
+    let url = format!("{}/messages", config.api_url);
+    let request = build_request(&config.current_model, &messages, tools, true, None);
+
+    let response = authorize(client.post(&url), config)
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error {}: {}", status, text);
+    }
+
+    let mut parser = EventParser::default();
+    let mapped_stream = SseLineStream::new(response.bytes_stream())
+        .map(move |line_result| {
+            let chunks = match line_result {
+                Ok(line) => parser.parse_line(&line),
+                Err(e) => vec![Err(e)],
+            };
+            futures::stream::iter(chunks)
+        })
+        .flatten();
+
+    Ok(Box::new(Box::pin(mapped_stream)))
+}
+
+/// Non-streaming Messages API request, returns the concatenated text blocks
+pub async fn chat_complete(
+    client: &Client,
+    config: &Config,
+    messages: Vec<Message>,
+    tools: Vec<tools::Tool>,
+    max_tokens: u32,
+) -> Result<String> {
+    let url = format!("{}/messages", config.api_url);
+    let request = build_request(
+        &config.current_model,
+        &messages,
+        tools,
+        false,
+        Some(max_tokens),
+    );
+
+    let response = authorize(client.post(&url), config)
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error {}: {}", status, text);
+    }
+
+    let response_json: Value = response.json().await?;
+    let content = response_json["content"]
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|b| b["type"].as_str() == Some("text"))
+                .filter_map(|b| b["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("")
+        })
+        .unwrap_or_default();
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use history::{FunctionCall, ToolCall};
+    use serde_json::json;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        }
+    }
+
+    #[test]
+    fn test_convert_messages() {
+        let mut assistant = message("assistant", "");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "toolu_1".to_string(),
+            tool_type: "function".to_string(),
+            function: FunctionCall {
+                name: "file_read".to_string(),
+                arguments: r#"{"path":"a.rs"}"#.to_string(),
+            },
+        }]);
+        let mut tool = message("tool", "fn main() {}");
+        tool.tool_call_id = Some("toolu_1".to_string());
+
+        let history = vec![
+            message("system", "be helpful"),
+            message("user", "read a.rs"),
+            assistant,
+            tool,
+            message("user", "thanks"),
+        ];
+
+        let (system, messages) = convert_messages(&history);
+        assert_eq!(system.as_deref(), Some("be helpful"));
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1].role, "assistant");
+        assert_eq!(
+            messages[1].content,
+            vec![ContentBlock::ToolUse {
+                id: "toolu_1".to_string(),
+                name: "file_read".to_string(),
+                input: json!({"path": "a.rs"}),
+            }]
+        );
+        // Tool result and the following user text are merged into one turn
+        assert_eq!(messages[2].role, "user");
+        assert_eq!(messages[2].content.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_stream_events() {
+        let mut parser = EventParser::default();
+        let lines = [
+            r#"data: {"type":"message_start","message":{"usage":{"input_tokens":10,"cache_read_input_tokens":5,"output_tokens":1}}}"#,
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Plan"}}"#,
+            r#"data: {"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Hi"}}"#,
+            r#"data: {"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"file_read","input":{}}}"#,
+            r#"data: {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"path\":"}}"#,
+            r#"data: {"type":"content_block_stop","index":2}"#,
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":20}}"#,
+            r#"data: {"type":"message_stop"}"#,
+        ];
+
+        let chunks: Vec<_> = lines
+            .iter()
+            .flat_map(|l| parser.parse_line(l))
+            .map(|c| c.unwrap())
+            .collect();
+
+        assert_eq!(chunks.len(), 7);
+        assert!(matches!(&chunks[0], StreamChunk::Reasoning(t) if t == "Plan"));
+        assert!(matches!(&chunks[1], StreamChunk::Content(t) if t == "Hi"));
+        assert!(matches!(&chunks[2], StreamChunk::ToolCall { id, name, .. } if id == "toolu_1" && name == "file_read"));
+        assert!(matches!(&chunks[3], StreamChunk::ToolCall { id, arguments, .. } if id == "toolu_1" && arguments == "{\"path\":"));
+        assert!(matches!(&chunks[4], StreamChunk::FinishReason(r) if r == "tool_calls"));
+        assert!(matches!(chunks[5], StreamChunk::Usage(u)
+            if u.prompt_tokens == 10 && u.cache_read_tokens == 5 && u.completion_tokens == 20));
+        assert!(matches!(chunks[6], StreamChunk::Done));
+    }
+
+    #[test]
+    fn test_parse_tool_without_input() {
+        let mut parser = EventParser::default();
+        parser.parse_line(r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_2","name":"todo_list","input":{}}}"#);
+
+        let mut chunks = parser.parse_line(r#"data: {"type":"content_block_stop","index":0}"#);
+        assert_eq!(chunks.len(), 1);
+        let chunk = chunks.remove(0).unwrap();
+        assert!(matches!(chunk, StreamChunk::ToolCall { arguments, .. } if arguments == "{}"));
+    }
+}
@@ -1,32 +1,73 @@
 use anyhow::Result;
 use futures::StreamExt;
-use reqwest::Client;
-use tokio_stream::Stream;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, RequestBuilder};
 
-use config::Config;
+use config::{ApiProvider, Config};
 use history::Message;
 use tools;
+use ui::events::{self, Event};
 use ui::get_i18n;
 
-use super::parser::parse_sse_line;
+use super::anthropic;
+use super::gemini;
+use super::parser::{parse_sse_line, parse_sse_usage};
 use super::stream::SseLineStream;
-use super::types::{ChatRequest, ModelsResponse, StreamChunk};
+use super::types::{ChatRequest, ChunkStream, ModelsResponse, StreamChunk, StreamOptions};
 
 #[derive(Clone)]
 pub struct ApiClient {
     client: Client,
     config: Config,
+    /// Tools offered to the model, all of them when None
+    allowed_tools: Option<Vec<String>>,
 }
 
 impl ApiClient {
     pub fn new(config: Config) -> Self {
+        // Profile specific headers (gateways, OpenRouter attribution, ...)
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.extra_headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+
         let client = Client::builder()
+            .default_headers(headers)
             .timeout(std::time::Duration::from_secs(300)) // 5 minute timeout
             .connect_timeout(std::time::Duration::from_secs(60)) // 1 minute connect timeout
             .build()
             .unwrap_or_else(|_| Client::new());
 
-        Self { client, config }
+        Self {
+            client,
+            config,
+            allowed_tools: None,
+        }
+    }
+
+    /// Offer only the named tools to the model, used for restricted subagents
+    pub fn with_allowed_tools(mut self, tools: Vec<String>) -> Self {
+        self.allowed_tools = Some(tools);
+        self
+    }
+
+    /// Tool definitions sent with each request
+    pub fn offered_tools(&self, mcp_integration: Option<&mcp::McpIntegration>) -> Vec<tools::Tool> {
+        let mut tools = tools::get_available_tools_with_mcp(mcp_integration);
+        if let Some(allowed) = &self.allowed_tools {
+            tools.retain(|tool| allowed.contains(&tool.function.name));
+        }
+        tools
+    }
+
+    /// Model used for requests
+    pub fn model(&self) -> &str {
+        &self.config.current_model
     }
 
     /// Clean message history: remove orphaned tool calls without responses
@@ -87,7 +128,7 @@ impl ApiClient {
         &self,
         messages: Vec<Message>,
         mcp_integration: Option<&mcp::McpIntegration>,
-    ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
+    ) -> Result<ChunkStream> {
         let cleaned_messages = Self::clean_messages(&messages);
 
         let max_retries = self.config.max_retries;
@@ -97,14 +138,14 @@ impl ApiClient {
             if attempt > 0 {
                 let delay = base_delay * (1 << (attempt - 1)); // exponential backoff
                 let i18n = get_i18n();
-                println!(
+                ui::output::println(&format!(
                     "\n\x1b[33m[!] {} {}/{}...{} {}ms\x1b[0m",
                     i18n.get("api_retry_label"),
                     attempt,
                     max_retries,
                     i18n.get("api_retry_waiting"),
                     delay
-                );
+                ));
                 tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
             }
 
@@ -113,15 +154,21 @@ impl ApiClient {
                 Err(e) => {
                     if attempt == max_retries {
                         let i18n = get_i18n();
-                        eprintln!("\n\x1b[31m[X] {}\x1b[0m", i18n.get("api_retries_failed"));
+                        ui::output::eprintln(&format!("\n\x1b[31m[X] {}\x1b[0m", i18n.get("api_retries_failed")));
                         return Err(e);
                     }
                     let i18n = get_i18n();
-                    eprintln!(
+                    ui::output::eprintln(&format!(
                         "\n\x1b[33m[!] {}: {}\x1b[0m",
                         i18n.get("api_request_failed"),
                         e
-                    );
+                    ));
+                    events::emit(&Event::Retry {
+                        attempt: attempt + 1,
+                        max_retries,
+                        delay_ms: base_delay * (1 << attempt),
+                        error: &e.to_string(),
+                    });
                 }
             }
         }
@@ -130,26 +177,57 @@ impl ApiClient {
         Err(anyhow::anyhow!(i18n.get("api_retries_failed")))
     }
 
-    /// Stream chat completions
+    /// Add provider specific authentication headers
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.config.provider {
+            ApiProvider::OpenAi => {
+                request.header("Authorization", format!("Bearer {}", self.config.api_key))
+            }
+            ApiProvider::Anthropic => anthropic::authorize(request, &self.config),
+            ApiProvider::Gemini => gemini::authorize(request, &self.config),
+        }
+    }
+
+    /// Stream chat completions from the configured provider
     async fn chat_stream(
         &self,
         messages: Vec<Message>,
         mcp_integration: Option<&mcp::McpIntegration>,
-    ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
+    ) -> Result<ChunkStream> {
+        let tools = self.offered_tools(mcp_integration);
+
+        match self.config.provider {
+            ApiProvider::OpenAi => self.chat_stream_openai(messages, tools).await,
+            ApiProvider::Anthropic => {
+                anthropic::chat_stream(&self.client, &self.config, messages, tools).await
+            }
+            ApiProvider::Gemini => {
+                gemini::chat_stream(&self.client, &self.config, messages, tools).await
+            }
+        }
+    }
+
+    /// Stream OpenAI-compatible chat completions
+    async fn chat_stream_openai(
+        &self,
+        messages: Vec<Message>,
+        tools: Vec<tools::Tool>,
+    ) -> Result<ChunkStream> {
         let url = format!("{}/chat/completions", self.config.api_url);
 
         let request = ChatRequest {
             model: self.config.current_model.clone(),
             messages,
-            tools: tools::get_available_tools_with_mcp(mcp_integration),
+            tools,
             stream: true,
             max_tokens: None,
+            stream_options: Some(StreamOptions {
+                include_usage: true,
+            }),
         };
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .authorize(self.client.post(&url))
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
@@ -164,32 +242,76 @@ impl ApiClient {
         let stream = response.bytes_stream();
         let sse_stream = SseLineStream::new(stream);
 
-        let mapped_stream = sse_stream.filter_map(|line_result| async move {
-            match line_result {
-                Ok(line) => parse_sse_line(&line),
-                Err(e) => Some(Err(e)),
-            }
-        });
+        let mapped_stream = sse_stream
+            .map(|line_result| {
+                let chunks: Vec<Result<StreamChunk>> = match line_result {
+                    Ok(line) => parse_sse_line(&line)
+                        .into_iter()
+                        .chain(parse_sse_usage(&line).map(|u| Ok(StreamChunk::Usage(u))))
+                        .collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                futures::stream::iter(chunks)
+            })
+            .flatten();
 
         Ok(Box::new(Box::pin(mapped_stream)))
     }
 
     /// Non-streaming chat completion (for simple requests like prompt optimization)
     pub async fn chat_complete(&self, messages: Vec<Message>, mcp_integration: Option<&mcp::McpIntegration>) -> Result<Message> {
+        let tools = tools::get_available_tools_with_mcp(mcp_integration);
+        let content = self.complete(messages, tools, 1000).await?; // Limit tokens for optimization
+
+        Ok(Message {
+            role: "assistant".to_string(),
+            content,
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        })
+    }
+
+    /// Non-streaming completion with explicit tools and token limit, returns the text content
+    pub async fn complete(
+        &self,
+        messages: Vec<Message>,
+        tools: Vec<tools::Tool>,
+        max_tokens: u32,
+    ) -> Result<String> {
+        match self.config.provider {
+            ApiProvider::OpenAi => self.chat_complete_openai(messages, tools, max_tokens).await,
+            ApiProvider::Anthropic => {
+                anthropic::chat_complete(&self.client, &self.config, messages, tools, max_tokens)
+                    .await
+            }
+            ApiProvider::Gemini => {
+                gemini::chat_complete(&self.client, &self.config, messages, tools, max_tokens)
+                    .await
+            }
+        }
+    }
+
+    /// Non-streaming OpenAI-compatible chat completion
+    async fn chat_complete_openai(
+        &self,
+        messages: Vec<Message>,
+        tools: Vec<tools::Tool>,
+        max_tokens: u32,
+    ) -> Result<String> {
         let url = format!("{}/chat/completions", self.config.api_url);
         
         let request = ChatRequest {
             model: self.config.current_model.clone(),
             messages,
-            tools: tools::get_available_tools_with_mcp(mcp_integration),
+            tools,
             stream: false,
-            max_tokens: Some(1000),  // Limit tokens for optimization
+            max_tokens: Some(max_tokens),
+            stream_options: None,
         };
         
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .authorize(self.client.post(&url))
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
@@ -204,28 +326,22 @@ impl ApiClient {
         // Parse response
         let response_json: serde_json::Value = response.json().await?;
         
-        let content = response_json["choices"][0]["message"]["content"]
+        Ok(response_json["choices"][0]["message"]["content"]
             .as_str()
             .unwrap_or("")
-            .to_string();
-        
-        Ok(Message {
-            role: "assistant".to_string(),
-            content,
-            tool_calls: None,
-            tool_call_id: None,
-            name: None,
-        })
+            .to_string())
     }
 
     /// List available models
     pub async fn list_models(&self) -> Result<Vec<String>> {
+        if self.config.provider == ApiProvider::Gemini {
+            return gemini::list_models(&self.client, &self.config).await;
+        }
+
         let url = format!("{}/models", self.config.api_url);
 
         let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .authorize(self.client.get(&url))
             .send()
             .await?;
 
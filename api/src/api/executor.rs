@@ -3,6 +3,7 @@ use std::path::Path;
 use history::{Message, ToolCall};
 use tools::{self, ToolResult};
 use ui::get_i18n;
+use ui::events::{self, Event};
 use ui::ToolCallDisplay;
 use futures::future::BoxFuture;
 use anyhow::Result;
@@ -20,7 +21,14 @@ pub async fn execute_tool_calls(
     execute_tool_calls_with_mcp(tool_calls, working_dir, displays, require_approval, session_id, None, None).await
 }
 
+/// Upper bound on read-only tool calls running at the same time
+const MAX_PARALLEL_TOOLS: usize = 8;
+
 /// Execute tool calls with MCP integration
+///
+/// Consecutive read-only calls run concurrently, and so do consecutive `task` calls
+/// since each subagent works in its own session. Everything else runs one at a time.
+/// Results always come back in the order the model asked for them.
 pub async fn execute_tool_calls_with_mcp(
     tool_calls: &[ToolCall],
     working_dir: &Path,
@@ -30,82 +38,199 @@ pub async fn execute_tool_calls_with_mcp(
     mcp_integration: Option<&mcp::McpIntegration>,
     custom_handler: Option<&CustomToolHandler>,
 ) -> Vec<Message> {
-    let mut results = Vec::new();
-
-    for tc in tool_calls {
-        // Skip invalid tool calls
-        if tc.id.is_empty() || tc.function.name.is_empty() {
-            let i18n = get_i18n();
-            eprintln!(
-                "\x1b[33m[!] {}:\x1b[0m {} id={}, name={}",
-                i18n.get("warning"),
-                i18n.get("api_skip_invalid_tool_call"),
-                tc.id,
-                tc.function.name
-            );
-            continue;
-        }
-
-        // Validate JSON arguments before execution
-        if serde_json::from_str::<serde_json::Value>(&tc.function.arguments).is_err() {
-            let i18n = get_i18n();
-            eprintln!(
-                "\x1b[33m[!] {}:\x1b[0m {} {}",
-                i18n.get("warning"),
-                i18n.get("api_skip_invalid_json_args"),
-                tc.function.name
-            );
-            continue;
-        }
+    let valid_calls: Vec<&ToolCall> = tool_calls.iter().filter(|tc| is_valid_tool_call(tc)).collect();
+    let mut results = Vec::with_capacity(valid_calls.len());
 
-        // Try custom handler first
-        let mut tool_result_opt = None;
-        if let Some(handler) = custom_handler {
-             match handler(&tc.function.name, &tc.function.arguments, working_dir).await {
-                 Ok(Some(res)) => tool_result_opt = Some(res),
-                 Ok(None) => {}, // Handler didn't handle it
-                 Err(e) => {
-                     // Handler failed
-                     tool_result_opt = Some(tools::ToolResult::error(format!("Custom tool handler error: {}", e)));
-                 }
-             }
-        }
+    let mut index = 0;
+    while index < valid_calls.len() {
+        let first = valid_calls[index].function.name.as_str();
+        let batch_len = valid_calls[index..]
+            .iter()
+            .take_while(|tc| joins_batch(first, &tc.function.name))
+            .count()
+            .max(1);
+        let batch = &valid_calls[index..index + batch_len];
+        index += batch_len;
 
-        let tool_result = if let Some(res) = tool_result_opt {
-            res
+        let tool_results = if batch.len() == 1 {
+            let tc = batch[0];
+            emit_tool_call_start(tc);
+            let custom = custom_handler.map(|h| h(&tc.function.name, &tc.function.arguments, working_dir));
+            vec![
+                run_tool_call(
+                    custom,
+                    &tc.function.name,
+                    &tc.function.arguments,
+                    working_dir,
+                    require_approval,
+                    session_id,
+                    mcp_integration,
+                )
+                .await,
+            ]
         } else {
-            tools::execute_tool_with_mcp(
-                &tc.function.name,
-                &tc.function.arguments,
-                working_dir,
-                require_approval,
-                session_id,
-                mcp_integration,
-            )
-            .await
-            .unwrap_or_else(|e| {
-                let i18n = get_i18n();
-                let tmpl = i18n.get("api_tool_execution_error");
-                let msg = tmpl.replace("{}", &e.to_string());
-                tools::ToolResult::error(msg)
-            })
+            let limit = if first == "task" {
+                tools::MAX_PARALLEL_SUBAGENTS
+            } else {
+                MAX_PARALLEL_TOOLS
+            };
+            run_concurrent_batch(batch, limit, working_dir, require_approval, session_id, mcp_integration, custom_handler)
+                .await
         };
 
-        // Update UI display
-        if let Some(display) = displays.get_mut(&tc.id) {
-            display.finish(tool_result.success, Some(tool_result.brief.clone()));
-            println!();
-            display.render_final();
+        for (tc, tool_result) in batch.iter().zip(tool_results) {
+            let content = tool_result.content();
+            events::emit(&Event::ToolCallEnd {
+                id: &tc.id,
+                name: &tc.function.name,
+                success: tool_result.success,
+                brief: &tool_result.brief,
+                output: &content,
+            });
+
+            // Update UI display
+            if let Some(display) = displays.get_mut(&tc.id) {
+                display.finish(tool_result.success, Some(tool_result.brief.clone()));
+                ui::output::println("");
+                display.render_final();
+            }
+
+            results.push(Message {
+                role: "tool".to_string(),
+                content,
+                tool_calls: None,
+                tool_call_id: Some(tc.id.clone()),
+                name: Some(tc.function.name.clone()),
+            });
         }
+    }
+
+    results
+}
+
+/// Whether a call can join the concurrent batch starting with `first`
+fn joins_batch(first: &str, name: &str) -> bool {
+    if first == "task" {
+        name == "task"
+    } else {
+        tools::is_read_only_tool(name)
+    }
+}
+
+/// Skip calls without an id or name and calls whose arguments are not valid JSON
+fn is_valid_tool_call(tc: &ToolCall) -> bool {
+    if tc.id.is_empty() || tc.function.name.is_empty() {
+        let i18n = get_i18n();
+        eprintln!(
+            "\x1b[33m[!] {}:\x1b[0m {} id={}, name={}",
+            i18n.get("warning"),
+            i18n.get("api_skip_invalid_tool_call"),
+            tc.id,
+            tc.function.name
+        );
+        return false;
+    }
+
+    if serde_json::from_str::<serde_json::Value>(&tc.function.arguments).is_err() {
+        let i18n = get_i18n();
+        eprintln!(
+            "\x1b[33m[!] {}:\x1b[0m {} {}",
+            i18n.get("warning"),
+            i18n.get("api_skip_invalid_json_args"),
+            tc.function.name
+        );
+        return false;
+    }
+
+    true
+}
+
+fn emit_tool_call_start(tc: &ToolCall) {
+    events::emit(&Event::ToolCallStart {
+        id: &tc.id,
+        name: &tc.function.name,
+        arguments: serde_json::from_str(&tc.function.arguments).unwrap_or_default(),
+    });
+}
 
-        results.push(Message {
-            role: "tool".to_string(),
-            content: tool_result.message,
-            tool_calls: None,
-            tool_call_id: Some(tc.id.clone()),
-            name: Some(tc.function.name.clone()),
-        });
+/// Run calls on separate tasks, at most `limit` at a time
+async fn run_concurrent_batch(
+    batch: &[&ToolCall],
+    limit: usize,
+    working_dir: &Path,
+    require_approval: bool,
+    session_id: Option<&str>,
+    mcp_integration: Option<&mcp::McpIntegration>,
+    custom_handler: Option<&CustomToolHandler>,
+) -> Vec<ToolResult> {
+    let mut results = Vec::with_capacity(batch.len());
+
+    for chunk in batch.chunks(limit) {
+        let handles: Vec<_> = chunk
+            .iter()
+            .map(|tc| {
+                emit_tool_call_start(tc);
+                let custom = custom_handler.map(|h| h(&tc.function.name, &tc.function.arguments, working_dir));
+                let name = tc.function.name.clone();
+                let arguments = tc.function.arguments.clone();
+                let working_dir = working_dir.to_path_buf();
+                let session_id = session_id.map(str::to_string);
+                let mcp_integration = mcp_integration.cloned();
+                // Output of a buffered subagent stays in its buffer
+                let buffer = ui::output::current_buffer();
+
+                tokio::spawn(ui::output::with_buffer(buffer, async move {
+                    run_tool_call(
+                        custom,
+                        &name,
+                        &arguments,
+                        &working_dir,
+                        require_approval,
+                        session_id.as_deref(),
+                        mcp_integration.as_ref(),
+                    )
+                    .await
+                }))
+            })
+            .collect();
+
+        // Await in request order, whichever call finishes first
+        for handle in handles {
+            results.push(handle.await.unwrap_or_else(|e| tool_execution_error(&e.to_string())));
+        }
     }
 
     results
 }
+
+/// Run one tool call, giving the custom handler the first chance to handle it
+async fn run_tool_call(
+    custom: Option<BoxFuture<'static, Result<Option<ToolResult>>>>,
+    name: &str,
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+    session_id: Option<&str>,
+    mcp_integration: Option<&mcp::McpIntegration>,
+) -> ToolResult {
+    if let Some(custom) = custom {
+        match custom.await {
+            Ok(Some(res)) => return res,
+            Ok(None) => {} // Handler didn't handle it
+            Err(e) => {
+                // Handler failed
+                return tools::ToolResult::error(format!("Custom tool handler error: {}", e));
+            }
+        }
+    }
+
+    tools::execute_tool_with_mcp(name, arguments, working_dir, require_approval, session_id, mcp_integration)
+        .await
+        .unwrap_or_else(|e| tool_execution_error(&e.to_string()))
+}
+
+fn tool_execution_error(error: &str) -> ToolResult {
+    let i18n = get_i18n();
+    let tmpl = i18n.get("api_tool_execution_error");
+    tools::ToolResult::error(tmpl.replace("{}", error))
+}
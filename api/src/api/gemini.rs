@@ -0,0 +1,579 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Result;
+use futures::StreamExt;
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use config::Config;
+use history::{Message, TokenUsage};
+use tools;
+
+use super::stream::SseLineStream;
+use super::types::{ChunkStream, StreamChunk};
+
+/// JSON schema keywords rejected by Gemini function declarations
+const UNSUPPORTED_SCHEMA_KEYS: &[&str] = &["$schema", "$id", "$comment", "additionalProperties"];
+
+/// Counter for synthesized tool call ids (older Gemini models don't return ids)
+static CALL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// generateContent request
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentRequest {
+    pub contents: Vec<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<GeminiTools>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_config: Option<GenerationConfig>,
+}
+
+/// Content with a role ("user" or "model") and its parts
+#[derive(Debug, Serialize)]
+pub struct Content {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    pub parts: Vec<Value>,
+}
+
+/// Tool wrapper holding function declarations
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiTools {
+    pub function_declarations: Vec<FunctionDeclaration>,
+}
+
+/// Function declaration in Gemini format
+#[derive(Debug, Serialize)]
+pub struct FunctionDeclaration {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+/// Generation options
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationConfig {
+    pub max_output_tokens: u32,
+}
+
+/// Models list response
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    models: Vec<ModelInfo>,
+}
+
+/// Model info from models list response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelInfo {
+    name: String,
+    #[serde(default)]
+    supported_generation_methods: Vec<String>,
+}
+
+/// Add Gemini authentication headers to a request
+pub fn authorize(request: RequestBuilder, config: &Config) -> RequestBuilder {
+    request.header("x-goog-api-key", &config.api_key)
+}
+
+/// Build a generateContent request from the shared message history
+pub fn build_request(
+    messages: &[Message],
+    tools: Vec<tools::Tool>,
+    max_tokens: Option<u32>,
+) -> GenerateContentRequest {
+    let (system, contents) = convert_messages(messages);
+
+    let declarations: Vec<_> = tools
+        .into_iter()
+        .map(|t| FunctionDeclaration {
+            name: t.function.name,
+            description: t.function.description,
+            parameters: sanitize_schema(t.function.parameters),
+        })
+        .collect();
+
+    GenerateContentRequest {
+        contents,
+        system_instruction: system.map(|text| Content {
+            role: None,
+            parts: vec![json!({ "text": text })],
+        }),
+        tools: if declarations.is_empty() {
+            Vec::new()
+        } else {
+            vec![GeminiTools {
+                function_declarations: declarations,
+            }]
+        },
+        generation_config: max_tokens.map(|max_output_tokens| GenerationConfig { max_output_tokens }),
+    }
+}
+
+/// Remove schema keywords that Gemini rejects, recursively
+fn sanitize_schema(schema: Value) -> Value {
+    match schema {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(k, _)| !UNSUPPORTED_SCHEMA_KEYS.contains(&k.as_str()))
+                .map(|(k, v)| (k, sanitize_schema(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(sanitize_schema).collect()),
+        other => other,
+    }
+}
+
+/// Convert OpenAI-style history into a system instruction and Gemini contents
+///
+/// Assistant tool calls become `functionCall` parts, tool messages become
+/// `functionResponse` parts, and consecutive contents of the same role are merged.
+pub fn convert_messages(messages: &[Message]) -> (Option<String>, Vec<Content>) {
+    let mut system_parts = Vec::new();
+    let mut contents: Vec<Content> = Vec::new();
+    // Tool messages reference calls by id, Gemini matches responses by name
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for msg in messages {
+        let (role, parts) = match msg.role.as_str() {
+            "system" => {
+                if !msg.content.trim().is_empty() {
+                    system_parts.push(msg.content.clone());
+                }
+                continue;
+            }
+            "assistant" => {
+                let mut parts = Vec::new();
+                if !msg.content.trim().is_empty() {
+                    parts.push(json!({ "text": msg.content }));
+                }
+                for tc in msg.tool_calls.iter().flatten() {
+                    let args: Value = serde_json::from_str(&tc.function.arguments)
+                        .unwrap_or_else(|_| json!({}));
+                    call_names.insert(tc.id.clone(), tc.function.name.clone());
+                    parts.push(json!({
+                        "functionCall": { "name": tc.function.name, "args": args }
+                    }));
+                }
+                ("model", parts)
+            }
+            "tool" => {
+                let name = msg
+                    .name
+                    .clone()
+                    .or_else(|| {
+                        msg.tool_call_id
+                            .as_ref()
+                            .and_then(|id| call_names.get(id).cloned())
+                    })
+                    .unwrap_or_default();
+                (
+                    "user",
+                    vec![json!({
+                        "functionResponse": {
+                            "name": name,
+                            "response": { "content": msg.content }
+                        }
+                    })],
+                )
+            }
+            _ => {
+                let mut parts = Vec::new();
+                if !msg.content.trim().is_empty() {
+                    parts.push(json!({ "text": msg.content }));
+                }
+                ("user", parts)
+            }
+        };
+
+        if parts.is_empty() {
+            continue;
+        }
+
+        match contents.last_mut() {
+            Some(last) if last.role.as_deref() == Some(role) => last.parts.extend(parts),
+            _ => contents.push(Content {
+                role: Some(role.to_string()),
+                parts,
+            }),
+        }
+    }
+
+    let system = if system_parts.is_empty() {
+        None
+    } else {
+        Some(system_parts.join("\n\n"))
+    };
+
+    (system, contents)
+}
+
+/// Stateful parser for Gemini SSE responses
+///
+/// Every chunk is a complete `GenerateContentResponse`, a single line can carry
+/// text and several function calls at once.
+#[derive(Debug, Default)]
+pub struct ResponseParser {
+    has_function_calls: bool,
+}
+
+impl ResponseParser {
+    /// Parse a single SSE line into zero or more chunks
+    pub fn parse_line(&mut self, line: &str) -> Vec<Result<StreamChunk>> {
+        let Some(data) = line.trim().strip_prefix("data:") else {
+            return Vec::new();
+        };
+        let data = data.trim();
+        if data.is_empty() {
+            return Vec::new();
+        }
+
+        match serde_json::from_str::<Value>(data) {
+            Ok(response) => self.parse_response(&response),
+            // SseLineStream only yields complete lines, errors here are unknown payloads
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Parse a decoded response payload
+    pub fn parse_response(&mut self, response: &Value) -> Vec<Result<StreamChunk>> {
+        let mut chunks = Vec::new();
+
+        if let Some(error) = response.get("error") {
+            chunks.push(Err(anyhow::anyhow!(
+                "API error {}: {}",
+                error["status"].as_str().unwrap_or("unknown"),
+                error["message"].as_str().unwrap_or("")
+            )));
+            return chunks;
+        }
+
+        let candidate = &response["candidates"][0];
+
+        for part in candidate["content"]["parts"].as_array().into_iter().flatten() {
+            if let Some(call) = part.get("functionCall") {
+                self.has_function_calls = true;
+                let id = call["id"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(next_call_id);
+                let args = call.get("args").cloned().unwrap_or_else(|| json!({}));
+                chunks.push(Ok(StreamChunk::ToolCall {
+                    id,
+                    name: call["name"].as_str().unwrap_or("").to_string(),
+                    arguments: args.to_string(),
+                }));
+            } else if let Some(text) = part["text"].as_str() {
+                if text.is_empty() {
+                    continue;
+                }
+                if part["thought"].as_bool() == Some(true) {
+                    chunks.push(Ok(StreamChunk::Reasoning(text.to_string())));
+                } else {
+                    chunks.push(Ok(StreamChunk::Content(text.to_string())));
+                }
+            }
+        }
+
+        if let Some(reason) = candidate["finishReason"].as_str() {
+            let reason = match reason {
+                // Gemini reports STOP for function calls too
+                "STOP" if self.has_function_calls => "tool_calls".to_string(),
+                "STOP" => "stop".to_string(),
+                "MAX_TOKENS" => "length".to_string(),
+                other => other.to_lowercase(),
+            };
+            chunks.push(Ok(StreamChunk::FinishReason(reason)));
+
+            // usageMetadata is cumulative, only report it with the final chunk
+            let metadata = &response["usageMetadata"];
+            if metadata.is_object() {
+                let thoughts = metadata["thoughtsTokenCount"].as_u64().unwrap_or(0);
+                chunks.push(Ok(StreamChunk::Usage(TokenUsage {
+                    prompt_tokens: metadata["promptTokenCount"].as_u64().unwrap_or(0),
+                    completion_tokens: metadata["candidatesTokenCount"].as_u64().unwrap_or(0)
+                        + thoughts,
+                    reasoning_tokens: thoughts,
+                    ..TokenUsage::default()
+                })));
+            }
+        }
+
+        chunks
+    }
+}
+
+/// Synthesize a unique tool call id
+fn next_call_id() -> String {
+    let n = CALL_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!(
+        "call_{}_{}",
+        chrono::Utc::now().timestamp_millis(),
+        n
+    )
+}
+
+/// Model path segment, accepting both "gemini-x" and "models/gemini-x"
+fn model_path(model: &str) -> String {
+    if model.starts_with("models/") {
+        model.to_string()
+    } else {
+        format!("models/{}", model)
+    }
+}
+
+/// Stream a generateContent response
+pub async fn chat_stream(
+    client: &Client,
+    config: &Config,
+    messages: Vec<Message>,
+    tools: Vec<tools::Tool>,
+) -> Result<ChunkStream> {
+    let url = format!(
+        "{}/{}:streamGenerateContent?alt=sse",
+        config.api_url,
+        model_path(&config.current_model)
+    );
+    let request = build_request(&messages, tools, None);
+
+    let response = authorize(client.post(&url), config)
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error {}: {}", status, text);
+    }
+
+    let mut parser = ResponseParser::default();
+    let mapped_stream = SseLineStream::new(response.bytes_stream())
+        .map(move |line_result| {
+            let chunks = match line_result {
+                Ok(line) => parser.parse_line(&line),
+                Err(e) => vec![Err(e)],
+            };
+            futures::stream::iter(chunks)
+        })
+        .flatten();
+
+    Ok(Box::new(Box::pin(mapped_stream)))
+}
+
+/// Non-streaming generateContent request, returns the concatenated text parts
+pub async fn chat_complete(
+    client: &Client,
+    config: &Config,
+    messages: Vec<Message>,
+    tools: Vec<tools::Tool>,
+    max_tokens: u32,
+) -> Result<String> {
+    let url = format!(
+        "{}/{}:generateContent",
+        config.api_url,
+        model_path(&config.current_model)
+    );
+    let request = build_request(&messages, tools, Some(max_tokens));
+
+    let response = authorize(client.post(&url), config)
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error {}: {}", status, text);
+    }
+
+    let response_json: Value = response.json().await?;
+    let content = response_json["candidates"][0]["content"]["parts"]
+        .as_array()
+        .map(|parts| {
+            parts
+                .iter()
+                .filter(|p| p["thought"].as_bool() != Some(true))
+                .filter_map(|p| p["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("")
+        })
+        .unwrap_or_default();
+
+    Ok(content)
+}
+
+/// List models that support generateContent
+pub async fn list_models(client: &Client, config: &Config) -> Result<Vec<String>> {
+    let url = format!("{}/models", config.api_url);
+
+    let response = authorize(client.get(&url), config).send().await?;
+
+    if !response.status().is_success() {
+        let i18n = ui::get_i18n();
+        anyhow::bail!(i18n.get("api_models_failed"));
+    }
+
+    let models_response: ModelsResponse = response.json().await?;
+    Ok(models_response
+        .models
+        .into_iter()
+        .filter(|m| {
+            m.supported_generation_methods.is_empty()
+                || m.supported_generation_methods
+                    .iter()
+                    .any(|method| method == "generateContent")
+        })
+        .map(|m| m.name.trim_start_matches("models/").to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::ApiProvider;
+    use history::{FunctionCall, ToolCall};
+    use httpmock::{Method::POST, MockServer};
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        }
+    }
+
+    fn test_config(api_url: String) -> Config {
+        Config {
+            provider: ApiProvider::Gemini,
+            api_key: "test-key".to_string(),
+            api_url,
+            current_model: "gemini-test".to_string(),
+            ui_language: "en".to_string(),
+            ai_language: "en".to_string(),
+            max_retries: 0,
+            retry_delay_ms: 0,
+            shorekeeper_model: None,
+            extra_headers: Default::default(),
+            profiles: Default::default(),
+            active_profile: None,
+            compaction: Default::default(),
+            mcp_server: Default::default(),
+            verification: Default::default(),
+            duet: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_convert_messages() {
+        let mut assistant = message("assistant", "Reading");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call_1".to_string(),
+            tool_type: "function".to_string(),
+            function: FunctionCall {
+                name: "file_read".to_string(),
+                arguments: r#"{"path":"a.rs"}"#.to_string(),
+            },
+        }]);
+        let mut tool = message("tool", "fn main() {}");
+        tool.tool_call_id = Some("call_1".to_string());
+
+        let history = vec![
+            message("system", "be helpful"),
+            message("user", "read a.rs"),
+            assistant,
+            tool,
+        ];
+
+        let (system, contents) = convert_messages(&history);
+        assert_eq!(system.as_deref(), Some("be helpful"));
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[1].role.as_deref(), Some("model"));
+        assert_eq!(
+            contents[1].parts[1],
+            json!({ "functionCall": { "name": "file_read", "args": { "path": "a.rs" } } })
+        );
+        // Tool name is recovered from the matching call id
+        assert_eq!(contents[2].parts[0]["functionResponse"]["name"], "file_read");
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_against_mock_server() {
+        let server = MockServer::start_async().await;
+        let body = [
+            r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"Let me","thought":true}]}}]}"#,
+            r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"Sure"},{"functionCall":{"name":"file_read","args":{"path":"a.rs"}}}]},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":12,"candidatesTokenCount":8,"thoughtsTokenCount":4}}"#,
+        ]
+        .join("\n\n");
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/models/gemini-test:streamGenerateContent")
+                .query_param("alt", "sse")
+                .header("x-goog-api-key", "test-key")
+                .body_contains("functionDeclarations");
+            then.status(200)
+                .header("Content-Type", "text/event-stream")
+                .body(body + "\n\n");
+        });
+
+        let config = test_config(server.base_url());
+        let stream = chat_stream(
+            &Client::new(),
+            &config,
+            vec![message("user", "read a.rs")],
+            tools::get_available_tools_with_mcp(None),
+        )
+        .await
+        .unwrap();
+        let chunks: Vec<_> = stream.map(|c| c.unwrap()).collect().await;
+
+        mock.assert();
+        assert_eq!(chunks.len(), 5);
+        assert!(matches!(&chunks[0], StreamChunk::Reasoning(t) if t == "Let me"));
+        assert!(matches!(&chunks[1], StreamChunk::Content(t) if t == "Sure"));
+        assert!(matches!(&chunks[2], StreamChunk::ToolCall { id, name, arguments }
+            if !id.is_empty() && name == "file_read" && arguments == r#"{"path":"a.rs"}"#));
+        assert!(matches!(&chunks[3], StreamChunk::FinishReason(r) if r == "tool_calls"));
+        assert!(matches!(chunks[4], StreamChunk::Usage(u)
+            if u.prompt_tokens == 12 && u.completion_tokens == 12 && u.reasoning_tokens == 4));
+    }
+
+    #[tokio::test]
+    async fn test_chat_complete_against_mock_server() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/models/gemini-test:generateContent")
+                .body_contains("maxOutputTokens");
+            then.status(200).json_body(json!({
+                "candidates": [{ "content": { "parts": [{ "text": "Hello" }] } }]
+            }));
+        });
+
+        let config = test_config(server.base_url());
+        let content = chat_complete(
+            &Client::new(),
+            &config,
+            vec![message("user", "hi")],
+            Vec::new(),
+            100,
+        )
+        .await
+        .unwrap();
+
+        mock.assert();
+        assert_eq!(content, "Hello");
+    }
+}
@@ -1,6 +1,8 @@
 mod accumulator;
+mod anthropic;
 mod client;
 mod executor;
+mod gemini;
 mod parser;
 mod stream;
 mod types;
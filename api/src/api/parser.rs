@@ -1,5 +1,7 @@
 use anyhow::Result;
 
+use history::TokenUsage;
+
 use super::types::{ChatResponse, StreamChunk};
 
 /// Parse a single SSE line (complete data)
@@ -87,6 +89,27 @@ pub fn parse_sse_message(data: &str) -> Option<Result<StreamChunk>> {
     None
 }
 
+/// Extract token usage from a SSE line, if it carries any
+///
+/// Usage can share a chunk with the finish reason, so it is parsed separately.
+pub fn parse_sse_usage(line: &str) -> Option<TokenUsage> {
+    let data = line.trim().strip_prefix("data: ")?.trim();
+    let usage = serde_json::from_str::<ChatResponse>(data).ok()?.usage?;
+    // OpenAI counts cache hits inside prompt_tokens, TokenUsage keeps them apart
+    let cached = usage.prompt_tokens_details.map(|d| d.cached_tokens).unwrap_or(0);
+
+    Some(TokenUsage {
+        prompt_tokens: usage.prompt_tokens.saturating_sub(cached),
+        cache_read_tokens: cached,
+        completion_tokens: usage.completion_tokens,
+        reasoning_tokens: usage
+            .completion_tokens_details
+            .map(|d| d.reasoning_tokens)
+            .unwrap_or(0),
+        ..TokenUsage::default()
+    })
+}
+
 /// Check if JSON structure is complete (brackets and quotes paired)
 pub fn is_json_structurally_complete(s: &str) -> bool {
     let mut braces = 0;
@@ -179,3 +202,23 @@ pub fn is_json_semantically_complete(tool_name: &str, arguments: &str) -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse_usage_cached_tokens() {
+        let line = r#"data: {"choices":[],"usage":{"prompt_tokens":100,"completion_tokens":20,"prompt_tokens_details":{"cached_tokens":60},"completion_tokens_details":{"reasoning_tokens":5}}}"#;
+        let usage = parse_sse_usage(line).unwrap();
+        assert_eq!(usage.prompt_tokens, 40);
+        assert_eq!(usage.cache_read_tokens, 60);
+        assert_eq!(usage.completion_tokens, 20);
+        assert_eq!(usage.reasoning_tokens, 5);
+
+        let line = r#"data: {"choices":[],"usage":{"prompt_tokens":100,"completion_tokens":20}}"#;
+        let usage = parse_sse_usage(line).unwrap();
+        assert_eq!(usage.prompt_tokens, 100);
+        assert_eq!(usage.cache_read_tokens, 0);
+    }
+}
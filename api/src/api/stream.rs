@@ -35,6 +35,8 @@ where
                 if !trimmed.is_empty() || self.buffer.is_empty() {
                     return Poll::Ready(Some(Ok(trimmed)));
                 }
+                // Blank separator line, keep draining the buffer before reading more
+                continue;
             }
 
             // Get next bytes from the stream
@@ -64,3 +66,21 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_events_in_one_chunk_are_split() {
+        let body = "data: a\n\ndata: b\n\ndata: c\n\n";
+        let inner = futures::stream::iter(vec![Ok(bytes::Bytes::from(body))]);
+        let lines: Vec<String> = SseLineStream::new(inner)
+            .map(|line| line.unwrap())
+            .filter(|line| futures::future::ready(!line.is_empty()))
+            .collect()
+            .await;
+        assert_eq!(lines, vec!["data: a", "data: b", "data: c"]);
+    }
+}
@@ -1,7 +1,12 @@
+use anyhow::Result;
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 
 use tools;
 
+/// Boxed stream of parsed chunks returned by every provider backend
+pub type ChunkStream = Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>;
+
 /// Chat request to be sent to the API
 #[derive(Debug, Serialize)]
 pub struct ChatRequest {
@@ -12,12 +17,47 @@ pub struct ChatRequest {
     pub stream: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+/// Streaming options (asks for a final usage chunk)
+#[derive(Debug, Serialize)]
+pub struct StreamOptions {
+    pub include_usage: bool,
 }
 
 /// Chat API response
 #[derive(Debug, Deserialize)]
 pub struct ChatResponse {
+    #[serde(default)]
     pub choices: Vec<Choice>,
+    pub usage: Option<Usage>,
+}
+
+/// Token usage in API response
+#[derive(Debug, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// Breakdown of prompt tokens
+#[derive(Debug, Deserialize)]
+pub struct PromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u64,
+}
+
+/// Breakdown of completion tokens
+#[derive(Debug, Deserialize)]
+pub struct CompletionTokensDetails {
+    #[serde(default)]
+    pub reasoning_tokens: u64,
 }
 
 /// Stream message for SSE responses (fields can be null)
@@ -88,6 +128,8 @@ pub enum StreamChunk {
     },
     /// Finish reason: stop, length, tool_calls, etc.
     FinishReason(String),
+    /// Token usage of the request
+    Usage(history::TokenUsage),
     /// Indicates stream is done
     Done,
 }
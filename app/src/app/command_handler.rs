@@ -1,3 +1,4 @@
+use super::duet;
 use super::notification;
 use super::startup::AppState;
 use anyhow::Result;
@@ -6,10 +7,19 @@ use chat;
 use commands;
 use history::Message;
 use security;
-use ui::get_i18n;
+use ui::{get_i18n, PlanDecision};
 use futures::future::BoxFuture;
 use tools::{HookType, execute_hook, HookContext};
 
+/// Sent to the model when the user approves the plan as written
+const PLAN_APPROVED: &str =
+    "The plan is approved and plan mode is off. Carry it out step by step and keep the todo list updated.";
+/// Sent with the user's edited version of the plan
+const PLAN_EDITED: &str =
+    "I edited the plan and approved my version, plan mode is off. Carry it out step by step and update the todo list to match:";
+/// Sent with the user's feedback, the model stays in plan mode
+const PLAN_REVISE: &str = "Revise the plan with this feedback and present the updated plan:";
+
 /// Handle user input and command processing
 pub async fn handle_user_input(line: &str, state: &mut AppState) -> Result<()> {
     // Handle commands
@@ -168,21 +178,71 @@ pub async fn handle_user_input(line: &str, state: &mut AppState) -> Result<()> {
     state.session.add_message(user_message);
 
     // Process chat and tool calls
-    if chat::run_agent_loop(
-        &state.api_client,
-        &state.config,
-        &mut state.session,
-        state.mcp_integration.as_ref(),
-        state.auto_approve,
-        None,
-    )
-    .await?
-    {
-        let _ = notification::notify_ai_completed().await;
-    }
+    run_agent_turn(state).await
+}
 
-    state.session.save()?;
-    Ok(())
+/// Run the agent on the session, in plan mode the user then reviews the plan
+async fn run_agent_turn(state: &mut AppState) -> Result<()> {
+    // Replies to a plan review continue the turn, they get no checkpoint of their own
+    let _checkpoint = chat::begin_checkpoint(&state.session);
+    loop {
+        let completed = if state.duet {
+            duet::run_duet_turn(
+                &state.api_client,
+                &state.config,
+                &mut state.session,
+                state.mcp_integration.as_ref(),
+                state.auto_approve,
+            )
+            .await?
+        } else {
+            chat::run_agent_loop(
+                &state.api_client,
+                &state.config,
+                &mut state.session,
+                state.mcp_integration.as_ref(),
+                state.auto_approve,
+                None,
+            )
+            .await?
+        };
+        if completed {
+            let _ = notification::notify_ai_completed().await;
+        }
+        state.session.save()?;
+
+        if !completed || !tools::is_plan_mode() {
+            return Ok(());
+        }
+        let plan = state
+            .session
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == "assistant")
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        let reply = match ui::prompt_plan_review(&plan)? {
+            PlanDecision::Approve => {
+                commands::switch_plan_mode(false, &get_i18n());
+                PLAN_APPROVED.to_string()
+            }
+            PlanDecision::Edit(edited) => {
+                commands::switch_plan_mode(false, &get_i18n());
+                format!("{}\n\n{}", PLAN_EDITED, edited.trim())
+            }
+            PlanDecision::Revise(feedback) => format!("{}\n\n{}", PLAN_REVISE, feedback.trim()),
+            PlanDecision::Reject => return Ok(()),
+        };
+
+        state.session.add_message(Message {
+            role: "user".to_string(),
+            content: reply,
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        });
+    }
 }
 
 /// Handle /agents.md command
@@ -200,19 +260,7 @@ async fn handle_agents_md_command(state: &mut AppState) -> Result<()> {
             state.session.add_message(analysis_message);
 
             // Auto-send to AI (same flow as normal user message)
-            if chat::run_agent_loop(
-                &state.api_client,
-                &state.config,
-                &mut state.session,
-                state.mcp_integration.as_ref(),
-                state.auto_approve,
-                None,
-            )
-            .await?
-            {
-                let _ = notification::notify_ai_completed().await;
-            }
-            state.session.save()?;
+            run_agent_turn(state).await?;
         }
         Err(e) => eprintln!("\n\x1b[31m[X] {}:\x1b[0m {}\n", state.i18n.get("error"), e),
     }
@@ -265,19 +313,7 @@ async fn handle_send_file_command(state: &mut AppState) -> Result<()> {
     state.session.add_message(file_message);
     
     // Process chat and tool calls
-    if chat::run_agent_loop(
-        &state.api_client,
-        &state.config,
-        &mut state.session,
-        state.mcp_integration.as_ref(),
-        state.auto_approve,
-        None,
-    )
-    .await?
-    {
-        let _ = notification::notify_ai_completed().await;
-    }
-    state.session.save()?;
+    run_agent_turn(state).await?;
     
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+use super::review::{parse_review_output, truncate};
+use anyhow::Result;
+use api::ApiClient;
+use chat;
+use colored::Colorize;
+use config::Config;
+use history::{ChatSession, Message};
+use mcp::McpIntegration;
+use ui::{self, Spinner};
+
+/// Longest diff sent to the critic
+const MAX_DIFF_CHARS: usize = 20_000;
+
+/// Longest final message sent to the critic
+const MAX_MESSAGE_CHARS: usize = 8_000;
+
+const CRITIC_SYSTEM_PROMPT: &str = "You are the critic in Friendev's duet mode. Another model (the coder) works on the user's request with tools, and after each of its turns you review the diff of the files it changed and its final message. Look for bugs, missed requirements, broken edge cases, missing tests and risky changes. Do not nitpick style or ask for work the user did not request. Reply strictly as a minified JSON object with two keys: \"details\" (string, your numbered objections, or a one-line summary when you have none, in the same language as the user request) and \"approval\" (boolean, true to sign off on the work, false if the coder must address the objections). Do not output markdown, code fences, additional keys, or commentary. Never call tools.";
+
+/// Run the agent on the session, then let the critic review each completed turn
+/// and send its objections back until it signs off or the round limit is hit
+///
+/// Returns the result of the last agent run.
+pub async fn run_duet_turn(
+    api_client: &ApiClient,
+    config: &Config,
+    session: &mut ChatSession,
+    mcp_integration: Option<&McpIntegration>,
+    auto_approve: bool,
+) -> Result<bool> {
+    let i18n = ui::get_i18n();
+    let critic = critic_client(config);
+    let max_rounds = config.duet.max_rounds.max(1);
+    let request = session
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "user")
+        .map(|m| m.content.clone())
+        .unwrap_or_default();
+    let mut critic_messages = vec![text_message("system", CRITIC_SYSTEM_PROMPT.to_string())];
+    // The whole exchange is one turn with one checkpoint, the critic's objections are
+    // added as user messages but don't start turns of their own
+    let _checkpoint = chat::begin_checkpoint(session);
+    let turn = tools::checkpoint::active_turn().unwrap_or_else(|| session.user_turns());
+
+    for round in 1..=max_rounds {
+        if !chat::run_agent_loop(api_client, config, session, mcp_integration, auto_approve, None).await? {
+            return Ok(false);
+        }
+
+        critic_messages.push(text_message("user", review_prompt(session, &request, round, max_rounds)));
+        let outcome = match review_turn(&critic, &critic_messages, session, turn).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!(
+                    "\n\x1b[33m[!] {}:\x1b[0m {}\n",
+                    i18n.get("duet_critic_failed"),
+                    e
+                );
+                return Ok(true);
+            }
+        };
+        critic_messages.push(text_message("assistant", outcome.raw));
+
+        if outcome.approval {
+            println!("\n  {} {}\n", "✓".green(), i18n.get("duet_signed_off"));
+            return Ok(true);
+        }
+
+        println!(
+            "\n{}",
+            i18n.get("duet_objections")
+                .replacen("{}", &round.to_string(), 1)
+                .replacen("{}", &max_rounds.to_string(), 1)
+                .yellow()
+        );
+        for line in outcome.details.trim().lines().filter(|l| !l.trim().is_empty()) {
+            println!("    {}", line.trim());
+        }
+        println!();
+
+        if round == max_rounds {
+            println!("\x1b[33m[!]\x1b[0m {}\n", i18n.get("duet_round_limit"));
+            break;
+        }
+
+        session.add_message(text_message(
+            "user",
+            format!(
+                "A critic model reviewed your last turn (round {} of {}). Address each objection, or explain briefly why it does not apply:\n\n{}",
+                round,
+                max_rounds,
+                outcome.details.trim()
+            ),
+        ));
+        session.save()?;
+    }
+
+    Ok(true)
+}
+
+/// Client for the critic: the duet model, else the Shorekeeper model, else the current model
+fn critic_client(config: &Config) -> ApiClient {
+    match config.duet.critic_model.as_ref().or(config.shorekeeper_model.as_ref()) {
+        Some(model) => ApiClient::new(config.resolve_model_ref(model)),
+        None => ApiClient::new(config.clone()),
+    }
+}
+
+/// The critic's view of a finished round: the request, the diff since the request and
+/// the final message
+fn review_prompt(session: &ChatSession, request: &str, round: usize, max_rounds: usize) -> String {
+    let diff = tools::checkpoint::list(&session.working_directory, &session.id.to_string())
+        .ok()
+        .and_then(|mut checkpoints| checkpoints.pop())
+        .and_then(|manifest| {
+            tools::checkpoint::diff(&session.working_directory, &session.id.to_string(), &manifest).ok()
+        })
+        .unwrap_or_default();
+    let diff = if diff.trim().is_empty() {
+        "(no files changed through the file tools)".to_string()
+    } else {
+        truncate(&diff, MAX_DIFF_CHARS).0
+    };
+
+    let final_message = session
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "assistant")
+        .map(|m| truncate(m.content.trim(), MAX_MESSAGE_CHARS).0)
+        .unwrap_or_default();
+
+    format!(
+        "Review round {} of {}.\n\n# User request\n{}\n\n# Diff since the request\n{}\n\n# Coder's final message\n{}",
+        round,
+        max_rounds,
+        truncate(request.trim(), MAX_MESSAGE_CHARS).0,
+        diff,
+        final_message
+    )
+}
+
+struct CriticOutcome {
+    approval: bool,
+    details: String,
+    /// Reply as sent, kept in the critic's history for the next round
+    raw: String,
+}
+
+async fn review_turn(critic: &ApiClient, messages: &[Message], session: &mut ChatSession, turn: usize) -> Result<CriticOutcome> {
+    let i18n = ui::get_i18n();
+    let mut spinner = Spinner::new();
+    spinner.render(&i18n.get("duet_review_wait"));
+
+    // Usage is recorded in the coder's session so /usage shows both models
+    let (response, tool_calls, _) = chat::send_and_receive(critic, messages.to_vec(), session, None, turn).await?;
+    if tool_calls.is_some() {
+        anyhow::bail!(i18n.get("approval_review_tool_error"));
+    }
+
+    let raw = response.content.trim().to_string();
+    let outcome = parse_review_output(&raw).map_err(|e| {
+        anyhow::anyhow!(i18n.get("approval_review_parse_error").replace("{}", &e))
+    })?;
+    Ok(CriticOutcome {
+        approval: outcome.approval,
+        details: outcome.details,
+        raw,
+    })
+}
+
+fn text_message(role: &str, content: String) -> Message {
+    Message {
+        role: role.to_string(),
+        content,
+        tool_calls: None,
+        tool_call_id: None,
+        name: None,
+    }
+}
@@ -0,0 +1,283 @@
+use super::duet;
+use super::review;
+use super::startup::{apply_approval_flags, apply_plan_flag, duet_flag};
+use super::stdout_redirect::StdoutToStderr;
+use anyhow::Result;
+use api::ApiClient;
+use config::{Config, PriceTable};
+use history::{ChatSession, Message};
+use mcp::McpIntegration;
+use std::env;
+use std::io::{self, IsTerminal, Read, Write};
+use std::time::Instant;
+use tools::tools::types::approve_action_for_session;
+use ui::events::{self, Event};
+use ui::get_i18n;
+
+/// Exit codes of headless mode
+pub const EXIT_SUCCESS: i32 = 0;
+/// The agent loop stopped on an API or tool error
+pub const EXIT_FAILURE: i32 = 1;
+/// Missing prompt, missing config or rejected input
+pub const EXIT_USAGE: i32 = 2;
+/// The agent finished but at least one action needed approval and was denied
+pub const EXIT_DENIED: i32 = 3;
+
+/// What a headless run writes to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Only the final assistant message
+    Text,
+    /// One JSON event per line, see `ui::events::Event`
+    StreamJson,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Self::Text),
+            "stream-json" => Some(Self::StreamJson),
+            _ => None,
+        }
+    }
+}
+
+/// Arguments of a headless run (`friendev -p "prompt"`)
+pub struct HeadlessArgs {
+    /// Prompt from the command line, read from stdin if None
+    pub prompt: Option<String>,
+    /// Actions approved up front, e.g. `file_write` or `run_command`
+    pub allow: Vec<String>,
+    /// Value of `--output-format`, validated when the run starts
+    pub output_format: String,
+}
+
+/// Parse `-p/--print [prompt]`, `--allow <actions>` and `--output-format <format>`
+///
+/// Returns None if Friendev should start the interactive REPL.
+pub fn parse_headless_args() -> Option<HeadlessArgs> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let index = args.iter().position(|a| a == "-p" || a == "--print")?;
+
+    // The next argument is the prompt unless it is another flag, `-` means stdin
+    let prompt = args
+        .get(index + 1)
+        .filter(|a| !a.starts_with("--") && a.as_str() != "-")
+        .cloned();
+
+    let mut allow = Vec::new();
+    for value in flag_values(&args, "--allow") {
+        allow.extend(
+            value
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty()),
+        );
+    }
+
+    let output_format = flag_values(&args, "--output-format")
+        .last()
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "text".to_string());
+
+    Some(HeadlessArgs {
+        prompt,
+        allow,
+        output_format,
+    })
+}
+
+/// Values of a flag given as `--flag value` or `--flag=value`
+pub(crate) fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    let mut values = Vec::new();
+    for (i, arg) in args.iter().enumerate() {
+        if arg == flag {
+            if let Some(value) = args.get(i + 1) {
+                values.push(value.as_str());
+            }
+        } else if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            values.push(value);
+        }
+    }
+    values
+}
+
+/// Run a single prompt to completion without a terminal and return the exit code
+///
+/// Progress output goes to stderr. Stdout only gets the final assistant message, or
+/// the NDJSON event stream with `--output-format stream-json`.
+pub async fn run_headless(args: HeadlessArgs) -> Result<i32> {
+    let started = Instant::now();
+    ui::set_headless_mode(true);
+    let i18n = get_i18n();
+
+    // Keep stdout clean for the answer while the agent prints its progress
+    let redirect = StdoutToStderr::begin();
+
+    let Some(format) = OutputFormat::from_name(&args.output_format) else {
+        eprintln!(
+            "\x1b[31m[X]\x1b[0m {}",
+            i18n.get("headless_bad_output_format")
+                .replace("{}", &args.output_format)
+        );
+        return Ok(EXIT_USAGE);
+    };
+    if format == OutputFormat::StreamJson {
+        events::set_event_sink(redirect.original_stdout());
+    }
+
+    let prompt = match args.prompt {
+        Some(prompt) => prompt,
+        None if !io::stdin().is_terminal() => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        }
+        None => String::new(),
+    };
+    if prompt.trim().is_empty() {
+        eprintln!("\x1b[31m[X]\x1b[0m {}", i18n.get("headless_no_prompt"));
+        emit_result(EXIT_USAGE, "", None, started);
+        return Ok(EXIT_USAGE);
+    }
+
+    if security::is_input_suspicious(&prompt) {
+        eprintln!(
+            "\x1b[31m[X] {}:\x1b[0m {}",
+            i18n.get("security_warning_label"),
+            i18n.get("security_forbidden_tokens")
+        );
+        emit_result(EXIT_USAGE, "", None, started);
+        return Ok(EXIT_USAGE);
+    }
+
+    // Setup is interactive, so it cannot run here
+    let config = match Config::load()? {
+        Some(config) => config,
+        None => {
+            eprintln!("\x1b[31m[X]\x1b[0m {}", i18n.get("headless_no_config"));
+            emit_result(EXIT_USAGE, "", None, started);
+            return Ok(EXIT_USAGE);
+        }
+    };
+
+    let auto_approve = apply_approval_flags();
+    apply_plan_flag();
+    for action in &args.allow {
+        approve_action_for_session(action);
+    }
+
+    let mut session = ChatSession::new(env::current_dir()?);
+    session.add_message(Message {
+        role: "user".to_string(),
+        content: prompt,
+        tool_calls: None,
+        tool_call_id: None,
+        name: None,
+    });
+    session.save()?;
+
+    events::emit(&Event::Start {
+        session_id: &session.id.to_string(),
+        model: &config.current_model,
+        working_directory: &session.working_directory.display().to_string(),
+    });
+
+    let api_client = ApiClient::new(config.clone());
+    review::install_review_handler(api_client.clone(), config.clone());
+
+    let mcp_integration = match McpIntegration::new().await {
+        Ok(integration) => Some(integration),
+        Err(e) => {
+            eprintln!(
+                "\x1b[33m[WARN]\x1b[0m {}: {}",
+                i18n.get("mcp_integration_failed"),
+                e
+            );
+            None
+        }
+    };
+
+    let result = if duet_flag() {
+        duet::run_duet_turn(&api_client, &config, &mut session, mcp_integration.as_ref(), auto_approve).await
+    } else {
+        chat::run_agent_loop(
+            &api_client,
+            &config,
+            &mut session,
+            mcp_integration.as_ref(),
+            auto_approve,
+            None,
+        )
+        .await
+    };
+    session.save()?;
+
+    let exit_code = match &result {
+        Ok(true) if ui::denied_approvals() > 0 => {
+            eprintln!(
+                "\x1b[33m[!]\x1b[0m {}",
+                i18n.get("headless_denied_summary")
+                    .replace("{}", &ui::denied_approvals().to_string())
+            );
+            EXIT_DENIED
+        }
+        Ok(true) => EXIT_SUCCESS,
+        Ok(false) => EXIT_FAILURE,
+        Err(e) => {
+            eprintln!("\x1b[31m[X] {}:\x1b[0m {}", i18n.get("error"), e);
+            EXIT_FAILURE
+        }
+    };
+
+    let answer = match result {
+        Ok(true) => session
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == "assistant")
+            .map(|m| m.content.trim())
+            .unwrap_or(""),
+        _ => "",
+    };
+
+    drop(redirect);
+    match format {
+        OutputFormat::Text if exit_code != EXIT_FAILURE => {
+            println!("{}", answer);
+            io::stdout().flush()?;
+        }
+        OutputFormat::Text => {}
+        OutputFormat::StreamJson => emit_result(exit_code, answer, Some(&session), started),
+    }
+
+    Ok(exit_code)
+}
+
+/// Emit the final `result` event with usage and cost of the session
+fn emit_result(exit_code: i32, text: &str, session: Option<&ChatSession>, started: Instant) {
+    let session_id = session.map(|s| s.id.to_string());
+    let usage = session.map(|s| s.total_usage()).unwrap_or_default();
+
+    // Cost is only reported when every model used has a price
+    let cost_usd = match (session, PriceTable::load()) {
+        (Some(session), Ok(prices)) => session.usage.iter().try_fold(0.0, |total, record| {
+            record.usage.cost(&prices, &record.model).map(|cost| total + cost)
+        }),
+        _ => None,
+    };
+
+    events::emit(&Event::Result {
+        success: exit_code == EXIT_SUCCESS,
+        exit_code,
+        text,
+        session_id: session_id.as_deref(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        denied_approvals: ui::denied_approvals(),
+        usage: serde_json::to_value(usage).unwrap_or_default(),
+        cost_usd,
+    });
+}
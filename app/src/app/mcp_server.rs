@@ -0,0 +1,172 @@
+use super::headless::{flag_values, EXIT_SUCCESS, EXIT_USAGE};
+use super::review;
+use super::stdout_redirect::StdoutToStderr;
+use anyhow::Result;
+use api::ApiClient;
+use config::{ApprovalPolicy, Config};
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, Content, Implementation, ListToolsResult,
+    PaginatedRequestParam, ServerCapabilities, ServerInfo,
+};
+use rmcp::service::{RequestContext, RoleServer};
+use rmcp::{ErrorData as McpError, ServerHandler, ServiceExt};
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tools::tools::definitions::get_builtin_tools;
+use tools::tools::types::approve_action_for_session;
+use ui::get_i18n;
+
+/// Tools that only make sense inside Friendev's own agent loop
+const INTERNAL_TOOLS: &[&str] = &["task"];
+
+/// Serves Friendev's builtin tools to MCP clients
+#[derive(Clone)]
+struct FriendevToolServer {
+    tools: Arc<Vec<rmcp::model::Tool>>,
+    working_dir: PathBuf,
+    require_approval: bool,
+}
+
+impl FriendevToolServer {
+    fn new(config: &Config, working_dir: PathBuf) -> Self {
+        let exposed = &config.mcp_server.tools;
+        let tools = get_builtin_tools()
+            .into_iter()
+            .filter(|t| !INTERNAL_TOOLS.contains(&t.function.name.as_str()))
+            .filter(|t| exposed.is_empty() || exposed.contains(&t.function.name))
+            .filter_map(|t| {
+                let schema = t.function.parameters.as_object()?.clone();
+                Some(rmcp::model::Tool::new(
+                    t.function.name,
+                    t.function.description,
+                    Arc::new(schema),
+                ))
+            })
+            .collect();
+
+        Self {
+            tools: Arc::new(tools),
+            working_dir,
+            require_approval: config.mcp_server.approval != ApprovalPolicy::Allow,
+        }
+    }
+}
+
+impl ServerHandler for FriendevToolServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "friendev".to_string(),
+                title: Some("Friendev".to_string()),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                icons: None,
+                website_url: None,
+            },
+            instructions: Some(
+                "Friendev's file editing, outline, index, search and git tools. \
+                 Paths are relative to the directory the server was started in."
+                    .to_string(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult::with_all_items(self.tools.as_ref().clone()))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.tools.iter().any(|t| t.name == request.name) {
+            return Err(McpError::invalid_params(
+                format!("unknown tool: {}", request.name),
+                None,
+            ));
+        }
+
+        let arguments = serde_json::Value::Object(request.arguments.unwrap_or_default());
+        let result = tools::execute_tool_with_mcp(
+            &request.name,
+            &arguments.to_string(),
+            &self.working_dir,
+            self.require_approval,
+            None,
+            None,
+        )
+        .await;
+
+        Ok(match result {
+            Ok(result) if result.success => {
+                CallToolResult::success(vec![Content::text(result.content())])
+            }
+            Ok(result) => CallToolResult::error(vec![Content::text(result.content())]),
+            Err(e) => CallToolResult::error(vec![Content::text(e.to_string())]),
+        })
+    }
+}
+
+/// Run `friendev mcp-serve`: serve the builtin tools over MCP stdio until the client
+/// disconnects, and return the exit code
+///
+/// Approvals follow `mcp_server.approval` in the config, `--yolo` and `--allow <actions>`
+/// override it for one run.
+pub async fn run_mcp_server() -> Result<i32> {
+    ui::set_headless_mode(true);
+    let i18n = get_i18n();
+
+    // Stdout carries the protocol, everything tools print goes to stderr
+    let redirect = StdoutToStderr::begin();
+
+    let Some(mut config) = Config::load()? else {
+        eprintln!("\x1b[31m[X]\x1b[0m {}", i18n.get("headless_no_config"));
+        return Ok(EXIT_USAGE);
+    };
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--yolo" || a == "--ally") {
+        config.mcp_server.approval = ApprovalPolicy::Allow;
+    }
+    for value in flag_values(&args, "--allow") {
+        config
+            .mcp_server
+            .allowed_actions
+            .extend(value.split(',').map(|a| a.trim().to_string()));
+    }
+
+    for action in &config.mcp_server.allowed_actions {
+        approve_action_for_session(action);
+    }
+    if config.mcp_server.approval == ApprovalPolicy::Review {
+        ui::set_smart_approval_mode(true);
+        review::install_review_handler(ApiClient::new(config.clone()), config.clone());
+    }
+
+    let server = FriendevToolServer::new(&config, env::current_dir()?);
+    eprintln!(
+        "\x1b[32m[OK]\x1b[0m {}",
+        i18n.get("mcp_serve_started")
+            .replacen("{}", &server.tools.len().to_string(), 1)
+            .replacen("{}", &format!("{:?}", config.mcp_server.approval).to_lowercase(), 1)
+    );
+
+    let service = match redirect.original_stdout_file() {
+        Some(stdout) => {
+            server
+                .serve((tokio::io::stdin(), tokio::fs::File::from_std(stdout)))
+                .await?
+        }
+        None => server.serve(rmcp::transport::stdio()).await?,
+    };
+    service.waiting().await?;
+
+    Ok(EXIT_SUCCESS)
+}
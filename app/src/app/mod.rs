@@ -1,4 +1,7 @@
 mod command_handler;
+mod duet;
+mod headless;
+mod mcp_server;
 mod notification;
 mod prompt_optimizer;
 mod reedline_config;
@@ -6,7 +9,10 @@ mod reedline_prompt;
 mod repl;
 mod review;
 mod startup;
+mod stdout_redirect;
 mod terminal_ui;
 
+pub use headless::{parse_headless_args, run_headless, HeadlessArgs};
+pub use mcp_server::run_mcp_server;
 pub use repl::run_repl;
 pub use startup::initialize_app;
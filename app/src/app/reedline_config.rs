@@ -6,6 +6,9 @@ use std::io;
 
 use super::reedline_prompt::FriendevPrompt;
 
+/// Internal marker added by the Shift+Tab keybinding
+const PLAN_MARKER: &str = "\x01PLAN\x01";
+
 /// Initialize reedline with custom configuration
 pub fn create_reedline() -> io::Result<Reedline> {
     // Create key bindings (Emacs-style with custom additions)
@@ -62,6 +65,19 @@ fn add_custom_keybindings(keybindings: &mut Keybindings) {
         ]),
     );
     
+    // Shift+Tab: Toggle plan mode, the typed text is kept
+    // (terminals report it as BackTab, with or without the Shift modifier)
+    for modifiers in [KeyModifiers::SHIFT, KeyModifiers::NONE] {
+        keybindings.add_binding(
+            modifiers,
+            KeyCode::BackTab,
+            ReedlineEvent::Multiple(vec![
+                ReedlineEvent::Edit(vec![EditCommand::InsertString(PLAN_MARKER.to_string())]),
+                ReedlineEvent::Submit,
+            ]),
+        );
+    }
+
     // Ctrl+Enter: Insert newline (another alternative)
     keybindings.add_binding(
         KeyModifiers::CONTROL,
@@ -89,6 +105,7 @@ pub fn create_prompt() -> FriendevPrompt {
 pub enum InputResult {
     Input(String),
     OptimizePrompt(String),  // Shift+Enter: optimize the current input
+    TogglePlanMode(String),  // Shift+Tab: toggle plan mode, keeps the current input
     CtrlC,
     CtrlD,
     Error(String),
@@ -97,6 +114,10 @@ pub enum InputResult {
 pub fn process_signal(signal: Signal) -> InputResult {
     match signal {
         Signal::Success(buffer) => {
+            if let Some(original) = buffer.strip_suffix(PLAN_MARKER) {
+                return InputResult::TogglePlanMode(original.to_string());
+            }
+
             // Check for optimization marker from Shift+Enter
             if let Some(original) = check_for_optimization(&buffer) {
                 if original.trim().is_empty() {
@@ -22,7 +22,11 @@ impl Prompt for FriendevPrompt {
     }
 
     fn render_prompt_indicator(&self, _prompt_mode: reedline::PromptEditMode) -> Cow<str> {
-        Cow::Owned(format!("\x1b[36m{}\x1b[0m ", self.prefix))
+        if tools::is_plan_mode() {
+            Cow::Owned(format!("\x1b[35mplan\x1b[0m \x1b[36m{}\x1b[0m ", self.prefix))
+        } else {
+            Cow::Owned(format!("\x1b[36m{}\x1b[0m ", self.prefix))
+        }
     }
 
     fn render_prompt_multiline_indicator(&self) -> Cow<str> {
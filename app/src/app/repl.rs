@@ -56,6 +56,16 @@ pub async fn run_repl(mut state: AppState) -> Result<()> {
                         }
                     }
                 }
+                InputResult::TogglePlanMode(original) => {
+                    last_ctrl_c = None;
+                    commands::switch_plan_mode(!tools::is_plan_mode(), &get_i18n());
+                    if !original.is_empty() {
+                        if let Err(e) = prefill_input(&mut line_editor, &original) {
+                            let i18n = get_i18n();
+                            eprintln!("\x1b[33m[!] {}:\x1b[0m {}\n", i18n.get("error"), e);
+                        }
+                    }
+                }
                 InputResult::CtrlC => {
                     let i18n = get_i18n();
                     let now = Instant::now();
@@ -53,9 +53,8 @@ pub fn install_review_handler(api_client: ApiClient, config: Config) {
         let (client, config_to_use) = match Config::load() {
             Ok(Some(loaded_config)) => {
                 if let Some(sk_model) = &loaded_config.shorekeeper_model {
-                    // Use shorekeeper specific model
-                    let mut sk_cfg = loaded_config.clone();
-                    sk_cfg.current_model = sk_model.clone();
+                    // Use shorekeeper specific model, optionally from another profile
+                    let sk_cfg = loaded_config.resolve_model_ref(sk_model);
                     (ApiClient::new(sk_cfg), loaded_config)
                 } else {
                     // Use current main model (freshly loaded)
@@ -173,14 +172,18 @@ async fn run_jury_review(
     let mut futures = Vec::new();
     for _ in 0..3 {
         let client = client.clone();
-        let session = session.clone(); // ChatSession is lightweight to clone? Actually it has fields, but we need it for api call context.
+        let mut session = session.clone(); // ChatSession is lightweight to clone? Actually it has fields, but we need it for api call context.
         // Actually we can reuse session but api client handles chat history locally? 
         // No, api client sends messages vector. We just need to send same messages.
         
         let msgs = vec![system_msg.clone(), message.clone()];
         
         futures.push(tokio::spawn(async move {
-            chat::send_and_receive(&client, msgs, &session, None).await
+            let turn = session.user_turns();
+            let result = chat::send_and_receive(&client, msgs, &mut session, None, turn).await;
+            // Usage shows up in the session the action belongs to
+            chat::defer_usage(&session);
+            result
         }));
     }
 
@@ -275,7 +278,7 @@ async fn run_review(
     spinner.render(&i18n.get("approval_review_wait"));
 
     let working_dir = env::current_dir().unwrap_or_else(|_| env::temp_dir());
-    let session = ChatSession::new(working_dir);
+    let mut session = ChatSession::new(working_dir);
 
     let (preview, truncated) = format_preview(request.preview.as_deref(), &i18n);
 
@@ -305,7 +308,11 @@ async fn run_review(
         name: None,
     });
 
-    let (response, tool_calls, _) = chat::send_and_receive(client, messages, &session, None).await?;
+    let turn = session.user_turns();
+    let result = chat::send_and_receive(client, messages, &mut session, None, turn).await;
+    // Usage shows up in the session the action belongs to
+    chat::defer_usage(&session);
+    let (response, tool_calls, _) = result?;
 
     if tool_calls.is_some() {
         anyhow::bail!(i18n.get("approval_review_tool_error"));
@@ -368,7 +375,7 @@ fn format_preview(preview: Option<&str>, i18n: &I18n) -> (String, bool) {
     }
 }
 
-fn truncate(text: &str, max_chars: usize) -> (String, bool) {
+pub(crate) fn truncate(text: &str, max_chars: usize) -> (String, bool) {
     if text.chars().count() <= max_chars {
         return (text.to_string(), false);
     }
@@ -378,9 +385,9 @@ fn truncate(text: &str, max_chars: usize) -> (String, bool) {
 }
 
 #[derive(Debug, Deserialize)]
-struct ReviewOutcome {
-    details: String,
-    approval: bool,
+pub(crate) struct ReviewOutcome {
+    pub(crate) details: String,
+    pub(crate) approval: bool,
 }
 
 #[derive(Clone)]
@@ -402,7 +409,7 @@ impl OwnedReviewRequest {
     }
 }
 
-fn parse_review_output(raw: &str) -> Result<ReviewOutcome, String> {
+pub(crate) fn parse_review_output(raw: &str) -> Result<ReviewOutcome, String> {
     if raw.is_empty() {
         return Err("empty output".to_string());
     }
@@ -19,28 +19,15 @@ pub struct AppState {
     pub api_client: ApiClient,
     pub mcp_integration: Option<McpIntegration>,
     pub auto_approve: bool,
+    /// A critic model reviews every completed turn (`--duet`)
+    pub duet: bool,
 }
 
 /// Initialize the application
 pub async fn initialize_app() -> Result<AppState> {
-    // Check for smart approval flags
-    let smart_approve = env::args().any(|arg| 
-        arg == "--shorekeeper" || 
-        arg == "--ally-but-i-dont-fully-trust" || 
-        arg == "--ew"
-    );
-
-    // Check for jury mode flag
-    let jury_mode = env::args().any(|arg| arg == "--jury");
-
-    if smart_approve {
-        ui::set_smart_approval_mode(true);
-    } else if jury_mode {
-        ui::set_jury_mode(true);
-    }
-
-    // Check for --ally or --yolo flag (disabled if smart approval or jury is active)
-    let auto_approve = !smart_approve && !jury_mode && env::args().any(|arg| arg == "--ally" || arg == "--yolo");
+    let auto_approve = apply_approval_flags();
+    apply_plan_flag();
+    let duet = duet_flag();
 
     // Check for --setup flag to force setup
     let force_setup = env::args().any(|arg| arg == "--setup");
@@ -135,6 +122,19 @@ pub async fn initialize_app() -> Result<AppState> {
     // Print welcome message
     prompts::print_welcome(&config, &i18n);
 
+    if duet {
+        let critic = config
+            .duet
+            .critic_model
+            .as_ref()
+            .or(config.shorekeeper_model.as_ref())
+            .unwrap_or(&config.current_model);
+        println!(
+            "\x1b[36m[DUET]\x1b[0m \x1b[2m{}\x1b[0m\n",
+            i18n.get("duet_enabled").replace("{}", critic)
+        );
+    }
+
     // Display MCP status if available
     if let Some(ref integration) = mcp_integration {
         mcp::display_mcp_status_sync_with_i18n(integration, &i18n);
@@ -147,9 +147,44 @@ pub async fn initialize_app() -> Result<AppState> {
         api_client,
         mcp_integration,
         auto_approve,
+        duet,
     })
 }
 
+/// Apply approval mode flags, returns whether tool calls are auto-approved
+pub(crate) fn apply_approval_flags() -> bool {
+    // Check for smart approval flags
+    let smart_approve = env::args().any(|arg| 
+        arg == "--shorekeeper" || 
+        arg == "--ally-but-i-dont-fully-trust" || 
+        arg == "--ew"
+    );
+
+    // Check for jury mode flag
+    let jury_mode = env::args().any(|arg| arg == "--jury");
+
+    if smart_approve {
+        ui::set_smart_approval_mode(true);
+    } else if jury_mode {
+        ui::set_jury_mode(true);
+    }
+
+    // Check for --ally or --yolo flag (disabled if smart approval or jury is active)
+    !smart_approve && !jury_mode && env::args().any(|arg| arg == "--ally" || arg == "--yolo")
+}
+
+/// Start in plan mode with `--plan`
+pub(crate) fn apply_plan_flag() {
+    if env::args().any(|arg| arg == "--plan") {
+        tools::set_plan_mode(true);
+    }
+}
+
+/// Whether `--duet` was given
+pub(crate) fn duet_flag() -> bool {
+    env::args().any(|arg| arg == "--duet")
+}
+
 fn check_outline_freshness(working_dir: &std::path::Path, i18n: &I18n) {
     // Simple check: if .friendev/index/outline.db exists, check git commits.
     // If not exists or > 15 commits diff, warn user.
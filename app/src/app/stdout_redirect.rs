@@ -0,0 +1,78 @@
+use std::io::{self, Write};
+
+/// Points the stdout file descriptor at stderr until dropped
+pub struct StdoutToStderr {
+    #[cfg(unix)]
+    saved: Option<i32>,
+}
+
+impl StdoutToStderr {
+    #[cfg(unix)]
+    pub fn begin() -> Self {
+        let _ = io::stdout().flush();
+        // SAFETY: dup/dup2 only duplicate file descriptors owned by this process
+        let saved = unsafe {
+            let saved = libc::dup(libc::STDOUT_FILENO);
+            if saved >= 0 && libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) >= 0 {
+                Some(saved)
+            } else {
+                if saved >= 0 {
+                    libc::close(saved);
+                }
+                None
+            }
+        };
+        Self { saved }
+    }
+
+    #[cfg(not(unix))]
+    pub fn begin() -> Self {
+        Self {}
+    }
+
+    /// Writer for the real stdout, which stays valid after the redirect ends
+    #[cfg(unix)]
+    pub fn original_stdout(&self) -> Box<dyn Write + Send> {
+        use std::os::unix::io::FromRawFd;
+
+        // SAFETY: the duplicated descriptor is owned by the returned File
+        match self.saved.map(|fd| unsafe { libc::dup(fd) }) {
+            Some(fd) if fd >= 0 => Box::new(unsafe { std::fs::File::from_raw_fd(fd) }),
+            _ => Box::new(io::stdout()),
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn original_stdout(&self) -> Box<dyn Write + Send> {
+        Box::new(io::stdout())
+    }
+
+    /// The real stdout as a file, None if it could not be redirected
+    #[cfg(unix)]
+    pub fn original_stdout_file(&self) -> Option<std::fs::File> {
+        use std::os::unix::io::FromRawFd;
+
+        // SAFETY: the duplicated descriptor is owned by the returned File
+        let fd = unsafe { libc::dup(self.saved?) };
+        (fd >= 0).then(|| unsafe { std::fs::File::from_raw_fd(fd) })
+    }
+
+    #[cfg(not(unix))]
+    pub fn original_stdout_file(&self) -> Option<std::fs::File> {
+        None
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StdoutToStderr {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        if let Some(saved) = self.saved.take() {
+            // SAFETY: `saved` is the descriptor duplicated in `begin`
+            unsafe {
+                libc::dup2(saved, libc::STDOUT_FILENO);
+                libc::close(saved);
+            }
+        }
+    }
+}
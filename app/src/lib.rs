@@ -1,3 +1,5 @@
 pub mod app;
 
-pub use app::{initialize_app, run_repl};
+pub use app::{
+    initialize_app, parse_headless_args, run_headless, run_mcp_server, run_repl, HeadlessArgs,
+};
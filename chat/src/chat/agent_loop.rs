@@ -1,12 +1,21 @@
 use anyhow::Result;
 use api::{ApiClient, CustomToolHandler};
+use futures::StreamExt;
+use std::path::Path;
 use config::Config;
 use history::{ChatSession, Message};
 use mcp::McpIntegration;
 use ui::get_i18n;
+use super::compaction;
 use super::message_builder;
 use super::send_receive;
+use super::stream_handler;
 use tools::ToolResult;
+use tokio::sync::Semaphore;
+
+/// Subagents running at once across every `task` call, subagents of subagents
+/// run in their parent's slot
+static SUBAGENT_SLOTS: Semaphore = Semaphore::const_new(tools::MAX_PARALLEL_SUBAGENTS);
 
 /// Run the agent loop: send message, handle tool calls, and repeat until done
 pub fn run_agent_loop<'a>(
@@ -18,12 +27,55 @@ pub fn run_agent_loop<'a>(
     subagent_type: Option<String>,
 ) -> futures::future::BoxFuture<'a, Result<bool>> {
     Box::pin(async move {
-        let mut messages = message_builder::build_messages_with_agents_md(session, config, mcp_integration, subagent_type.as_deref())?;
-        
-        // Define custom handler for "task" tool
+        // Top-level turns get a checkpoint, subagent writes are recorded into the parent's
+        let _checkpoint = if subagent_type.is_none() {
+            // Agent files edited since the last turn take effect from this one
+            tools::load_custom_agents(&session.working_directory);
+            begin_checkpoint(session)
+        } else {
+            None
+        };
+        // Usage is recorded under the checkpoint's turn, messages added since it began don't count
+        let turn = tools::checkpoint::active_turn().unwrap_or_else(|| session.user_turns());
+
+        // Nested subagents start from this loop's client, not the one restricted below
         let client_clone = api_client.clone();
         let config_clone = config.clone();
         let mcp_clone = mcp_integration.cloned();
+        let nested = subagent_type.is_some();
+        let nested_turn = std::sync::Arc::new(tokio::sync::Mutex::new(()));
+
+        // User-defined subagents bring their own tools, model and turn limit
+        let custom_agent = subagent_type.as_deref().and_then(tools::custom_agent);
+        let max_turns = custom_agent.as_ref().and_then(|agent| agent.max_turns);
+        let agent_config;
+        let agent_client;
+        let (api_client, config) = match custom_agent.as_ref().and_then(|agent| agent.model.as_ref()) {
+            Some(model) => {
+                agent_config = config.resolve_model_ref(model);
+                agent_client = ApiClient::new(agent_config.clone());
+                (&agent_client, &agent_config)
+            }
+            None => (api_client, config),
+        };
+
+        // Restricted subagents are only offered, and may only run, their own tools
+        let allowed_tools = match &custom_agent {
+            Some(agent) => agent.tools.clone(),
+            None => subagent_type.as_deref().and_then(tools::subagent_tools),
+        };
+        let restricted_client;
+        let api_client = match &allowed_tools {
+            Some(allowed) => {
+                restricted_client = api_client.clone().with_allowed_tools(allowed.clone());
+                &restricted_client
+            }
+            None => api_client,
+        };
+
+        let mut messages = message_builder::build_messages_with_agents_md(session, config, mcp_integration, subagent_type.as_deref(), custom_agent.as_ref())?;
+        
+        // Define custom handler for "task" tool
         
         let custom_handler: CustomToolHandler = Box::new(move |name, args, working_dir| {
             let client = client_clone.clone();
@@ -32,54 +84,88 @@ pub fn run_agent_loop<'a>(
             let working_dir = working_dir.to_path_buf();
             let name = name.to_string();
             let args = args.to_string();
+            let allowed = allowed_tools.clone();
+            let nested_turn = nested_turn.clone();
             
             Box::pin(async move {
+                if allowed.is_some_and(|allowed| !allowed.contains(&name)) {
+                    let i18n = get_i18n();
+                    return Ok(Some(ToolResult::error(i18n.get("subagent_tool_not_allowed").replace("{}", &name))));
+                }
                 if name != "task" {
                     return Ok(None);
                 }
                 
-                // Parse arguments
-                let args_json: serde_json::Value = serde_json::from_str(&args)?;
-                let _description = args_json.get("description").and_then(|v| v.as_str()).unwrap_or("Subtask");
-                let prompt = args_json.get("prompt").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing prompt"))?;
-                let subagent_type_str = args_json.get("subagent_type").and_then(|v| v.as_str()).unwrap_or("general");
-                
-                // Create sub-session
-                let mut sub_session = ChatSession::new(working_dir);
-                
-                // Add user message
-                sub_session.add_message(Message {
-                    role: "user".to_string(),
-                    content: prompt.to_string(),
-                    tool_calls: None,
-                    tool_call_id: None,
-                    name: None,
-                });
-                
-                println!("\n\x1b[36m🤖 Starting subagent: {}\x1b[0m", subagent_type_str);
-                
-                // Run loop (recursive)
-                // Inherit auto_approve status from parent session
-                let success = run_agent_loop(&client, &config, &mut sub_session, mcp.as_ref(), auto_approve, Some(subagent_type_str.to_string())).await?;
-                
-                // Extract result
-                let result_content = if success {
-                    sub_session.messages.iter().rev()
-                        .find(|m| m.role == "assistant")
-                        .map(|m| m.content.clone())
-                        .unwrap_or_else(|| "Subagent completed but returned no content.".to_string())
+                let tasks = parse_tasks(&serde_json::from_str(&args)?)?;
+                let total = tasks.len();
+                // A subagent waits for its own subagents, so they take turns in its slot
+                // instead of queueing for slots that may all be held by waiting parents
+                let _turn = if nested {
+                    Some(nested_turn.lock().await)
                 } else {
-                    "Subagent failed to complete the task.".to_string()
+                    stream_handler::reset_subagent_interrupt();
+                    None
                 };
-                
-                println!("\n\x1b[36m🤖 Subagent finished\x1b[0m");
-                
-                Ok(Some(ToolResult::ok(format!("Subagent '{}' completed", subagent_type_str), result_content)))
+                let limit = if nested { 1 } else { tools::MAX_PARALLEL_SUBAGENTS };
+                if total > 1 {
+                    let i18n = get_i18n();
+                    ui::output::println(&format!(
+                        "\n\x1b[36m🤖 {}\x1b[0m",
+                        i18n.get("subagents_running")
+                            .replacen("{}", &total.to_string(), 1)
+                            .replacen("{}", &limit.to_string(), 1)
+                    ));
+                }
+
+                // Independent tasks run side by side, each printing into its own buffer
+                let mut reports: Vec<(usize, String)> = futures::stream::iter(tasks.clone().into_iter().enumerate())
+                    .map(|(index, task)| {
+                        let label = if total > 1 {
+                            format!("Subagent {}/{} · {} · {}", index + 1, total, task.subagent_type, task.description)
+                        } else {
+                            format!("Subagent · {} · {}", task.subagent_type, task.description)
+                        };
+                        let run = run_subagent(&client, &config, mcp.as_ref(), &working_dir, auto_approve, label, task);
+                        async move {
+                            let _slot = if nested {
+                                None
+                            } else {
+                                Some(SUBAGENT_SLOTS.acquire().await.expect("subagent slots are never closed"))
+                            };
+                            (index, run.await)
+                        }
+                    })
+                    .buffer_unordered(limit)
+                    .collect()
+                    .await;
+                reports.sort_by_key(|(index, _)| *index);
+
+                if total == 1 {
+                    let (_, report) = reports.remove(0);
+                    return Ok(Some(ToolResult::ok(format!("Subagent '{}' completed", tasks[0].subagent_type), report)));
+                }
+                let combined = tasks
+                    .iter()
+                    .zip(&reports)
+                    .enumerate()
+                    .map(|(index, (task, (_, report)))| {
+                        format!("## Task {}/{}: {} ({})\n\n{}", index + 1, total, task.description, task.subagent_type, report.trim())
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                Ok(Some(ToolResult::ok(format!("{} subagents completed", total), combined)))
             })
         });
 
+        let mut turns = 0;
         loop {
-            match send_receive::send_and_receive(api_client, messages.clone(), session, mcp_integration).await {
+            // Summarize older turns before the context window overflows
+            if compaction::auto_compact(api_client, config, session, &messages, mcp_integration).await {
+                messages = message_builder::build_messages_with_agents_md(session, config, mcp_integration, subagent_type.as_deref(), custom_agent.as_ref())?;
+            }
+
+            turns += 1;
+            match send_receive::send_and_receive(api_client, messages.clone(), session, mcp_integration, turn).await {
                 Ok((response_msg, tool_calls, mut displays)) => {
                     session.add_message(response_msg);
                     
@@ -93,7 +179,7 @@ pub fn run_agent_loop<'a>(
                         } else {
                             msg.replace("{}", &e.to_string())
                         };
-                        eprintln!("\n\x1b[33m[!] {}\x1b[0m", msg);
+                        ui::output::eprintln(&format!("\n\x1b[33m[!] {}\x1b[0m", msg));
                     }
 
                     if let Some(calls) = tool_calls {
@@ -111,6 +197,10 @@ pub fn run_agent_loop<'a>(
                         for result in tool_results {
                             session.add_message(result);
                         }
+                        // Reviews and subagents of these calls ran in sessions of their own
+                        if !nested {
+                            send_receive::record_deferred_usage(session, turn);
+                        }
                         
                         // Save session immediately after tool execution results are added
                         if let Err(e) = session.save() {
@@ -121,11 +211,17 @@ pub fn run_agent_loop<'a>(
                             } else {
                                 msg.replace("{}", &e.to_string())
                             };
-                            eprintln!("\n\x1b[33m[!] {}\x1b[0m", msg);
+                            ui::output::eprintln(&format!("\n\x1b[33m[!] {}\x1b[0m", msg));
+                        }
+
+                        if let Some(limit) = max_turns.filter(|limit| turns >= *limit) {
+                            let i18n = get_i18n();
+                            ui::output::println(&format!("\n\x1b[33m[!] {}\x1b[0m", i18n.get("subagent_turn_limit").replace("{}", &limit.to_string())));
+                            return Ok(true);
                         }
 
                         // Rebuild messages with new history
-                        messages = message_builder::build_messages_with_agents_md(session, config, mcp_integration, subagent_type.as_deref())?;
+                        messages = message_builder::build_messages_with_agents_md(session, config, mcp_integration, subagent_type.as_deref(), custom_agent.as_ref())?;
                         continue;
                     }
 
@@ -133,7 +229,8 @@ pub fn run_agent_loop<'a>(
                 }
                 Err(e) => {
                     let i18n = get_i18n();
-                    eprintln!("\n\x1b[31m[X] {}:\x1b[0m {}\n", i18n.get("api_error"), e);
+                    ui::output::eprintln(&format!("\n\x1b[31m[X] {}:\x1b[0m {}\n", i18n.get("api_error"), e));
+                    ui::events::emit(&ui::events::Event::Error { message: &e.to_string() });
                     // Remove last message since no valid response
                     if !session.messages.is_empty() {
                         session.messages.pop();
@@ -144,3 +241,178 @@ pub fn run_agent_loop<'a>(
         }
     })
 }
+
+/// One subagent run requested by a `task` call
+#[derive(Clone)]
+struct TaskSpec {
+    description: String,
+    prompt: String,
+    subagent_type: String,
+}
+
+impl TaskSpec {
+    fn from_json(value: &serde_json::Value) -> Result<Self> {
+        let text = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        Ok(Self {
+            description: text("description").unwrap_or_else(|| "Subtask".to_string()),
+            prompt: text("prompt").ok_or_else(|| anyhow::anyhow!("Missing prompt"))?,
+            subagent_type: text("subagent_type").unwrap_or_else(|| "general".to_string()),
+        })
+    }
+}
+
+/// The tasks of a `task` call: its `tasks` list, else the single task in its own arguments
+fn parse_tasks(args: &serde_json::Value) -> Result<Vec<TaskSpec>> {
+    match args.get("tasks").and_then(|v| v.as_array()) {
+        Some(tasks) if !tasks.is_empty() => tasks.iter().map(TaskSpec::from_json).collect(),
+        _ => Ok(vec![TaskSpec::from_json(args)?]),
+    }
+}
+
+/// Run one subagent in a fresh session and return its report
+///
+/// Its output is buffered and printed as one collapsed section when it finishes, so
+/// subagents running side by side don't interleave. The full output is kept in
+/// `.friendev/runs/subagent-<id>.log`.
+async fn run_subagent(
+    client: &ApiClient,
+    config: &Config,
+    mcp_integration: Option<&McpIntegration>,
+    working_dir: &Path,
+    auto_approve: bool,
+    label: String,
+    task: TaskSpec,
+) -> String {
+    let mut sub_session = ChatSession::new(working_dir.to_path_buf());
+    sub_session.add_message(Message {
+        role: "user".to_string(),
+        content: task.prompt.clone(),
+        tool_calls: None,
+        tool_call_id: None,
+        name: None,
+    });
+
+    ui::output::println(&format!("\n\x1b[36m🤖 Starting subagent: {}\x1b[0m", task.subagent_type));
+
+    // Names the log file and tags the subagent's events
+    let task_id = format!("subagent-{}", &sub_session.id.simple().to_string()[..8]);
+
+    // Inherit auto_approve status from parent session
+    let (success, output) = ui::output::buffered(&task_id, run_agent_loop(
+        client,
+        config,
+        &mut sub_session,
+        mcp_integration,
+        auto_approve,
+        Some(task.subagent_type.clone()),
+    ))
+    .await;
+
+    send_receive::defer_usage(&sub_session);
+
+    let log_path = tools::background::log_path(working_dir, &task_id);
+    let saved = log_path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&log_path, ui::output::rendered_lines(&output).join("\n") + "\n"));
+    let footer = saved.ok().map(|_| get_i18n().get("subagent_output_saved").replace("{}", &log_path.display().to_string()));
+    let mark = if matches!(success, Ok(true)) { "✓" } else { "✗" };
+    ui::output::print_collapsed_section(&format!("{} {}", mark, label), &output, footer.as_deref());
+
+    let result_content = match success {
+        Ok(true) if sub_session.messages.last().is_some_and(|m| m.role == "tool") => {
+            // Stopped by its turn limit while still calling tools
+            let notes = sub_session.messages.iter().rev()
+                .find(|m| m.role == "assistant" && !m.content.trim().is_empty())
+                .map(|m| format!(" Its last notes:\n\n{}", m.content.trim()))
+                .unwrap_or_default();
+            format!("Subagent stopped at its turn limit before finishing.{}", notes)
+        }
+        Ok(true) => sub_session.messages.iter().rev()
+            .find(|m| m.role == "assistant")
+            .map(|m| m.content.clone())
+            .unwrap_or_else(|| "Subagent completed but returned no content.".to_string()),
+        Ok(false) => "Subagent failed to complete the task.".to_string(),
+        Err(e) => format!("Subagent failed to complete the task: {}", e),
+    };
+
+    // The investigator's report is all the parent needs
+    if tools::is_investigator(&task.subagent_type) {
+        condense_report(&result_content).to_string()
+    } else {
+        result_content
+    }
+}
+
+/// The report part of an investigator reply, from its `## Summary` heading on
+fn condense_report(content: &str) -> &str {
+    match content.find("## Summary") {
+        Some(start) => content[start..].trim_end(),
+        None => content.trim(),
+    }
+}
+
+/// Start the checkpoint of the user turn that was just added to the session
+///
+/// Does nothing while a turn is being recorded already, runs continuing that turn,
+/// like the one after a plan review, stay part of it.
+pub fn begin_checkpoint(session: &ChatSession) -> Option<tools::checkpoint::TurnGuard> {
+    if tools::checkpoint::active_turn().is_some() {
+        return None;
+    }
+    let prompt = session
+        .messages
+        .last()
+        .filter(|m| m.role == "user")
+        .map(|m| m.content.as_str())
+        .unwrap_or("");
+
+    match tools::checkpoint::begin_turn(
+        &session.working_directory,
+        &session.id.to_string(),
+        session.user_turns(),
+        session.messages.len().saturating_sub(1),
+        session.compacted_turns,
+        prompt,
+    ) {
+        Ok(guard) => Some(guard),
+        Err(e) => {
+            let i18n = get_i18n();
+            eprintln!("\n\x1b[33m[!] {}:\x1b[0m {}", i18n.get("checkpoint_create_failed"), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_condense_report_drops_preamble() {
+        let reply = "Let me summarize what I found.\n\n## Summary\nParsing happens in `src/parser.rs:10`.\n\n## Findings\n- x\n";
+        assert_eq!(
+            condense_report(reply),
+            "## Summary\nParsing happens in `src/parser.rs:10`.\n\n## Findings\n- x"
+        );
+        assert_eq!(condense_report("  no headings  "), "no headings");
+    }
+
+    #[test]
+    fn test_parse_tasks() {
+        let single = parse_tasks(&serde_json::json!({"description": "find a", "prompt": "Where is a?"})).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].subagent_type, "general");
+
+        let many = parse_tasks(&serde_json::json!({"tasks": [
+            {"description": "a", "prompt": "Check a", "subagent_type": "investigator"},
+            {"prompt": "Check b"}
+        ]}))
+        .unwrap();
+        assert_eq!(many.len(), 2);
+        assert_eq!(many[0].subagent_type, "investigator");
+        assert_eq!(many[1].description, "Subtask");
+
+        assert!(parse_tasks(&serde_json::json!({"tasks": [{"description": "no prompt"}]})).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::Result;
+use api::ApiClient;
+use config::Config;
+use history::{ChatSession, Message};
+use mcp::McpIntegration;
+use ui::get_i18n;
+
+/// Rough bytes-per-token ratio used for estimation (CJK text is 3 bytes per char)
+const BYTES_PER_TOKEN: usize = 4;
+
+/// Tokens added per message for role and formatting
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Tool output longer than this is truncated in the summarizer transcript, and in
+/// kept turns when there is nothing old enough to summarize
+const MAX_TOOL_OUTPUT_CHARS: usize = 2000;
+
+/// Appended where `truncate` cut text off
+const TRUNCATION_MARK: &str = "... [truncated]";
+
+/// Upper bound for the generated summary
+const SUMMARY_MAX_TOKENS: u32 = 4096;
+
+/// Prefix of the message that replaces compacted turns
+const SUMMARY_HEADER: &str = "[Summary of earlier conversation]";
+
+/// Result of a compaction run
+pub struct CompactionStats {
+    pub messages_compacted: usize,
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+}
+
+/// Estimate the token count of a message sequence
+pub fn estimate_tokens(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|m| {
+            let mut bytes = m.content.len();
+            for tc in m.tool_calls.iter().flatten() {
+                bytes += tc.function.name.len() + tc.function.arguments.len();
+            }
+            bytes / BYTES_PER_TOKEN + MESSAGE_OVERHEAD_TOKENS
+        })
+        .sum()
+}
+
+/// Estimate the token count of the tool definitions sent along with the messages
+pub fn estimate_tool_tokens(tools: &[tools::Tool]) -> usize {
+    serde_json::to_string(tools).map_or(0, |json| json.len()) / BYTES_PER_TOKEN
+}
+
+/// Whether the outgoing messages and tool definitions are close enough to the
+/// context window to compact
+pub fn should_compact(messages: &[Message], tools: &[tools::Tool], config: &Config) -> bool {
+    let settings = &config.compaction;
+    let limit = (settings.context_window as f32 * settings.threshold) as usize;
+    settings.enabled && estimate_tokens(messages) + estimate_tool_tokens(tools) >= limit
+}
+
+/// Index of the first message kept verbatim
+///
+/// Splitting only at user messages keeps every assistant tool call together with
+/// its tool results. At least the current turn is always kept, and nothing is
+/// compacted while there are no more turns than `keep_recent_turns`.
+fn split_point(messages: &[Message], keep_recent_turns: usize) -> usize {
+    let user_turns: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.role == "user")
+        .map(|(i, _)| i)
+        .collect();
+
+    let keep = keep_recent_turns.max(1);
+    if user_turns.len() > keep {
+        user_turns[user_turns.len() - keep]
+    } else {
+        0
+    }
+}
+
+/// Cut long tool results down to `MAX_TOOL_OUTPUT_CHARS`, except those of the latest
+/// tool calls, which the model has yet to read
+///
+/// Returns how many results were cut.
+fn trim_tool_results(messages: &mut [Message]) -> usize {
+    let latest = messages
+        .iter()
+        .rposition(|m| m.role == "assistant" && m.tool_calls.is_some())
+        .unwrap_or(0);
+    let mut trimmed = 0;
+    for msg in messages[..latest].iter_mut().filter(|m| m.role == "tool") {
+        if msg.content.chars().count() > MAX_TOOL_OUTPUT_CHARS && !msg.content.ends_with(TRUNCATION_MARK) {
+            msg.content = truncate(&msg.content, MAX_TOOL_OUTPUT_CHARS);
+            trimmed += 1;
+        }
+    }
+    trimmed
+}
+
+/// Render messages as plain text for the summarizer
+fn render_transcript(messages: &[Message]) -> String {
+    let mut transcript = String::new();
+
+    for msg in messages {
+        match msg.role.as_str() {
+            "system" => {
+                let summary = msg.content.trim_start_matches(SUMMARY_HEADER).trim();
+                transcript.push_str(&format!("[Earlier summary]\n{}\n\n", summary));
+            }
+            "assistant" => {
+                if !msg.content.trim().is_empty() {
+                    transcript.push_str(&format!("[Assistant]\n{}\n\n", msg.content.trim()));
+                }
+                for tc in msg.tool_calls.iter().flatten() {
+                    transcript.push_str(&format!(
+                        "[Assistant called {}] {}\n\n",
+                        tc.function.name,
+                        truncate(&tc.function.arguments, MAX_TOOL_OUTPUT_CHARS)
+                    ));
+                }
+            }
+            "tool" => {
+                transcript.push_str(&format!(
+                    "[Result of {}]\n{}\n\n",
+                    msg.name.as_deref().unwrap_or("tool"),
+                    truncate(&msg.content, MAX_TOOL_OUTPUT_CHARS)
+                ));
+            }
+            _ => {
+                transcript.push_str(&format!("[User]\n{}\n\n", msg.content.trim()));
+            }
+        }
+    }
+
+    transcript
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(max_chars).collect();
+    format!("{}{}", truncated, TRUNCATION_MARK)
+}
+
+/// Summarize older turns of the session and replace them with the summary
+///
+/// When no turn is old enough, long tool results of the kept turns are cut instead,
+/// a single agentic turn can fill the window on its own. Returns None if there is
+/// nothing to compact. The session is saved after a successful compaction.
+pub async fn compact_session(
+    api_client: &ApiClient,
+    config: &Config,
+    session: &mut ChatSession,
+) -> Result<Option<CompactionStats>> {
+    let split = split_point(&session.messages, config.compaction.keep_recent_turns);
+    let older = &session.messages[..split];
+
+    // A lone previous summary has nothing new to fold in
+    if older.iter().all(|m| m.role == "system") {
+        let tokens_before = estimate_tokens(&session.messages);
+        let trimmed = trim_tool_results(&mut session.messages[split..]);
+        if trimmed == 0 {
+            return Ok(None);
+        }
+        session.save()?;
+        return Ok(Some(CompactionStats {
+            messages_compacted: trimmed,
+            tokens_before,
+            tokens_after: estimate_tokens(&session.messages),
+        }));
+    }
+
+    let summarizer = match &config.compaction.summarizer_model {
+        Some(reference) => ApiClient::new(config.resolve_model_ref(reference)),
+        None => api_client.clone(),
+    };
+
+    let request = vec![
+        Message {
+            role: "system".to_string(),
+            content: prompts::get_compaction_prompt(&config.ai_language),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        },
+        Message {
+            role: "user".to_string(),
+            content: render_transcript(older),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        },
+    ];
+
+    let summary = summarizer
+        .complete(request, Vec::new(), SUMMARY_MAX_TOKENS)
+        .await?;
+    if summary.trim().is_empty() {
+        anyhow::bail!("summarizer returned an empty response");
+    }
+
+    let tokens_before = estimate_tokens(&session.messages);
+    let compacted_turns = older.iter().filter(|m| m.role == "user").count();
+
+    let mut messages = vec![Message {
+        role: "system".to_string(),
+        content: format!("{}\n\n{}", SUMMARY_HEADER, summary.trim()),
+        tool_calls: None,
+        tool_call_id: None,
+        name: None,
+    }];
+    messages.extend(session.messages.drain(split..));
+
+    session.messages = messages;
+    session.compacted_turns += compacted_turns;
+    session.save()?;
+
+    Ok(Some(CompactionStats {
+        messages_compacted: split,
+        tokens_before,
+        tokens_after: estimate_tokens(&session.messages),
+    }))
+}
+
+/// Compact the session if the outgoing messages are close to the context window
+///
+/// Returns true if the session changed and the messages need to be rebuilt.
+pub async fn auto_compact(
+    api_client: &ApiClient,
+    config: &Config,
+    session: &mut ChatSession,
+    messages: &[Message],
+    mcp_integration: Option<&McpIntegration>,
+) -> bool {
+    if !should_compact(messages, &api_client.offered_tools(mcp_integration), config) {
+        return false;
+    }
+
+    let i18n = get_i18n();
+    ui::output::println(&format!("\n\x1b[36m[*] {}\x1b[0m", i18n.get("compact_running")));
+
+    match compact_session(api_client, config, session).await {
+        Ok(Some(stats)) => {
+            print_stats(&stats);
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            ui::output::eprintln(&format!(
+                "\n\x1b[33m[!] {}:\x1b[0m {}",
+                i18n.get("compact_failed"),
+                e
+            ));
+            false
+        }
+    }
+}
+
+/// Print the result of a compaction run
+pub fn print_stats(stats: &CompactionStats) {
+    let i18n = get_i18n();
+    ui::output::println(&format!(
+        "\x1b[32m[OK]\x1b[0m {}\n",
+        i18n.get("compact_done")
+            .replacen("{}", &stats.messages_compacted.to_string(), 1)
+            .replacen("{}", &stats.tokens_before.to_string(), 1)
+            .replacen("{}", &stats.tokens_after.to_string(), 1)
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use history::{FunctionCall, ToolCall};
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        }
+    }
+
+    #[test]
+    fn test_split_point_keeps_tool_pairs() {
+        let mut assistant = message("assistant", "");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call_1".to_string(),
+            tool_type: "function".to_string(),
+            function: FunctionCall {
+                name: "file_read".to_string(),
+                arguments: "{}".to_string(),
+            },
+        }]);
+
+        let messages = vec![
+            message("user", "first"),
+            assistant,
+            message("tool", "content"),
+            message("assistant", "done"),
+            message("user", "second"),
+            message("assistant", "ok"),
+            message("user", "third"),
+        ];
+
+        assert_eq!(split_point(&messages, 2), 4);
+        // No more turns than requested leaves everything in place
+        assert_eq!(split_point(&messages, 3), 0);
+        assert_eq!(split_point(&messages, 10), 0);
+        assert_eq!(split_point(&[message("assistant", "hi")], 2), 0);
+    }
+
+    #[test]
+    fn test_trim_tool_results() {
+        let mut assistant = message("assistant", "");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call_1".to_string(),
+            tool_type: "function".to_string(),
+            function: FunctionCall {
+                name: "file_read".to_string(),
+                arguments: "{}".to_string(),
+            },
+        }]);
+        let long = "x".repeat(MAX_TOOL_OUTPUT_CHARS + 1);
+
+        let mut messages = vec![
+            message("user", "only turn"),
+            assistant.clone(),
+            message("tool", &long),
+            message("tool", "short"),
+            assistant,
+            message("tool", &long),
+        ];
+        assert_eq!(trim_tool_results(&mut messages), 1);
+        assert!(messages[2].content.ends_with(TRUNCATION_MARK));
+        assert_eq!(messages[3].content, "short");
+        // The latest results are still unread
+        assert_eq!(messages[5].content, long);
+        // Cut results aren't cut again
+        assert_eq!(trim_tool_results(&mut messages), 0);
+    }
+}
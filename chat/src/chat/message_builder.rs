@@ -5,11 +5,14 @@ use prompts;
 
 /// Build message sequence with SYSTEM prompt and history
 /// AGENTS.md is integrated in the system prompt (loaded in real-time)
+///
+/// `custom_agent` is the definition of a user-defined `subagent_type`, loaded once per run.
 pub fn build_messages_with_agents_md(
     session: &ChatSession,
     config: &Config,
     mcp_integration: Option<&mcp::McpIntegration>,
     subagent_type: Option<&str>,
+    custom_agent: Option<&config::AgentDefinition>,
 ) -> Result<Vec<Message>> {
     let system_prompt = if let Some(type_) = subagent_type {
         prompts::get_subagent_system_prompt(
@@ -18,6 +21,7 @@ pub fn build_messages_with_agents_md(
             &session.working_directory,
             mcp_integration,
             type_,
+            custom_agent,
         )
     } else {
         prompts::get_system_prompt(
@@ -28,6 +32,12 @@ pub fn build_messages_with_agents_md(
         )
     };
 
+    let system_prompt = if tools::is_plan_mode() {
+        format!("{}{}", system_prompt, prompts::get_plan_mode_prompt())
+    } else {
+        system_prompt
+    };
+
     let mut messages = vec![Message {
         role: "system".to_string(),
         content: system_prompt,
@@ -2,8 +2,9 @@ mod output_formatter;
 mod send_receive;
 mod stream_handler;
 pub mod message_builder;
+pub mod compaction;
 pub mod agent_loop;
 
 // Re-export public API
-pub use send_receive::send_and_receive;
-pub use agent_loop::run_agent_loop;
+pub use send_receive::{defer_usage, send_and_receive};
+pub use agent_loop::{begin_checkpoint, run_agent_loop};
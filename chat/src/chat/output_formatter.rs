@@ -1,12 +1,10 @@
-use std::io::{self, Write};
 use ui::{enhanced_output, get_i18n};
 
 /// Handle content output
 pub fn print_content(text: &str, has_reasoning: &mut bool) -> std::io::Result<()> {
     // If there was reasoning before, reset and add spacing
     if *has_reasoning {
-        print!("\n\n");
-        io::stdout().flush()?;
+        ui::output::print("\n\n");
         *has_reasoning = false;
     }
     enhanced_output::print_content(text)
@@ -34,17 +32,15 @@ pub fn print_ai_prefix() -> std::io::Result<()> {
 
 /// Print tool call separator
 pub fn print_tool_call_separator() -> std::io::Result<()> {
-    println!();
+    ui::output::println("");
     Ok(())
 }
 
 /// Finalize output formatting
 pub fn finalize_output(has_reasoning: bool, content_empty: bool) -> std::io::Result<()> {
     // Ensure proper newline at the end
-    if has_reasoning {
-        println!();
-    } else if !content_empty {
-        println!();
+    if has_reasoning || !content_empty {
+        ui::output::println("");
     }
     Ok(())
 }
@@ -3,15 +3,36 @@ use super::stream_handler;
 use anyhow::Result;
 use api::ApiClient;
 use history::{ChatSession, Message};
+use history::TokenUsage;
 use std::collections::HashMap;
+use std::sync::Mutex;
 use ui::ToolCallDisplay;
 
-/// Send messages to AI and receive response
+/// Usage of model calls made in throwaway sessions, like approval reviews and subagents,
+/// waiting to be recorded in the session they served
+static DEFERRED_USAGE: Mutex<Vec<(String, TokenUsage)>> = Mutex::new(Vec::new());
+
+/// Queue the usage recorded in a throwaway session for the main session
+pub fn defer_usage(session: &ChatSession) {
+    let records = session.usage.iter().map(|record| (record.model.clone(), record.usage));
+    DEFERRED_USAGE.lock().unwrap().extend(records);
+}
+
+/// Record the queued usage in `session` under user turn `turn`
+pub fn record_deferred_usage(session: &mut ChatSession, turn: usize) {
+    let deferred = std::mem::take(&mut *DEFERRED_USAGE.lock().unwrap());
+    for (model, usage) in deferred {
+        session.record_usage(turn, &model, usage);
+    }
+}
+
+/// Send messages to AI and receive response, its usage is recorded under user turn `turn`
 pub async fn send_and_receive(
     client: &ApiClient,
     messages: Vec<Message>,
-    _session: &ChatSession,
+    session: &mut ChatSession,
     mcp_integration: Option<&mcp::McpIntegration>,
+    turn: usize,
 ) -> Result<(
     Message,
     Option<Vec<history::ToolCall>>,
@@ -21,8 +42,12 @@ pub async fn send_and_receive(
     let stream = client.chat_stream_with_retry(messages, mcp_integration).await?;
 
     // Handle stream chunks (with ESC interruption support)
-    let (content, tool_accumulator, has_tool_calls, interrupted) =
+    let (content, tool_accumulator, has_tool_calls, interrupted, usage) =
         stream_handler::handle_stream_chunks(stream).await?;
+
+    if let Some(usage) = usage {
+        session.record_usage(turn, client.model(), usage);
+    }
     
     // If interrupted, return empty response
     if interrupted {
@@ -3,37 +3,57 @@ use anyhow::Result;
 use api::{StreamChunk, ToolCallAccumulator};
 use crossterm::event::{poll, read, Event, KeyCode};
 use futures::StreamExt;
+use history::TokenUsage;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
+use ui::events;
+
+/// Set when ESC stopped a buffered subagent, the subagents running beside it stop too
+static SUBAGENTS_INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Let the subagents about to start run until ESC is pressed again
+pub fn reset_subagent_interrupt() {
+    SUBAGENTS_INTERRUPTED.store(false, Ordering::Relaxed);
+}
 
 /// Process stream chunks and handle output with ESC key interruption support
 pub async fn handle_stream_chunks(
     stream: impl futures::Stream<Item = Result<StreamChunk>> + Unpin,
-) -> Result<(String, ToolCallAccumulator, bool, bool)> {
+) -> Result<(String, ToolCallAccumulator, bool, bool, Option<TokenUsage>)> {
     let mut stream = Box::pin(stream);
 
     let mut content = String::new();
     let mut tool_accumulator = ToolCallAccumulator::new();
     let mut has_tool_calls = false;
     let mut interrupted = false;
+    let mut usage = None;
 
     let mut is_first_reasoning = true;
     let mut has_reasoning = false;
 
     output_formatter::print_ai_prefix()?;
+    let is_subagent = ui::output::is_buffered();
 
     while let Some(chunk_result) = stream.next().await {
-        // Check for ESC key press (non-blocking)
-        if check_interrupt()? {
+        // Check for ESC key press (non-blocking), there is no keyboard without a terminal.
+        // Subagents running side by side steal each other's key presses, so one ESC stops them all
+        let stopped_beside = is_subagent && SUBAGENTS_INTERRUPTED.load(Ordering::Relaxed);
+        if stopped_beside || (!ui::is_headless_mode() && check_interrupt()?) {
+            if is_subagent {
+                SUBAGENTS_INTERRUPTED.store(true, Ordering::Relaxed);
+            }
             interrupted = true;
-            println!("\n\n\x1b[33m⚠ 已停止生成\x1b[0m\n");
+            ui::output::println("\n\n\x1b[33m⚠ 已停止生成\x1b[0m\n");
             break;
         }
         match chunk_result? {
             StreamChunk::Content(text) => {
+                events::emit(&events::Event::Text { text: &text });
                 output_formatter::print_content(&text, &mut has_reasoning)?;
                 content.push_str(&text);
             }
             StreamChunk::Reasoning(text) => {
+                events::emit(&events::Event::Reasoning { text: &text });
                 output_formatter::print_reasoning(
                     &text,
                     &mut is_first_reasoning,
@@ -47,7 +67,7 @@ pub async fn handle_stream_chunks(
             } => {
                 // If there was reasoning before, reset color and newline
                 if has_reasoning {
-                    print!("\x1b[0m\n\n");
+                    ui::output::print("\x1b[0m\n\n");
                     has_reasoning = false;
                 }
                 if !has_tool_calls {
@@ -61,6 +81,10 @@ pub async fn handle_stream_chunks(
                 // Record finish reason
                 tool_accumulator.set_finish_reason(reason);
             }
+            StreamChunk::Usage(reported) => {
+                // Some servers report cumulative usage on every chunk, keep the latest
+                usage = Some(reported);
+            }
             StreamChunk::Done => break,
         }
     }
@@ -70,7 +94,7 @@ pub async fn handle_stream_chunks(
         output_formatter::finalize_output(has_reasoning, content.is_empty())?;
     }
 
-    Ok((content, tool_accumulator, has_tool_calls, interrupted))
+    Ok((content, tool_accumulator, has_tool_calls, interrupted, usage))
 }
 
 /// Check if ESC key is pressed (non-blocking)
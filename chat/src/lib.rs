@@ -1,3 +1,3 @@
 pub mod chat;
 
-pub use chat::{send_and_receive, run_agent_loop};
+pub use chat::{begin_checkpoint, compaction, defer_usage, send_and_receive, run_agent_loop};
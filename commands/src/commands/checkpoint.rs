@@ -0,0 +1,114 @@
+use anyhow::Result;
+
+use ::history::ChatSession;
+use i18n::I18n;
+use tools::checkpoint;
+
+/// Handle /checkpoint command
+pub fn handle_checkpoint_command(
+    parts: &[&str],
+    session: &mut ChatSession,
+    i18n: &I18n,
+) -> Result<()> {
+    match parts.get(1) {
+        Some(&"list") | None => {
+            let checkpoints =
+                checkpoint::list(&session.working_directory, &session.id.to_string())?;
+
+            if checkpoints.is_empty() {
+                println!("\n\x1b[90m[i] {}\x1b[0m\n", i18n.get("checkpoint_none"));
+                return Ok(());
+            }
+
+            println!("\n\x1b[1;33m{}:\x1b[0m", i18n.get("checkpoint_title"));
+            for cp in &checkpoints {
+                println!(
+                    "  \x1b[36m#{:<4}\x1b[0m \x1b[90m{}\x1b[0m  {} \x1b[90m({} {})\x1b[0m",
+                    cp.turn,
+                    cp.created_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S"),
+                    cp.prompt,
+                    cp.files.len(),
+                    i18n.get("checkpoint_files")
+                );
+            }
+            println!();
+        }
+        Some(&"restore") => match parts.get(2).and_then(|n| n.parse::<usize>().ok()) {
+            Some(turn) => restore_turn(session, turn, i18n)?,
+            None => {
+                println!(
+                    "\n\x1b[33m[!] {}:\x1b[0m /checkpoint restore <n>\n",
+                    i18n.get("usage")
+                );
+            }
+        },
+        _ => {
+            println!("\n\x1b[33m[?] {}:\x1b[0m", i18n.get("usage"));
+            println!(
+                "    \x1b[36m/checkpoint\x1b[0m list         {}",
+                i18n.get("cmd_checkpoint_list")
+            );
+            println!(
+                "    \x1b[36m/checkpoint\x1b[0m restore <n>  {}",
+                i18n.get("cmd_checkpoint_restore")
+            );
+            println!(
+                "    \x1b[36m/undo\x1b[0m                    {}\n",
+                i18n.get("cmd_undo")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Handle /undo command: roll back the most recent turn
+pub fn handle_undo_command(session: &mut ChatSession, i18n: &I18n) -> Result<()> {
+    let checkpoints = checkpoint::list(&session.working_directory, &session.id.to_string())?;
+
+    match checkpoints.last() {
+        Some(latest) => restore_turn(session, latest.turn, i18n),
+        None => {
+            println!("\n\x1b[90m[i] {}\x1b[0m\n", i18n.get("checkpoint_none"));
+            Ok(())
+        }
+    }
+}
+
+/// Restore files and session messages to the state before `turn`
+fn restore_turn(session: &mut ChatSession, turn: usize, i18n: &I18n) -> Result<()> {
+    let manifest = match checkpoint::restore(
+        &session.working_directory,
+        &session.id.to_string(),
+        turn,
+    ) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            println!("\n\x1b[31m[X] {}:\x1b[0m {}\n", i18n.get("error"), e);
+            return Ok(());
+        }
+    };
+
+    // Message indices shift when older turns are compacted after the checkpoint
+    if manifest.compacted_turns == session.compacted_turns
+        && manifest.message_count <= session.messages.len()
+    {
+        session.messages.truncate(manifest.message_count);
+        session.updated_at = chrono::Utc::now();
+        session.save()?;
+    } else {
+        println!(
+            "\n\x1b[33m[!] {}\x1b[0m",
+            i18n.get("checkpoint_messages_kept")
+        );
+    }
+
+    println!(
+        "\n\x1b[32m[OK]\x1b[0m {}\n",
+        i18n.get("checkpoint_restored")
+            .replacen("{}", &turn.to_string(), 1)
+            .replacen("{}", &manifest.files.len().to_string(), 1)
+    );
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+
+use ::history::ChatSession;
+use api::ApiClient;
+use chat::compaction;
+use config::Config;
+use i18n::I18n;
+
+/// Handle /compact command
+pub async fn handle_compact_command(
+    config: &Config,
+    session: &mut ChatSession,
+    api_client: &ApiClient,
+    i18n: &I18n,
+) -> Result<()> {
+    println!("\n\x1b[36m[*] {}\x1b[0m", i18n.get("compact_running"));
+
+    match compaction::compact_session(api_client, config, session).await {
+        Ok(Some(stats)) => compaction::print_stats(&stats),
+        Ok(None) => println!("\n\x1b[90m[i] {}\x1b[0m\n", i18n.get("compact_nothing")),
+        Err(e) => println!(
+            "\n\x1b[31m[X] {}:\x1b[0m {}\n",
+            i18n.get("compact_failed"),
+            e
+        ),
+    }
+
+    Ok(())
+}
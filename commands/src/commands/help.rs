@@ -21,6 +21,33 @@ pub fn print_help(i18n: &I18n) {
         i18n.get("cmd_model_switch").dimmed()
     );
 
+    // Provider commands
+    println!("\n{}", i18n.get("help_provider").yellow().bold());
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/provider list".cyan(),
+        i18n.get("cmd_provider_list").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/provider use <name>".cyan(),
+        i18n.get("cmd_provider_use").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/provider add <name>".cyan(),
+        i18n.get("cmd_provider_add").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/provider remove <name>".cyan(),
+        i18n.get("cmd_provider_remove").dimmed()
+    );
+
     // History commands
     println!("\n{}", i18n.get("help_history").yellow().bold());
     println!(
@@ -71,6 +98,42 @@ pub fn print_help(i18n: &I18n) {
         "/help".cyan(),
         i18n.get("cmd_help").dimmed()
     );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/usage".cyan(),
+        i18n.get("cmd_usage").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/compact".cyan(),
+        i18n.get("cmd_compact").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/checkpoint list".cyan(),
+        i18n.get("cmd_checkpoint_list").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/checkpoint restore <n>".cyan(),
+        i18n.get("cmd_checkpoint_restore").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/undo".cyan(),
+        i18n.get("cmd_undo").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/plan [on|off]".cyan(),
+        i18n.get("cmd_plan").dimmed()
+    );
     println!(
         "  {} {:25} {}",
         "·".bright_black(),
@@ -110,6 +173,24 @@ pub fn print_help(i18n: &I18n) {
         "/runcommand info <id>".cyan(),
         i18n.get("cmd_runcommand_info").dimmed()
     );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/runcommand ps".cyan(),
+        i18n.get("cmd_runcommand_ps").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/runcommand logs <id>".cyan(),
+        i18n.get("cmd_runcommand_logs").dimmed()
+    );
+    println!(
+        "  {} {:25} {}",
+        "·".bright_black(),
+        "/runcommand kill <id>".cyan(),
+        i18n.get("cmd_runcommand_kill").dimmed()
+    );
 
     println!("\n{}", "═".repeat(60).bright_black());
     println!();
@@ -1,9 +1,14 @@
 mod agents;
+mod checkpoint;
+mod compact;
 mod help;
 mod history;
 mod language;
 mod model;
+mod plan;
+mod provider;
 mod runcommand;
+mod usage;
 mod index;
 pub mod todo;
 pub mod mcp;
@@ -18,6 +23,7 @@ use i18n::I18n;
 
 pub use agents::handle_agents_md_command;
 pub use help::print_help;
+pub use plan::switch_plan_mode;
 
 /// Handle command - returns Ok(()) if successfully processed, Err if error
 pub async fn handle_command(
@@ -42,6 +48,9 @@ pub async fn handle_command_with_parts(
     match parts.first() {
         Some(&"/exit") => {
             println!("\n\x1b[36m{}\x1b[0m\n", i18n.get("goodbye"));
+            // Exiting here skips the cleanup after the REPL, background commands
+            // run in their own process groups and would outlive Friendev
+            tools::background::kill_all();
             std::process::exit(0);
         }
         Some(&"/help") => {
@@ -50,6 +59,9 @@ pub async fn handle_command_with_parts(
         Some(&"/model") => {
             model::handle_model_command(&parts, config, api_client, &i18n).await?;
         }
+        Some(&"/provider") => {
+            provider::handle_provider_command(parts, config, api_client, &i18n)?;
+        }
         Some(&"/history") => {
             history::handle_history_command(&parts, config, session, &i18n)?;
         }
@@ -69,6 +81,21 @@ pub async fn handle_command_with_parts(
         Some(&"/index") => {
             index::handle_index_command(parts[1..].to_vec(), &i18n).await?;
         }
+        Some(&"/compact") => {
+            compact::handle_compact_command(config, session, api_client, &i18n).await?;
+        }
+        Some(&"/checkpoint") => {
+            checkpoint::handle_checkpoint_command(parts, session, &i18n)?;
+        }
+        Some(&"/undo") => {
+            checkpoint::handle_undo_command(session, &i18n)?;
+        }
+        Some(&"/usage") => {
+            usage::handle_usage_command(session, &i18n)?;
+        }
+        Some(&"/plan") => {
+            plan::handle_plan_command(parts, &i18n)?;
+        }
         Some(&"/todo") => {
             todo::handle_todo_command(&parts, &i18n, session)?;
         }
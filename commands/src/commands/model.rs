@@ -50,7 +50,7 @@ pub async fn handle_model_command(
                         let _ = enhanced_output::print_success(&success_msg);
                     } else {
                         println!(
-                            "\n\x1b[33m[!] {}:\x1b[0m /model sk switch <model_name>\n",
+                            "\n\x1b[33m[!] {}:\x1b[0m /model sk switch <model_name | profile:model>\n",
                             i18n.get("usage")
                         );
                     }
@@ -98,7 +98,7 @@ pub async fn handle_model_command(
                      // Unknown sk subcommand
                      println!("\n\x1b[33m[?] Usage:\x1b[0m");
                      println!("    \x1b[36m/model sk\x1b[0m                Interactive selection");
-                     println!("    \x1b[36m/model sk\x1b[0m switch <name>  Set specific model");
+                     println!("    \x1b[36m/model sk\x1b[0m switch <name>  Set specific model (or profile:model)");
                  }
             }
         }
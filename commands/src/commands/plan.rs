@@ -0,0 +1,27 @@
+use anyhow::Result;
+use i18n::I18n;
+
+/// Handle /plan command, toggles plan mode without an argument
+pub fn handle_plan_command(parts: &[&str], i18n: &I18n) -> Result<()> {
+    let enabled = match parts.get(1) {
+        None => !tools::is_plan_mode(),
+        Some(&"on") => true,
+        Some(&"off") => false,
+        Some(_) => {
+            println!("\n\x1b[33m[!] {}:\x1b[0m /plan [on|off]\n", i18n.get("usage"));
+            return Ok(());
+        }
+    };
+    switch_plan_mode(enabled, i18n);
+    Ok(())
+}
+
+/// Turn plan mode on or off and tell the user
+pub fn switch_plan_mode(enabled: bool, i18n: &I18n) {
+    tools::set_plan_mode(enabled);
+    if enabled {
+        println!("\n\x1b[36m[PLAN]\x1b[0m {}\n", i18n.get("plan_mode_on"));
+    } else {
+        println!("\n\x1b[32m[OK]\x1b[0m {}\n", i18n.get("plan_mode_off"));
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+
+use api::ApiClient;
+use config::Config;
+use i18n::I18n;
+use ui::enhanced_output;
+
+/// Handle /provider command
+pub fn handle_provider_command(
+    parts: &[&str],
+    config: &mut Config,
+    api_client: &mut ApiClient,
+    i18n: &I18n,
+) -> Result<()> {
+    match parts.get(1) {
+        Some(&"list") | None => {
+            print_profiles(config, i18n);
+        }
+        Some(&"use") => {
+            if let Some(name) = parts.get(2) {
+                if !config.profiles.contains_key(*name) {
+                    println!(
+                        "\n\x1b[31m[X] {}:\x1b[0m {}\n",
+                        i18n.get("provider_not_found"),
+                        name
+                    );
+                    return Ok(());
+                }
+                if let Some(kept) = config.use_profile(name)? {
+                    let _ = enhanced_output::print_success(&format!("{} {}", i18n.get("provider_kept_settings"), kept));
+                }
+                *api_client = ApiClient::new(config.clone());
+
+                let success_msg = format!(
+                    "{} {} ({} / {})",
+                    i18n.get("provider_switched"),
+                    name,
+                    config.provider,
+                    config.current_model
+                );
+                let _ = enhanced_output::print_success(&success_msg);
+            } else {
+                println!(
+                    "\n\x1b[33m[!] {}:\x1b[0m /provider use <name>\n",
+                    i18n.get("usage")
+                );
+            }
+        }
+        Some(&"add") => {
+            if let Some(name) = parts.get(2) {
+                println!();
+                let profile = config.prompt_profile(i18n)?;
+                config.add_profile(name.to_string(), profile)?;
+
+                let success_msg = format!("{} {}", i18n.get("provider_saved"), name);
+                let _ = enhanced_output::print_success(&success_msg);
+            } else {
+                println!(
+                    "\n\x1b[33m[!] {}:\x1b[0m /provider add <name>\n",
+                    i18n.get("usage")
+                );
+            }
+        }
+        Some(&"remove") | Some(&"rm") => {
+            if let Some(name) = parts.get(2) {
+                if config.remove_profile(name)? {
+                    let success_msg = format!("{} {}", i18n.get("provider_removed"), name);
+                    let _ = enhanced_output::print_success(&success_msg);
+                } else {
+                    println!(
+                        "\n\x1b[31m[X] {}:\x1b[0m {}\n",
+                        i18n.get("provider_not_found"),
+                        name
+                    );
+                }
+            } else {
+                println!(
+                    "\n\x1b[33m[!] {}:\x1b[0m /provider remove <name>\n",
+                    i18n.get("usage")
+                );
+            }
+        }
+        _ => {
+            println!("\n\x1b[33m[?] {}:\x1b[0m", i18n.get("help_provider"));
+            println!(
+                "    \x1b[36m/provider\x1b[0m list          {}",
+                i18n.get("cmd_provider_list")
+            );
+            println!(
+                "    \x1b[36m/provider\x1b[0m use <name>    {}",
+                i18n.get("cmd_provider_use")
+            );
+            println!(
+                "    \x1b[36m/provider\x1b[0m add <name>    {}",
+                i18n.get("cmd_provider_add")
+            );
+            println!(
+                "    \x1b[36m/provider\x1b[0m remove <name> {}\n",
+                i18n.get("cmd_provider_remove")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Print configured profiles, marking the active one
+fn print_profiles(config: &Config, i18n: &I18n) {
+    println!("\n\x1b[1;33m{}:\x1b[0m", i18n.get("help_provider"));
+
+    if config.profiles.is_empty() {
+        println!("  \x1b[90m{}\x1b[0m\n", i18n.get("provider_none"));
+        return;
+    }
+
+    for (name, profile) in &config.profiles {
+        let marker = if config.active_profile.as_deref() == Some(name.as_str()) {
+            "\x1b[32m[*]\x1b[0m"
+        } else {
+            "\x1b[90m[ ]\x1b[0m"
+        };
+        println!(
+            "  {} \x1b[1m{:16}\x1b[0m {:10} {} \x1b[90m{}\x1b[0m",
+            marker, name, profile.provider, profile.model, profile.api_url
+        );
+    }
+
+    if config.active_profile.is_none() {
+        println!("\n  \x1b[90m{}\x1b[0m", i18n.get("provider_no_active"));
+    }
+    println!();
+}
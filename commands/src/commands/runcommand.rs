@@ -1,5 +1,6 @@
 use anyhow::Result;
 use i18n::I18n;
+use tools::background;
 use tools::CommandConfig;
 
 /// Handle /runcommand command
@@ -142,6 +143,91 @@ pub fn handle_run_command_command(parts: &[&str], i18n: &I18n) -> Result<()> {
                 );
             }
         }
+        Some(&"ps") => match CommandConfig::load() {
+            Ok(config) => {
+                let commands = config.list_background_commands();
+
+                if commands.is_empty() {
+                    println!(
+                        "\n\x1b[90m[i] {}\x1b[0m\n",
+                        i18n.get("runcommand_ps_empty")
+                    );
+                } else {
+                    println!("\n\x1b[1;33m{}:\x1b[0m", i18n.get("runcommand_ps_header"));
+                    for cmd in commands {
+                        let status = background::effective_status(cmd);
+                        let color = match status.as_str() {
+                            "running" => "32",
+                            "completed" => "90",
+                            _ => "31",
+                        };
+                        println!(
+                            "  \x1b[36m{}\x1b[0m \x1b[{}m{:<9}\x1b[0m {} {}",
+                            cmd.id,
+                            color,
+                            status,
+                            cmd.start_time.format("%m-%d %H:%M"),
+                            cmd.command
+                        );
+                    }
+                    println!();
+                }
+            }
+            Err(e) => eprintln!(
+                "\n\x1b[31m[X] {}:\x1b[0m {}\n",
+                i18n.get("runcommand_load_config_failed"),
+                e
+            ),
+        },
+        Some(&"logs") => {
+            if let Some(id_str) = parts.get(2) {
+                let lines = parts.get(3).and_then(|n| n.parse().ok()).unwrap_or(50);
+                let log_path = CommandConfig::load()
+                    .ok()
+                    .and_then(|c| c.get_background_command(id_str).and_then(|cmd| cmd.log_path.clone()));
+
+                match log_path.map(|path| background::read_log(&path, None, lines)) {
+                    Some(Ok(chunk)) => {
+                        println!("\n\x1b[1;33m{}:\x1b[0m", i18n.get("runcommand_info_output"));
+                        println!("{}", chunk.text);
+                    }
+                    Some(Err(e)) => eprintln!("\n\x1b[31m[X] {}\x1b[0m\n", e),
+                    None => println!(
+                        "\n\x1b[31m[X] {}\x1b[0m\n",
+                        i18n.get("runcommand_info_not_found").replace("{}", id_str)
+                    ),
+                }
+            } else {
+                println!(
+                    "\n\x1b[33m[!] {}:\x1b[0m /runcommand logs <id> [lines]\n",
+                    i18n.get("usage")
+                );
+            }
+        }
+        Some(&"kill") => {
+            if let Some(id_str) = parts.get(2) {
+                match background::kill(id_str) {
+                    Ok(true) => println!(
+                        "\n\x1b[32m[OK]\x1b[0m {}\n",
+                        i18n.get("command_kill_brief").replace("{}", id_str)
+                    ),
+                    Ok(false) => println!(
+                        "\n\x1b[33m[!] {}\x1b[0m\n",
+                        i18n.get("command_kill_not_running").replace("{}", id_str)
+                    ),
+                    Err(e) => eprintln!(
+                        "\n\x1b[31m[X] {}:\x1b[0m {}\n",
+                        i18n.get("runcommand_load_config_failed"),
+                        e
+                    ),
+                }
+            } else {
+                println!(
+                    "\n\x1b[33m[!] {}:\x1b[0m /runcommand kill <id>\n",
+                    i18n.get("usage")
+                );
+            }
+        }
         _ => {
             println!(
                 "\n\x1b[33m[?] {}:\x1b[0m",
@@ -160,9 +246,21 @@ pub fn handle_run_command_command(parts: &[&str], i18n: &I18n) -> Result<()> {
                 i18n.get("cmd_runcommand_del")
             );
             println!(
-                "    \x1b[36m/runcommand\x1b[0m info <id>   {}\n",
+                "    \x1b[36m/runcommand\x1b[0m info <id>   {}",
                 i18n.get("cmd_runcommand_info")
             );
+            println!(
+                "    \x1b[36m/runcommand\x1b[0m ps          {}",
+                i18n.get("cmd_runcommand_ps")
+            );
+            println!(
+                "    \x1b[36m/runcommand\x1b[0m logs <id>   {}",
+                i18n.get("cmd_runcommand_logs")
+            );
+            println!(
+                "    \x1b[36m/runcommand\x1b[0m kill <id>   {}\n",
+                i18n.get("cmd_runcommand_kill")
+            );
         }
     }
     Ok(())
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+
+use ::history::{ChatSession, TokenUsage};
+use config::{Config, PriceTable};
+use i18n::I18n;
+
+/// Usage aggregated over the API calls of one user turn
+#[derive(Default)]
+struct TurnTotals {
+    calls: usize,
+    usage: TokenUsage,
+    cost: f64,
+}
+
+/// Handle /usage command
+pub fn handle_usage_command(session: &ChatSession, i18n: &I18n) -> Result<()> {
+    if session.usage.is_empty() {
+        println!("\n\x1b[90m[i] {}\x1b[0m\n", i18n.get("usage_none"));
+        return Ok(());
+    }
+
+    let prices = PriceTable::load()?;
+    let mut turns: BTreeMap<usize, TurnTotals> = BTreeMap::new();
+    let mut unpriced = BTreeSet::new();
+
+    for record in &session.usage {
+        let totals = turns.entry(record.turn).or_default();
+        totals.calls += 1;
+        totals.usage += record.usage;
+        match record.usage.cost(&prices, &record.model) {
+            Some(cost) => totals.cost += cost,
+            None => {
+                unpriced.insert(record.model.as_str());
+            }
+        }
+    }
+
+    println!("\n\x1b[1;33m{}:\x1b[0m", i18n.get("usage_title"));
+    println!(
+        "  \x1b[90m{:>6} {:>6} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}\x1b[0m",
+        i18n.get("usage_turn"),
+        i18n.get("usage_calls"),
+        i18n.get("usage_prompt"),
+        i18n.get("usage_cache_read"),
+        i18n.get("usage_cache_write"),
+        i18n.get("usage_completion"),
+        i18n.get("usage_reasoning"),
+        i18n.get("usage_cost")
+    );
+
+    let mut session_totals = TurnTotals::default();
+    for (turn, totals) in &turns {
+        print_row(&turn.to_string(), totals);
+        session_totals.calls += totals.calls;
+        session_totals.usage += totals.usage;
+        session_totals.cost += totals.cost;
+    }
+
+    println!("  \x1b[90m{}\x1b[0m", "-".repeat(89));
+    print!("\x1b[1m");
+    print_row(&i18n.get("usage_total"), &session_totals);
+    print!("\x1b[0m");
+
+    if !unpriced.is_empty() {
+        let models: Vec<&str> = unpriced.into_iter().collect();
+        let path = Config::config_dir()?.join("prices.json");
+        println!(
+            "\n\x1b[33m[!] {}\x1b[0m",
+            i18n.get("usage_unpriced")
+                .replacen("{}", &models.join(", "), 1)
+                .replacen("{}", &path.display().to_string(), 1)
+        );
+    }
+    println!();
+
+    Ok(())
+}
+
+fn print_row(label: &str, totals: &TurnTotals) {
+    println!(
+        "  {:>6} {:>6} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+        label,
+        totals.calls,
+        totals.usage.prompt_tokens,
+        totals.usage.cache_read_tokens,
+        totals.usage.cache_write_tokens,
+        totals.usage.completion_tokens,
+        totals.usage.reasoning_tokens,
+        format!("${:.4}", totals.cost)
+    );
+}
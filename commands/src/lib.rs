@@ -6,7 +6,7 @@ use config::Config;
 use history::ChatSession;
 use mcp::McpIntegration;
 
-pub use commands::{handle_agents_md_command, print_help, handle_command_with_parts};
+pub use commands::{handle_agents_md_command, print_help, handle_command_with_parts, switch_plan_mode};
 
 /// Handle commands that start with /
 pub async fn handle_command(
@@ -0,0 +1,200 @@
+use super::paths;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A user-defined subagent, a markdown file whose front matter describes the
+/// agent and whose body is its system prompt
+///
+/// ```markdown
+/// ---
+/// name: test-runner
+/// description: Runs the test suite and reports failures
+/// tools: [file_read, file_search, run_command]
+/// model: fast:gpt-4o-mini
+/// max_turns: 10
+/// ---
+/// You run tests and explain why they fail...
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentDefinition {
+    pub name: String,
+    pub description: String,
+    /// Tools the agent may use, every tool when absent
+    pub tools: Option<Vec<String>>,
+    /// Model reference (`<model>`, `<profile>` or `<profile>:<model>`) replacing the parent's model
+    pub model: Option<String>,
+    /// Most model round-trips before the agent has to stop
+    pub max_turns: Option<usize>,
+    pub prompt: String,
+    /// File the agent was loaded from
+    pub source: PathBuf,
+}
+
+impl AgentDefinition {
+    /// Load the user agents from `<config dir>/agents` and the project agents from
+    /// `.friendev/agents`, a project agent replaces a user agent of the same name
+    ///
+    /// Files that fail to parse are skipped.
+    pub fn load_all(working_dir: &Path) -> Vec<Self> {
+        let mut agents = BTreeMap::new();
+        let user_dir = paths::config_dir().ok().map(|dir| dir.join("agents"));
+        for dir in user_dir.into_iter().chain([Self::project_dir(working_dir)]) {
+            for agent in Self::read_dir(&dir) {
+                agents.insert(agent.name.clone(), agent);
+            }
+        }
+        agents.into_values().collect()
+    }
+
+    /// Project agents directory, `.friendev/agents` in the workspace
+    pub fn project_dir(working_dir: &Path) -> PathBuf {
+        working_dir.join(".friendev").join("agents")
+    }
+
+    fn read_dir(dir: &Path) -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        files.sort();
+        files
+            .into_iter()
+            .filter_map(|path| {
+                let content = fs::read_to_string(&path).ok()?;
+                Self::parse(&content, &path).ok()
+            })
+            .collect()
+    }
+
+    /// Parse an agent file, the name defaults to the file stem
+    pub fn parse(content: &str, source: &Path) -> Result<Self> {
+        let content = content.trim_start_matches('\u{feff}');
+        let mut lines = content.lines();
+        if lines.next().map(str::trim_end) != Some("---") {
+            bail!("{}: missing front matter", source.display());
+        }
+
+        let mut fields: Vec<(String, String)> = Vec::new();
+        let mut closed = false;
+        for line in lines.by_ref() {
+            if line.trim_end() == "---" {
+                closed = true;
+                break;
+            }
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            // `- item` lines continue the list of the previous key
+            if let Some(item) = line.trim_start().strip_prefix("- ") {
+                match fields.last_mut() {
+                    Some((_, value)) => {
+                        if !value.is_empty() {
+                            value.push(',');
+                        }
+                        value.push_str(item.trim());
+                    }
+                    None => bail!("{}: list item without a key", source.display()),
+                }
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                bail!("{}: invalid front matter line `{}`", source.display(), line.trim());
+            };
+            fields.push((key.trim().to_lowercase(), value.trim().to_string()));
+        }
+        if !closed {
+            bail!("{}: unterminated front matter", source.display());
+        }
+
+        let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let mut agent = Self {
+            name: stem,
+            description: String::new(),
+            tools: None,
+            model: None,
+            max_turns: None,
+            prompt: lines.collect::<Vec<_>>().join("\n").trim().to_string(),
+            source: source.to_path_buf(),
+        };
+        for (key, value) in fields {
+            let value = unquote(&value);
+            match key.as_str() {
+                "name" => agent.name = value.to_string(),
+                "description" => agent.description = value.to_string(),
+                "tools" | "allowed_tools" | "allowed-tools" => agent.tools = Some(parse_list(value)),
+                "model" => agent.model = Some(value.to_string()).filter(|m| !m.is_empty()),
+                "max_turns" | "max-turns" => match value.parse() {
+                    Ok(0) => bail!("{}: max_turns must be at least 1", source.display()),
+                    Ok(turns) => agent.max_turns = Some(turns),
+                    Err(_) => bail!("{}: max_turns must be a number", source.display()),
+                },
+                _ => {}
+            }
+        }
+
+        if agent.name.is_empty() || agent.name.contains(char::is_whitespace) {
+            bail!("{}: invalid agent name `{}`", source.display(), agent.name);
+        }
+        if agent.prompt.is_empty() {
+            bail!("{}: empty system prompt", source.display());
+        }
+        Ok(agent)
+    }
+}
+
+/// `[a, b]`, `a, b` or the comma-joined `- item` lines
+fn parse_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .unwrap_or(value);
+    value
+        .split(',')
+        .map(|item| unquote(item.trim()).to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_agent() {
+        let content = "---\nname: test-runner\ndescription: \"Runs the tests\"\ntools: [file_read, 'run_command']\nmodel: fast:gpt-4o-mini\nmax_turns: 5\n---\n\nYou run tests.\n";
+        let agent = AgentDefinition::parse(content, Path::new("agents/runner.md")).unwrap();
+        assert_eq!(agent.name, "test-runner");
+        assert_eq!(agent.description, "Runs the tests");
+        assert_eq!(agent.tools, Some(vec!["file_read".to_string(), "run_command".to_string()]));
+        assert_eq!(agent.model.as_deref(), Some("fast:gpt-4o-mini"));
+        assert_eq!(agent.max_turns, Some(5));
+        assert_eq!(agent.prompt, "You run tests.");
+
+        let content = "---\ndescription: Docs\ntools:\n  - file_read\n  - file_search\n---\nWrite docs.";
+        let agent = AgentDefinition::parse(content, Path::new("agents/docs.md")).unwrap();
+        assert_eq!(agent.name, "docs");
+        assert_eq!(agent.tools, Some(vec!["file_read".to_string(), "file_search".to_string()]));
+        assert!(agent.model.is_none() && agent.max_turns.is_none());
+
+        assert!(AgentDefinition::parse("no front matter", Path::new("a.md")).is_err());
+        assert!(AgentDefinition::parse("---\nname: a\n", Path::new("a.md")).is_err());
+        assert!(AgentDefinition::parse("---\nname: a\n---\n", Path::new("a.md")).is_err());
+        assert!(AgentDefinition::parse("---\nmax_turns: 0\n---\nRun.", Path::new("a.md")).is_err());
+    }
+}
@@ -8,6 +8,36 @@ pub fn default_retry_delay_ms() -> u64 {
     300
 }
 
+/// Default context window in tokens
+pub fn default_context_window() -> usize {
+    128_000
+}
+
+/// Default fraction of the context window that triggers compaction
+pub fn default_compaction_threshold() -> f32 {
+    0.8
+}
+
+/// Default number of recent user turns kept verbatim by compaction
+pub fn default_keep_recent_turns() -> usize {
+    4
+}
+
+/// Default time limit of a post-edit checker in seconds
+pub fn default_verification_timeout_secs() -> u64 {
+    120
+}
+
+/// Default number of critic review rounds per user message in duet mode
+pub fn default_duet_max_rounds() -> usize {
+    3
+}
+
+/// Default for flags that are enabled unless turned off
+pub fn default_true() -> bool {
+    true
+}
+
 use i18n::SUPPORTED_LANGUAGES;
 
 /// Default UI language (first supported language)
@@ -1,6 +1,11 @@
+mod agents;
 mod defaults;
 mod paths;
+mod permissions;
 mod persistence;
+mod prices;
+mod profiles;
+mod sandbox;
 mod setup;
 mod types;
 mod updates;
@@ -8,7 +13,14 @@ mod updates;
 use anyhow::Result;
 
 // Re-export public API
-pub use types::{Config, LspConfig, LspSettings};
+pub use agents::AgentDefinition;
+pub use permissions::{Permission, PermissionPolicy, PermissionRule};
+pub use prices::{ModelPrice, PriceTable};
+pub use sandbox::SandboxConfig;
+pub use types::{
+    ApiProvider, ApprovalPolicy, CompactionConfig, Config, DuetConfig, LspConfig, LspSettings, McpServerConfig,
+    ProviderProfile, VerificationConfig,
+};
 
 impl Config {
     /// Get or create config directory
@@ -60,4 +72,31 @@ impl Config {
     pub fn update_shorekeeper_model(&mut self, model: String) -> Result<()> {
         updates::update_shorekeeper_model(self, model)
     }
+
+    /// Switch to a named provider profile
+    ///
+    /// Returns the profile the settings that came from no profile were kept as, if any.
+    pub fn use_profile(&mut self, name: &str) -> Result<Option<String>> {
+        profiles::use_profile(self, name)
+    }
+
+    /// Add or replace a named provider profile
+    pub fn add_profile(&mut self, name: String, profile: ProviderProfile) -> Result<()> {
+        profiles::add_profile(self, name, profile)
+    }
+
+    /// Remove a named provider profile, returns false if it did not exist
+    pub fn remove_profile(&mut self, name: &str) -> Result<bool> {
+        profiles::remove_profile(self, name)
+    }
+
+    /// Interactively create a provider profile
+    pub fn prompt_profile(&self, i18n: &i18n::I18n) -> Result<ProviderProfile> {
+        profiles::prompt_profile(self, i18n)
+    }
+
+    /// Resolve `<model>`, `<profile>` or `<profile>:<model>` into a config for that model
+    pub fn resolve_model_ref(&self, reference: &str) -> Config {
+        profiles::resolve_model_ref(self, reference)
+    }
 }
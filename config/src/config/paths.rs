@@ -20,3 +20,13 @@ pub fn config_path() -> Result<PathBuf> {
 pub fn lsp_config_path() -> Result<PathBuf> {
     Ok(config_dir()?.join("lsp.json"))
 }
+
+/// Get model price table file path
+pub fn prices_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("prices.json"))
+}
+
+/// Get global permission policy file path
+pub fn permissions_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("permissions.json"))
+}
@@ -0,0 +1,109 @@
+use super::paths;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What a matching rule does with a tool call
+///
+/// Ordered by strictness, when several rules match the strictest one wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Run without asking, even when approvals are required
+    Allow,
+    /// Always ask, even in `--yolo` mode or after "always approve"
+    Ask,
+    /// Never run
+    Deny,
+}
+
+/// A permission rule, every field that is set must match the tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionRule {
+    pub permission: Permission,
+    /// Tool name, `*` wildcards allowed (`file_*`), any tool when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Glob over the paths the tool touches, relative to the workspace (`**/.env*`),
+    /// a pattern without `/` also matches the file name in any directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Regex over the `run_command` command line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Only match paths outside the workspace
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub outside_workspace: bool,
+}
+
+/// Rules from the global `permissions.json` followed by the project's
+/// `.friendev/permissions.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionPolicy {
+    #[serde(default)]
+    pub rules: Vec<PermissionRule>,
+}
+
+impl PermissionPolicy {
+    /// Load the global and project rules, missing files count as empty
+    pub fn load(working_dir: &Path) -> Result<Self> {
+        let mut policy = Self::read(&paths::permissions_path()?)?;
+        policy
+            .rules
+            .extend(Self::read(&Self::project_path(working_dir))?.rules);
+        Ok(policy)
+    }
+
+    /// Project policy file, `.friendev/permissions.json` in the workspace
+    pub fn project_path(working_dir: &Path) -> PathBuf {
+        working_dir.join(".friendev").join("permissions.json")
+    }
+
+    /// Append a rule to the project policy file unless it is already there
+    pub fn add_project_rule(working_dir: &Path, rule: PermissionRule) -> Result<()> {
+        let path = Self::project_path(working_dir);
+        let mut policy = Self::read(&path)?;
+        if policy.rules.contains(&rule) {
+            return Ok(());
+        }
+        policy.rules.push(rule);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(&policy)?)?;
+        Ok(())
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).with_context(|| format!("invalid {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let policy: PermissionPolicy = serde_json::from_str(
+            r#"{"rules": [
+                {"permission": "deny", "path": ".env*"},
+                {"permission": "ask", "tool": "file_*", "outside_workspace": true},
+                {"permission": "allow", "tool": "run_command", "command": "^cargo (check|test)\\b"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(policy.rules.len(), 3);
+        assert_eq!(policy.rules[0].permission, Permission::Deny);
+        assert!(policy.rules[1].outside_workspace);
+        assert_eq!(policy.rules[2].command.as_deref(), Some(r"^cargo (check|test)\b"));
+        assert!(Permission::Deny > Permission::Ask && Permission::Ask > Permission::Allow);
+    }
+}
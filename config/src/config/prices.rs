@@ -0,0 +1,124 @@
+use super::paths;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Prompt tokens read from the cache, charged as input when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+    /// Prompt tokens written to the cache, charged as input when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+}
+
+/// User-editable price table stored in `prices.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PriceTable {
+    pub models: BTreeMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    /// Load the price table, writing the defaults on first use so users can edit them
+    pub fn load() -> Result<Self> {
+        let path = paths::prices_path()?;
+        if !path.exists() {
+            let table = Self::default_prices();
+            fs::write(&path, serde_json::to_string_pretty(&table)?)?;
+            return Ok(table);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Find the price of a model
+    ///
+    /// Falls back to the longest entry contained in the model name, so
+    /// "openai/gpt-4o-2024-08-06" matches "gpt-4o".
+    pub fn price_for(&self, model: &str) -> Option<ModelPrice> {
+        if let Some(price) = self.models.get(model) {
+            return Some(*price);
+        }
+        self.models
+            .iter()
+            .filter(|(name, _)| model.contains(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, price)| *price)
+    }
+
+    /// Cost in USD of a request, None if the model has no price
+    pub fn cost(
+        &self,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+    ) -> Option<f64> {
+        let price = self.price_for(model)?;
+        Some(
+            (prompt_tokens as f64 * price.input
+                + completion_tokens as f64 * price.output
+                + cache_read_tokens as f64 * price.cache_read.unwrap_or(price.input)
+                + cache_write_tokens as f64 * price.cache_write.unwrap_or(price.input))
+                / 1_000_000.0,
+        )
+    }
+
+    /// Built-in prices written to `prices.json` on first use
+    fn default_prices() -> Self {
+        let models = [
+            ("gpt-4o", 2.5, 10.0, None),
+            ("gpt-4o-mini", 0.15, 0.6, None),
+            ("gpt-4.1", 2.0, 8.0, None),
+            ("gpt-4.1-mini", 0.4, 1.6, None),
+            ("claude-sonnet-4-5", 3.0, 15.0, Some((0.3, 3.75))),
+            ("claude-haiku-4-5", 1.0, 5.0, Some((0.1, 1.25))),
+            ("gemini-2.5-pro", 1.25, 10.0, None),
+            ("gemini-2.5-flash", 0.3, 2.5, None),
+            ("deepseek-chat", 0.27, 1.1, None),
+        ]
+        .into_iter()
+        .map(|(name, input, output, cache)| {
+            let price = ModelPrice {
+                input,
+                output,
+                cache_read: cache.map(|(read, _)| read),
+                cache_write: cache.map(|(_, write)| write),
+            };
+            (name.to_string(), price)
+        })
+        .collect();
+
+        Self { models }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_lookup_prefers_longest_match() {
+        let table = PriceTable::default_prices();
+
+        let mini = table.price_for("openai/gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(mini.input, 0.15);
+        assert!(table.price_for("unknown-model").is_none());
+
+        let cost = table.cost("gpt-4o", 1_000_000, 100_000, 0, 0).unwrap();
+        assert!((cost - 3.5).abs() < 1e-9);
+
+        // Cache reads and writes have their own prices, else they cost like input
+        let cost = table.cost("claude-sonnet-4-5", 0, 0, 1_000_000, 1_000_000).unwrap();
+        assert!((cost - 4.05).abs() < 1e-9);
+        let cost = table.cost("gpt-4o", 0, 0, 1_000_000, 0).unwrap();
+        assert!((cost - 2.5).abs() < 1e-9);
+    }
+}
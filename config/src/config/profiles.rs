@@ -0,0 +1,267 @@
+use super::defaults;
+use super::persistence;
+use super::types::{ApiProvider, Config, ProviderProfile};
+use anyhow::{anyhow, Result};
+use i18n::I18n;
+use std::collections::HashMap;
+
+/// Copy a profile's settings into the top-level config
+fn apply(config: &mut Config, name: &str, profile: &ProviderProfile) {
+    config.provider = profile.provider;
+    config.api_url = profile.api_url.clone();
+    config.api_key = profile.api_key.clone();
+    config.current_model = profile.model.clone();
+    config.max_retries = profile.max_retries;
+    config.retry_delay_ms = profile.retry_delay_ms;
+    config.extra_headers = profile.headers.clone();
+    config.active_profile = Some(name.to_string());
+}
+
+/// Switch to a named profile and persist it as active
+///
+/// Returns the profile the settings that came from no profile were kept as, if any.
+pub fn use_profile(config: &mut Config, name: &str) -> Result<Option<String>> {
+    let kept = switch_profile(config, name)?;
+    persistence::save_config(config)?;
+    Ok(kept)
+}
+
+fn switch_profile(config: &mut Config, name: &str) -> Result<Option<String>> {
+    let profile = config
+        .profiles
+        .get(name)
+        .cloned()
+        .ok_or_else(|| anyhow!("Unknown provider profile: '{}'", name))?;
+    let kept = keep_unprofiled(config);
+    apply(config, name, &profile);
+    Ok(kept)
+}
+
+/// Keep settings that came from no profile as a profile of their own, `default` unless
+/// that name is taken, before a switch overwrites them
+///
+/// Nothing is added when a profile holds the same settings already.
+fn keep_unprofiled(config: &mut Config) -> Option<String> {
+    if config.active_profile.is_some() {
+        return None;
+    }
+    let current = ProviderProfile {
+        provider: config.provider,
+        api_url: config.api_url.clone(),
+        api_key: config.api_key.clone(),
+        model: config.current_model.clone(),
+        max_retries: config.max_retries,
+        retry_delay_ms: config.retry_delay_ms,
+        headers: config.extra_headers.clone(),
+    };
+    if config.profiles.values().any(|profile| *profile == current) {
+        return None;
+    }
+    let name = std::iter::once("default".to_string())
+        .chain((2..).map(|i| format!("default-{}", i)))
+        .find(|name| !config.profiles.contains_key(name))?;
+    config.profiles.insert(name.clone(), current);
+    Some(name)
+}
+
+/// Add or replace a named profile
+pub fn add_profile(config: &mut Config, name: String, profile: ProviderProfile) -> Result<()> {
+    config.profiles.insert(name, profile);
+    persistence::save_config(config)
+}
+
+/// Remove a named profile, returns false if it did not exist
+///
+/// Removing the active profile keeps its settings in place, only the link is dropped.
+pub fn remove_profile(config: &mut Config, name: &str) -> Result<bool> {
+    if config.profiles.remove(name).is_none() {
+        return Ok(false);
+    }
+    if config.active_profile.as_deref() == Some(name) {
+        config.active_profile = None;
+    }
+    persistence::save_config(config)?;
+    Ok(true)
+}
+
+/// Resolve a model reference into a config for that model
+///
+/// Accepts `<model>`, `<profile>` or `<profile>:<model>`. The profile prefix is only
+/// honoured if such a profile exists, so model names containing ':' keep working.
+pub fn resolve_model_ref(config: &Config, reference: &str) -> Config {
+    let mut resolved = config.clone();
+
+    if let Some(profile) = config.profiles.get(reference) {
+        apply(&mut resolved, reference, profile);
+        return resolved;
+    }
+
+    if let Some((name, model)) = reference.split_once(':') {
+        if let Some(profile) = config.profiles.get(name) {
+            apply(&mut resolved, name, profile);
+            resolved.current_model = model.to_string();
+            return resolved;
+        }
+    }
+
+    resolved.current_model = reference.to_string();
+    resolved
+}
+
+/// Interactively create a profile, prefilled from the current settings
+pub fn prompt_profile(config: &Config, i18n: &I18n) -> Result<ProviderProfile> {
+    let provider_names: Vec<&str> = ApiProvider::ALL.iter().map(|p| p.as_str()).collect();
+    let default_idx = ApiProvider::ALL
+        .iter()
+        .position(|p| *p == config.provider)
+        .unwrap_or(0);
+    let provider_idx = dialoguer::Select::new()
+        .with_prompt(i18n.get("setup_provider"))
+        .default(default_idx)
+        .items(&provider_names)
+        .interact()?;
+    let provider = ApiProvider::ALL[provider_idx];
+
+    // Only offer the current values when the provider is unchanged
+    let same_provider = provider == config.provider;
+
+    let api_url = dialoguer::Input::<String>::new()
+        .with_prompt(i18n.get("setup_api_url"))
+        .default(if same_provider {
+            config.api_url.clone()
+        } else {
+            provider.default_api_url().to_string()
+        })
+        .interact_text()?;
+
+    let api_key = dialoguer::Input::<String>::new()
+        .with_prompt(i18n.get("setup_api_key"))
+        .interact_text()?;
+
+    let model = dialoguer::Input::<String>::new()
+        .with_prompt(i18n.get("setup_model"))
+        .default(if same_provider {
+            config.current_model.clone()
+        } else {
+            provider.default_model().to_string()
+        })
+        .interact_text()?;
+
+    let max_retries = dialoguer::Input::<u32>::new()
+        .with_prompt(i18n.get("provider_max_retries"))
+        .default(defaults::default_max_retries())
+        .interact_text()?;
+
+    let retry_delay_ms = dialoguer::Input::<u64>::new()
+        .with_prompt(i18n.get("provider_retry_delay"))
+        .default(defaults::default_retry_delay_ms())
+        .interact_text()?;
+
+    let headers_input = dialoguer::Input::<String>::new()
+        .with_prompt(i18n.get("provider_headers"))
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(ProviderProfile {
+        provider,
+        api_url,
+        api_key,
+        model,
+        max_retries,
+        retry_delay_ms,
+        headers: parse_headers(&headers_input),
+    })
+}
+
+/// Parse "Name: value, Other: value" into a header map
+fn parse_headers(input: &str) -> HashMap<String, String> {
+    input
+        .split(',')
+        .filter_map(|pair| pair.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .filter(|(k, _)| !k.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "api_key": "main-key",
+            "api_url": "https://api.openai.com/v1",
+            "current_model": "gpt-4o",
+            "shorekeeper_model": null
+        }))
+        .unwrap();
+        let profile = ProviderProfile {
+            provider: ApiProvider::Anthropic,
+            api_url: "https://api.anthropic.com/v1".to_string(),
+            api_key: "fast-key".to_string(),
+            model: "claude-haiku-4-5".to_string(),
+            max_retries: 1,
+            retry_delay_ms: 100,
+            headers: HashMap::from([("X-Team".to_string(), "a".to_string())]),
+        };
+        config.profiles.insert("fast".to_string(), profile);
+        config
+    }
+
+    #[test]
+    fn test_resolve_model_ref() {
+        let config = config();
+
+        let model = resolve_model_ref(&config, "gpt-4o-mini");
+        assert_eq!(model.current_model, "gpt-4o-mini");
+        assert_eq!((model.provider, model.api_key.as_str()), (ApiProvider::OpenAi, "main-key"));
+        assert!(model.active_profile.is_none());
+
+        let profile = resolve_model_ref(&config, "fast");
+        assert_eq!(profile.current_model, "claude-haiku-4-5");
+        assert_eq!((profile.provider, profile.api_key.as_str()), (ApiProvider::Anthropic, "fast-key"));
+        assert_eq!(profile.extra_headers.get("X-Team").map(String::as_str), Some("a"));
+        assert_eq!(profile.active_profile.as_deref(), Some("fast"));
+
+        let both = resolve_model_ref(&config, "fast:claude-sonnet-4-5");
+        assert_eq!(both.current_model, "claude-sonnet-4-5");
+        assert_eq!(both.provider, ApiProvider::Anthropic);
+
+        // Without a profile of that name the whole reference is the model
+        let tagged = resolve_model_ref(&config, "qwen2.5-coder:7b");
+        assert_eq!(tagged.current_model, "qwen2.5-coder:7b");
+        assert_eq!(tagged.provider, ApiProvider::OpenAi);
+    }
+
+    #[test]
+    fn test_switch_keeps_unprofiled_settings() {
+        let mut config = config();
+        assert_eq!(switch_profile(&mut config, "fast").unwrap().as_deref(), Some("default"));
+        assert_eq!(config.api_key, "fast-key");
+
+        // Switching back restores the original settings, later switches add nothing
+        assert_eq!(switch_profile(&mut config, "default").unwrap(), None);
+        assert_eq!((config.api_key.as_str(), config.current_model.as_str()), ("main-key", "gpt-4o"));
+        assert_eq!(config.provider, ApiProvider::OpenAi);
+        assert_eq!(switch_profile(&mut config, "fast").unwrap(), None);
+        assert_eq!(config.profiles.len(), 2);
+
+        // A profile called `default` that holds other settings is left alone
+        let mut config = self::config();
+        let mut other = config.profiles["fast"].clone();
+        other.api_key = "other-key".to_string();
+        config.profiles.insert("default".to_string(), other);
+        assert_eq!(switch_profile(&mut config, "fast").unwrap().as_deref(), Some("default-2"));
+        assert_eq!(config.profiles["default-2"].api_key, "main-key");
+        assert!(switch_profile(&mut config, "missing").is_err());
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let headers = parse_headers("X-Team: a, Authorization: Bearer t:1 ,  : skipped, no-colon");
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["X-Team"], "a");
+        assert_eq!(headers["Authorization"], "Bearer t:1");
+        assert!(parse_headers("").is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Restrictions for commands run by `run_command`, read from the project's
+/// `.friendev/sandbox.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Let commands use the network
+    #[serde(default = "default_true")]
+    pub network: bool,
+    /// Writable besides the working directory and the temp directory
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+    /// CPU time limit in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<u64>,
+    /// Address space limit in megabytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// Wall clock limit in seconds, the command is killed when it runs longer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            network: true,
+            writable_paths: Vec::new(),
+            cpu_secs: None,
+            memory_mb: None,
+            timeout_secs: None,
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// Load the project sandbox, a missing file means no sandbox
+    pub fn load(working_dir: &Path) -> Result<Self> {
+        let path = Self::project_path(working_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// Project sandbox file, `.friendev/sandbox.json` in the workspace
+    pub fn project_path(working_dir: &Path) -> PathBuf {
+        working_dir.join(".friendev").join("sandbox.json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sandbox() {
+        let sandbox: SandboxConfig =
+            serde_json::from_str(r#"{"enabled": true, "network": false, "memory_mb": 512}"#).unwrap();
+        assert!(sandbox.enabled && !sandbox.network);
+        assert_eq!(sandbox.memory_mb, Some(512));
+        assert!(sandbox.writable_paths.is_empty() && sandbox.cpu_secs.is_none());
+
+        let default: SandboxConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(default, SandboxConfig::default());
+    }
+}
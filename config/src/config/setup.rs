@@ -1,6 +1,6 @@
 use super::defaults;
 use super::persistence;
-use super::types::Config;
+use super::types::{ApiProvider, Config};
 use anyhow::Result;
 use i18n::{I18n, SUPPORTED_LANGUAGES};
 
@@ -23,30 +23,40 @@ pub fn initialize_config() -> Result<Config> {
     // Update i18n with selected language for remaining prompts
     let i18n = I18n::new(&ui_language);
 
-    // Step 2: API Key
+    // Step 2: API Provider
+    let provider_names: Vec<&str> = ApiProvider::ALL.iter().map(|p| p.as_str()).collect();
+    let provider_idx = dialoguer::Select::new()
+        .with_prompt(i18n.get("setup_provider"))
+        .default(0)
+        .items(&provider_names)
+        .interact()?;
+    let provider = ApiProvider::ALL[provider_idx];
+
+    // Step 3: API Key
     let api_key = dialoguer::Input::<String>::new()
         .with_prompt(i18n.get("setup_api_key"))
         .interact_text()?;
 
-    // Step 3: API URL
+    // Step 4: API URL
     let api_url = dialoguer::Input::<String>::new()
         .with_prompt(i18n.get("setup_api_url"))
-        .default("https://api.openai.com/v1".to_string())
+        .default(provider.default_api_url().to_string())
         .interact_text()?;
 
-    // Step 4: Default Model
+    // Step 5: Default Model
     let current_model = dialoguer::Input::<String>::new()
         .with_prompt(i18n.get("setup_model"))
-        .default("gpt-4".to_string())
+        .default(provider.default_model().to_string())
         .interact_text()?;
 
-    // Step 5: AI Language (last)
+    // Step 6: AI Language (last)
     let ai_language = dialoguer::Input::<String>::new()
         .with_prompt(i18n.get("setup_ai_language"))
         .default(SUPPORTED_LANGUAGES[0].to_string())
         .interact_text()?;
 
     let config = Config {
+        provider,
         api_key,
         api_url,
         current_model,
@@ -55,6 +65,13 @@ pub fn initialize_config() -> Result<Config> {
         max_retries: defaults::default_max_retries(),
         retry_delay_ms: defaults::default_retry_delay_ms(),
         shorekeeper_model: None,
+        extra_headers: Default::default(),
+        profiles: Default::default(),
+        active_profile: None,
+        compaction: Default::default(),
+        mcp_server: Default::default(),
+        verification: Default::default(),
+        duet: Default::default(),
     };
 
     persistence::save_config(&config)?;
@@ -1,10 +1,12 @@
 use super::defaults;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    pub provider: ApiProvider,
     pub api_key: String,
     pub api_url: String,
     pub current_model: String,
@@ -16,7 +18,206 @@ pub struct Config {
     pub max_retries: u32,
     #[serde(default = "defaults::default_retry_delay_ms")]
     pub retry_delay_ms: u64,
+    /// Model used by Shorekeeper, either `<model>` or `<profile>:<model>`
     pub shorekeeper_model: Option<String>,
+    /// Extra HTTP headers sent with every API request
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra_headers: HashMap<String, String>,
+    /// Named provider profiles
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProviderProfile>,
+    /// Name of the profile the top-level settings were taken from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    /// Context compaction settings
+    #[serde(default)]
+    pub compaction: CompactionConfig,
+    /// Settings of `friendev mcp-serve`
+    #[serde(default)]
+    pub mcp_server: McpServerConfig,
+    /// Checks run after file edits
+    #[serde(default)]
+    pub verification: VerificationConfig,
+    /// Critic settings of `--duet`
+    #[serde(default)]
+    pub duet: DuetConfig,
+}
+
+/// Context compaction settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    /// Compact automatically when the estimated context reaches the threshold
+    #[serde(default = "defaults::default_true")]
+    pub enabled: bool,
+    /// Context window of the model in tokens
+    #[serde(default = "defaults::default_context_window")]
+    pub context_window: usize,
+    /// Fraction of the context window that triggers compaction
+    #[serde(default = "defaults::default_compaction_threshold")]
+    pub threshold: f32,
+    /// Number of most recent user turns kept verbatim
+    #[serde(default = "defaults::default_keep_recent_turns")]
+    pub keep_recent_turns: usize,
+    /// Model used for summaries, `<model>` or `<profile>:<model>` (defaults to the current model)
+    #[serde(default)]
+    pub summarizer_model: Option<String>,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            context_window: defaults::default_context_window(),
+            threshold: defaults::default_compaction_threshold(),
+            keep_recent_turns: defaults::default_keep_recent_turns(),
+            summarizer_model: None,
+        }
+    }
+}
+
+/// Checks run after `file_write`, `file_replace` and `file_diff_edit`, new errors are
+/// attached to the tool result so the model fixes them in the same turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Checker per file extension: `lsp`, `cargo`, `tsc` or a shell command
+    /// (extensions not listed use cargo for Rust, tsc for TypeScript and LSP otherwise)
+    #[serde(default)]
+    pub checkers: HashMap<String, String>,
+    /// Seconds a checker may run before it is abandoned
+    #[serde(default = "defaults::default_verification_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            checkers: HashMap::new(),
+            timeout_secs: defaults::default_verification_timeout_secs(),
+        }
+    }
+}
+
+/// Duet mode, a critic model reviews every completed turn of the main model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuetConfig {
+    /// Critic model, `<model>` or `<profile>:<model>` (defaults to the Shorekeeper
+    /// model, then the current model)
+    #[serde(default)]
+    pub critic_model: Option<String>,
+    /// Most review rounds per user message before the critic's sign-off is skipped
+    #[serde(default = "defaults::default_duet_max_rounds")]
+    pub max_rounds: usize,
+}
+
+impl Default for DuetConfig {
+    fn default() -> Self {
+        Self {
+            critic_model: None,
+            max_rounds: defaults::default_duet_max_rounds(),
+        }
+    }
+}
+
+/// How `friendev mcp-serve` answers approval requests, since it has no terminal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalPolicy {
+    /// Reject every action that needs approval
+    #[default]
+    Deny,
+    /// Let Shorekeeper review each action
+    Review,
+    /// Approve everything
+    Allow,
+}
+
+/// Settings of `friendev mcp-serve`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Policy for actions that need approval
+    #[serde(default)]
+    pub approval: ApprovalPolicy,
+    /// Actions approved regardless of the policy, e.g. `file_replace`
+    #[serde(default)]
+    pub allowed_actions: Vec<String>,
+    /// Tools to expose (all builtin tools if empty)
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+/// Named set of connection settings that can be switched with `/provider use`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderProfile {
+    #[serde(default)]
+    pub provider: ApiProvider,
+    pub api_url: String,
+    pub api_key: String,
+    /// Default model of this profile
+    pub model: String,
+    #[serde(default = "defaults::default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "defaults::default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// Wire protocol spoken by the configured API endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiProvider {
+    /// OpenAI-compatible `/chat/completions`
+    #[default]
+    OpenAi,
+    /// Anthropic Messages API (`/messages`)
+    Anthropic,
+    /// Google Gemini API (`:streamGenerateContent`)
+    Gemini,
+}
+
+impl ApiProvider {
+    /// All providers, in the order shown during setup
+    pub const ALL: &'static [ApiProvider] = &[
+        ApiProvider::OpenAi,
+        ApiProvider::Anthropic,
+        ApiProvider::Gemini,
+    ];
+
+    /// Identifier used in config files and on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiProvider::OpenAi => "openai",
+            ApiProvider::Anthropic => "anthropic",
+            ApiProvider::Gemini => "gemini",
+        }
+    }
+
+    /// Default base URL offered during setup
+    pub fn default_api_url(&self) -> &'static str {
+        match self {
+            ApiProvider::OpenAi => "https://api.openai.com/v1",
+            ApiProvider::Anthropic => "https://api.anthropic.com/v1",
+            ApiProvider::Gemini => "https://generativelanguage.googleapis.com/v1beta",
+        }
+    }
+
+    /// Default model offered during setup
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            ApiProvider::OpenAi => "gpt-4",
+            ApiProvider::Anthropic => "claude-sonnet-4-5",
+            ApiProvider::Gemini => "gemini-2.5-pro",
+        }
+    }
+}
+
+impl std::fmt::Display for ApiProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
 }
 
 /// LSP Configuration
@@ -1,3 +1,6 @@
 pub mod config;
 
-pub use config::Config;
+pub use config::{
+    AgentDefinition, ApiProvider, ApprovalPolicy, CompactionConfig, Config, DuetConfig, McpServerConfig, ModelPrice,
+    Permission, PermissionPolicy, PermissionRule, PriceTable, ProviderProfile, SandboxConfig, VerificationConfig,
+};
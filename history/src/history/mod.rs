@@ -5,4 +5,4 @@ mod types;
 
 // Re-export public API
 pub use session::ChatSession;
-pub use types::{FunctionCall, Message, ToolCall};
+pub use types::{FunctionCall, Message, TokenUsage, ToolCall, UsageRecord};
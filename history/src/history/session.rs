@@ -1,6 +1,6 @@
 use super::management;
 use super::persistence;
-use super::types::Message;
+use super::types::{Message, TokenUsage, UsageRecord};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -16,6 +16,12 @@ pub struct ChatSession {
     pub messages: Vec<Message>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Token usage of every API call made in this session
+    #[serde(default)]
+    pub usage: Vec<UsageRecord>,
+    /// Number of user turns folded into summaries by context compaction
+    #[serde(default)]
+    pub compacted_turns: usize,
 }
 
 impl ChatSession {
@@ -28,6 +34,8 @@ impl ChatSession {
             messages: Vec::new(),
             created_at: now,
             updated_at: now,
+            usage: Vec::new(),
+            compacted_turns: 0,
         }
     }
 
@@ -37,6 +45,33 @@ impl ChatSession {
         self.updated_at = Utc::now();
     }
 
+    /// Number of user messages so far, compacted ones included
+    ///
+    /// Right after a user message is added this is the number of the turn it starts,
+    /// later messages injected into the turn (critic replies, plan reviews) count too.
+    pub fn user_turns(&self) -> usize {
+        self.compacted_turns + self.messages.iter().filter(|m| m.role == "user").count()
+    }
+
+    /// Record token usage of an API call made during user turn `turn`
+    pub fn record_usage(&mut self, turn: usize, model: &str, usage: TokenUsage) {
+        self.usage.push(UsageRecord {
+            turn,
+            model: model.to_string(),
+            usage,
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Total token usage of this session
+    pub fn total_usage(&self) -> TokenUsage {
+        let mut total = TokenUsage::default();
+        for record in &self.usage {
+            total += record.usage;
+        }
+        total
+    }
+
     /// Save session to disk
     pub fn save(&self) -> Result<()> {
         persistence::save_session(self)
@@ -28,3 +28,53 @@ pub struct FunctionCall {
     pub name: String,
     pub arguments: String,
 }
+
+/// Token counts reported by the API for a single request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    /// Completion tokens, including reasoning tokens
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub reasoning_tokens: u64,
+    /// Prompt tokens read from the provider's prompt cache, not part of `prompt_tokens`
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Prompt tokens written to the provider's prompt cache, not part of `prompt_tokens`
+    #[serde(default)]
+    pub cache_write_tokens: u64,
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+    }
+}
+
+impl TokenUsage {
+    /// Cost in USD, None if the model has no price
+    pub fn cost(&self, prices: &config::PriceTable, model: &str) -> Option<f64> {
+        prices.cost(
+            model,
+            self.prompt_tokens,
+            self.completion_tokens,
+            self.cache_read_tokens,
+            self.cache_write_tokens,
+        )
+    }
+}
+
+/// Usage of one API call within a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Index of the user turn (1-based) the call belongs to
+    pub turn: usize,
+    pub model: String,
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
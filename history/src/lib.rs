@@ -1,3 +1,3 @@
 pub mod history;
 
-pub use history::{ChatSession, FunctionCall, Message, ToolCall};
+pub use history::{ChatSession, FunctionCall, Message, TokenUsage, ToolCall, UsageRecord};
@@ -211,13 +211,17 @@ pub fn get_messages() -> HashMap<String, String> {
         "setup_welcome".to_string(),
         "Welcome to Friendev! First-time use requires initialization configuration.".to_string(),
     );
+    m.insert(
+        "setup_provider".to_string(),
+        "Please select API provider".to_string(),
+    );
     m.insert(
         "setup_api_key".to_string(),
-        "Please enter OpenAI API Key".to_string(),
+        "Please enter API Key".to_string(),
     );
     m.insert(
         "setup_api_url".to_string(),
-        "Please enter OpenAI Base URL".to_string(),
+        "Please enter API Base URL".to_string(),
     );
     m.insert(
         "setup_model".to_string(),
@@ -514,7 +518,7 @@ pub fn get_messages() -> HashMap<String, String> {
         "run_command_bg_brief".to_string(),
         "Started background command: {}".to_string(),
     );
-    m.insert("run_command_bg_output".to_string(), "Command started in background\nRun ID: {}\nCommand: {}\n\nUse /runcommand info {{}} to check status".to_string());
+    m.insert("run_command_bg_output".to_string(), "Command started in background\nRun ID: {}\nCommand: {}\n\nUse command_status, command_output and command_kill with this run ID to follow it".to_string());
     m.insert(
         "run_command_fg_brief".to_string(),
         "Command executed: {} (exit: {})".to_string(),
@@ -841,5 +845,174 @@ pub fn get_messages() -> HashMap<String, String> {
     m.insert("send_md_read_error".to_string(), "Failed to read send.md file: {}".to_string());
     m.insert("send_md_sending".to_string(), "[INFO] Sending send.md file content to AI...".to_string());
 
+    // Provider profiles
+    m.insert("help_provider".to_string(), "Provider Commands".to_string());
+    m.insert("cmd_provider_list".to_string(), "List provider profiles".to_string());
+    m.insert("cmd_provider_use".to_string(), "Switch to a provider profile".to_string());
+    m.insert("cmd_provider_add".to_string(), "Add or replace a provider profile".to_string());
+    m.insert("cmd_provider_remove".to_string(), "Remove a provider profile".to_string());
+    m.insert("provider_none".to_string(), "No provider profiles configured, add one with /provider add <name>".to_string());
+    m.insert("provider_no_active".to_string(), "No profile active, using the settings from config.json".to_string());
+    m.insert("provider_switched".to_string(), "Switched to provider profile:".to_string());
+    m.insert("provider_saved".to_string(), "Provider profile saved:".to_string());
+    m.insert("provider_kept_settings".to_string(), "Previous settings kept as provider profile:".to_string());
+    m.insert("provider_removed".to_string(), "Provider profile removed:".to_string());
+    m.insert("provider_not_found".to_string(), "Provider profile not found".to_string());
+    m.insert("provider_max_retries".to_string(), "Max retries".to_string());
+    m.insert("provider_retry_delay".to_string(), "Retry delay (ms)".to_string());
+    m.insert("provider_headers".to_string(), "Extra headers (Name: value, comma separated, optional)".to_string());
+
+    // Token usage
+    m.insert("cmd_usage".to_string(), "Show token usage and cost of this session".to_string());
+    m.insert("usage_title".to_string(), "Token Usage".to_string());
+    m.insert("usage_turn".to_string(), "Turn".to_string());
+    m.insert("usage_calls".to_string(), "Calls".to_string());
+    m.insert("usage_prompt".to_string(), "Prompt".to_string());
+    m.insert("usage_cache_read".to_string(), "Cache read".to_string());
+    m.insert("usage_cache_write".to_string(), "Cache write".to_string());
+    m.insert("usage_completion".to_string(), "Completion".to_string());
+    m.insert("usage_reasoning".to_string(), "Reasoning".to_string());
+    m.insert("usage_cost".to_string(), "Cost".to_string());
+    m.insert("usage_total".to_string(), "Total".to_string());
+    m.insert("usage_none".to_string(), "No token usage recorded in this session yet".to_string());
+    m.insert("usage_unpriced".to_string(), "No price for: {} (edit {})".to_string());
+
+    // Context compaction
+    m.insert("cmd_compact".to_string(), "Summarize older turns to free up context".to_string());
+    m.insert("compact_running".to_string(), "Compacting conversation context...".to_string());
+    m.insert("compact_done".to_string(), "Context compacted: {} messages summarized, ~{} -> ~{} tokens".to_string());
+    m.insert("compact_nothing".to_string(), "Nothing to compact yet".to_string());
+    m.insert("compact_failed".to_string(), "Context compaction failed".to_string());
+
+    // Checkpoints
+    m.insert("cmd_checkpoint_list".to_string(), "List checkpoints of this session".to_string());
+    m.insert("cmd_checkpoint_restore".to_string(), "Restore files and conversation to before turn <n>".to_string());
+    m.insert("cmd_undo".to_string(), "Undo the file changes and messages of the last turn".to_string());
+    m.insert("checkpoint_title".to_string(), "Checkpoints".to_string());
+    m.insert("checkpoint_files".to_string(), "files".to_string());
+    m.insert("checkpoint_none".to_string(), "No checkpoints in this session yet".to_string());
+    m.insert("checkpoint_restored".to_string(), "Restored to before turn {} ({} files)".to_string());
+    m.insert("checkpoint_messages_kept".to_string(), "Conversation was compacted since this checkpoint, messages were kept".to_string());
+    m.insert("checkpoint_create_failed".to_string(), "Failed to create checkpoint".to_string());
+
+    // Headless mode
+    m.insert("headless_no_prompt".to_string(), "No prompt given. Usage: friendev -p \"prompt\" or pipe the prompt into friendev -p".to_string());
+    m.insert("headless_no_config".to_string(), "No configuration found. Run friendev once interactively to set it up".to_string());
+    m.insert("headless_approval_denied".to_string(), "Denied (no terminal, use --yolo or --allow):".to_string());
+    m.insert("headless_denied_summary".to_string(), "{} action(s) were denied because they needed approval".to_string());
+    m.insert("headless_bad_output_format".to_string(), "Unknown output format: {} (expected text or stream-json)".to_string());
+
+    // Git tools
+    m.insert("git_status_brief".to_string(), "On {}: {} staged, {} unstaged, {} untracked".to_string());
+    m.insert("git_diff_brief".to_string(), "{} files changed (+{} -{})".to_string());
+    m.insert("git_log_brief".to_string(), "Listed {} commits".to_string());
+    m.insert("git_blame_brief".to_string(), "Blamed {} lines of {}".to_string());
+    m.insert("git_show_brief".to_string(), "Showed {}".to_string());
+    m.insert("git_invalid_ref".to_string(), "Invalid git revision `{}`: it must name an existing commit, branch or tag and must not start with `-`".to_string());
+    m.insert("git_commit_brief".to_string(), "Committed {}".to_string());
+    m.insert("git_commit_empty_message".to_string(), "Commit message must not be empty".to_string());
+    m.insert("git_checkout_brief".to_string(), "Checked out {}".to_string());
+    m.insert("git_checkout_create_with_paths".to_string(), "`create` cannot be combined with `paths`".to_string());
+
+    // MCP server mode
+    m.insert("mcp_serve_started".to_string(), "Serving {} tools over MCP stdio (approval policy: {})".to_string());
+
+    // LSP tools
+    m.insert("lsp_no_server".to_string(), "No language server configured for .{} files, add one to lsp.json".to_string());
+    m.insert("lsp_line_out_of_range".to_string(), "Line {} is past the end of the file ({} lines)".to_string());
+    m.insert("lsp_symbol_not_found".to_string(), "'{}' not found on line {}".to_string());
+    m.insert("lsp_definition_brief".to_string(), "Found {} definitions".to_string());
+    m.insert("lsp_references_brief".to_string(), "Found {} references".to_string());
+    m.insert("lsp_hover_brief".to_string(), "Hover info at {}".to_string());
+    m.insert("lsp_hover_empty".to_string(), "No hover information at this position".to_string());
+    m.insert("lsp_workspace_symbols_brief".to_string(), "Found {} symbols matching '{}'".to_string());
+    m.insert("lsp_diagnostics_brief".to_string(), "{} errors, {} warnings in {}".to_string());
+
+    // Post-edit verification
+    m.insert("verification_new_errors".to_string(), "{} reported {} new errors after this edit, fix them before moving on:".to_string());
+    m.insert("verification_brief".to_string(), "{} new errors".to_string());
+
+    // apply_patch tool
+    m.insert("apply_patch_brief".to_string(), "Patched {} files".to_string());
+    m.insert("apply_patch_empty".to_string(), "The patch contains no file changes".to_string());
+    m.insert("apply_patch_parse_error".to_string(), "Could not parse the patch: {}".to_string());
+    m.insert("apply_patch_failed".to_string(), "Patch not applied, no files were changed: {}".to_string());
+    m.insert("apply_patch_write_failed".to_string(), "Writing the patch failed, all files were restored: {}".to_string());
+
+    // Stale-edit protection
+    m.insert("stale_edit_unread".to_string(), "{} has not been read in this session, read it with file_read before editing it".to_string());
+    m.insert("stale_edit_changed".to_string(), "{} changed since you last read it, nothing was written. Its current content around the edit is below, retry against it (or file_read for more):".to_string());
+    m.insert("stale_edit_brief".to_string(), "{} changed on disk".to_string());
+
+    // Permission policy
+    m.insert("permission_denied".to_string(), "Blocked by permission rule {}".to_string());
+    m.insert("permission_policy_invalid".to_string(), "The permission policy could not be loaded, fix it before running tools: {}".to_string());
+    m.insert("permission_policy_tampered".to_string(), "Policy files changed after sandboxed commands could write to them, review them and restart Friendev to use them: {}".to_string());
+    m.insert("approval_opt_always_pattern".to_string(), "Always Allow {} (saved)".to_string());
+    m.insert("approval_pattern_saved".to_string(), "Saved to .friendev/permissions.json".to_string());
+
+    // Sandbox
+    m.insert("sandbox_config_invalid".to_string(), "Invalid sandbox configuration: {}".to_string());
+    m.insert("sandbox_timed_out".to_string(), "Command killed after the sandbox time limit of {}s".to_string());
+
+    // Background command tools
+    m.insert("command_not_found".to_string(), "Background command not found: {}".to_string());
+    m.insert("command_status_brief".to_string(), "{} background commands".to_string());
+    m.insert("command_status_empty".to_string(), "No background commands".to_string());
+    m.insert("command_output_brief".to_string(), "Read {} bytes of output".to_string());
+    m.insert("command_output_no_log".to_string(), "No log file for background command {}".to_string());
+    m.insert("command_output_header".to_string(), "Run {} ({}), bytes {}-{} of {}, next offset: {}".to_string());
+    m.insert("command_kill_brief".to_string(), "Killed {}".to_string());
+    m.insert("command_kill_not_running".to_string(), "Background command {} is not running".to_string());
+
+    // /runcommand ps, logs and kill
+    m.insert("cmd_runcommand_ps".to_string(), "List background commands".to_string());
+    m.insert("cmd_runcommand_logs".to_string(), "Show the last lines of a background command log".to_string());
+    m.insert("cmd_runcommand_kill".to_string(), "Stop a running background command".to_string());
+    m.insert("runcommand_ps_header".to_string(), "Background commands".to_string());
+    m.insert("runcommand_ps_empty".to_string(), "No background commands".to_string());
+
+    // Persistent shell
+    m.insert("shell_brief".to_string(), "Exit {} in {}".to_string());
+    m.insert("shell_output_header".to_string(), "Exit code: {}
+Directory: {}".to_string());
+    m.insert("shell_timed_out".to_string(), "Command timed out after {}s, the shell was restarted and its directory and environment reset".to_string());
+    m.insert("shell_exited".to_string(), "The shell exited, the next command starts a new one in the workspace".to_string());
+    m.insert("shell_syntax_error".to_string(), "Shell syntax error, nothing was run: {}".to_string());
+    m.insert("shell_start_failed".to_string(), "Failed to start shell: {}".to_string());
+    m.insert("shell_reset".to_string(), "Shell restarted".to_string());
+    m.insert("shell_sandbox_restarted".to_string(), "The sandbox settings changed, so the shell was restarted in the workspace under the new ones: directory and environment were reset".to_string());
+    m.insert("shell_unsupported".to_string(), "The persistent shell is not available on Windows, use run_command".to_string());
+
+    // Plan mode
+    m.insert("plan_mode_blocked".to_string(), "Plan mode is on: {} can change the workspace and is not available. Use read-only tools and finish with a plan for the user to approve.".to_string());
+    m.insert("plan_mode_on".to_string(), "Plan mode on: only read-only tools run, the AI ends with a plan for you to approve".to_string());
+    m.insert("plan_mode_off".to_string(), "Plan mode off".to_string());
+    m.insert("plan_review_prompt".to_string(), "Execute this plan?".to_string());
+    m.insert("plan_opt_approve".to_string(), "Approve and execute".to_string());
+    m.insert("plan_opt_edit".to_string(), "Edit the plan, then execute".to_string());
+    m.insert("plan_opt_revise".to_string(), "Ask for changes".to_string());
+    m.insert("plan_opt_reject".to_string(), "Reject".to_string());
+    m.insert("plan_revise_prompt".to_string(), "What should change".to_string());
+    m.insert("plan_edit_cancelled".to_string(), "Edit cancelled".to_string());
+    m.insert("plan_rejected".to_string(), "Plan rejected, still in plan mode".to_string());
+    m.insert("cmd_plan".to_string(), "Toggle plan mode (read-only, plan before executing)".to_string());
+    m.insert("hint_plan_mode".to_string(), "Shift+Tab or /plan = Toggle plan mode".to_string());
+
+    // Duet mode
+    m.insert("duet_enabled".to_string(), "Duet mode: {} reviews every completed turn".to_string());
+    m.insert("duet_review_wait".to_string(), "Critic is reviewing the turn...".to_string());
+    m.insert("duet_signed_off".to_string(), "Critic signed off".to_string());
+    m.insert("duet_objections".to_string(), "Critic objections (round {}/{}):".to_string());
+    m.insert("duet_round_limit".to_string(), "Round limit reached, the remaining objections were not sent back".to_string());
+    m.insert("duet_critic_failed".to_string(), "Critic review failed".to_string());
+
+    // Subagents
+    m.insert("subagent_tool_not_allowed".to_string(), "{} is not available to this subagent".to_string());
+    m.insert("subagent_turn_limit".to_string(), "Subagent reached its limit of {} turns".to_string());
+    m.insert("output_lines_hidden".to_string(), "... {} earlier lines hidden".to_string());
+    m.insert("subagent_output_saved".to_string(), "Full output: {}".to_string());
+    m.insert("subagents_running".to_string(), "Running {} subagents, at most {} at a time".to_string());
+
     m
 }
@@ -169,13 +169,17 @@ pub fn get_messages() -> HashMap<String, String> {
         "setup_welcome".to_string(),
         "欢迎使用 Friendev！首次使用需要初始化配置。".to_string(),
     );
+    m.insert(
+        "setup_provider".to_string(),
+        "请选择 API 提供商".to_string(),
+    );
     m.insert(
         "setup_api_key".to_string(),
-        "请输入 OpenAI API Key".to_string(),
+        "请输入 API Key".to_string(),
     );
     m.insert(
         "setup_api_url".to_string(),
-        "请输入 OpenAI API URL".to_string(),
+        "请输入 API URL".to_string(),
     );
     m.insert("setup_model".to_string(), "请输入默认模型".to_string());
     m.insert(
@@ -450,7 +454,7 @@ pub fn get_messages() -> HashMap<String, String> {
     );
     m.insert(
         "run_command_bg_output".to_string(),
-        "命令已在后台启动\n运行 ID: {}\n命令: {}\n\n使用 /runcommand info {{}} 查看状态"
+        "命令已在后台启动\n运行 ID: {}\n命令: {}\n\n使用 command_status、command_output 和 command_kill 配合此运行 ID 跟踪命令"
             .to_string(),
     );
     m.insert(
@@ -757,5 +761,174 @@ pub fn get_messages() -> HashMap<String, String> {
     m.insert("send_md_read_error".to_string(), "读取send.md文件失败: {}".to_string());
     m.insert("send_md_sending".to_string(), "[信息] 正在发送 send.md 文件内容给 AI...".to_string());
 
+    // 提供商配置
+    m.insert("help_provider".to_string(), "提供商命令".to_string());
+    m.insert("cmd_provider_list".to_string(), "列出提供商配置".to_string());
+    m.insert("cmd_provider_use".to_string(), "切换到提供商配置".to_string());
+    m.insert("cmd_provider_add".to_string(), "添加或替换提供商配置".to_string());
+    m.insert("cmd_provider_remove".to_string(), "删除提供商配置".to_string());
+    m.insert("provider_none".to_string(), "尚未配置提供商，使用 /provider add <名称> 添加".to_string());
+    m.insert("provider_no_active".to_string(), "未启用任何配置，使用 config.json 中的设置".to_string());
+    m.insert("provider_switched".to_string(), "已切换到提供商配置:".to_string());
+    m.insert("provider_saved".to_string(), "提供商配置已保存:".to_string());
+    m.insert("provider_kept_settings".to_string(), "原有设置已保存为提供商配置:".to_string());
+    m.insert("provider_removed".to_string(), "提供商配置已删除:".to_string());
+    m.insert("provider_not_found".to_string(), "未找到提供商配置".to_string());
+    m.insert("provider_max_retries".to_string(), "最大重试次数".to_string());
+    m.insert("provider_retry_delay".to_string(), "重试间隔 (毫秒)".to_string());
+    m.insert("provider_headers".to_string(), "额外请求头 (名称: 值，逗号分隔，可选)".to_string());
+
+    // Token 用量
+    m.insert("cmd_usage".to_string(), "显示本会话的 Token 用量和费用".to_string());
+    m.insert("usage_title".to_string(), "Token 用量".to_string());
+    m.insert("usage_turn".to_string(), "轮次".to_string());
+    m.insert("usage_calls".to_string(), "请求".to_string());
+    m.insert("usage_prompt".to_string(), "输入".to_string());
+    m.insert("usage_cache_read".to_string(), "缓存读取".to_string());
+    m.insert("usage_cache_write".to_string(), "缓存写入".to_string());
+    m.insert("usage_completion".to_string(), "输出".to_string());
+    m.insert("usage_reasoning".to_string(), "推理".to_string());
+    m.insert("usage_cost".to_string(), "费用".to_string());
+    m.insert("usage_total".to_string(), "合计".to_string());
+    m.insert("usage_none".to_string(), "本会话尚未记录 Token 用量".to_string());
+    m.insert("usage_unpriced".to_string(), "以下模型没有价格: {} (请编辑 {})".to_string());
+
+    // 上下文压缩
+    m.insert("cmd_compact".to_string(), "压缩较早的对话以释放上下文".to_string());
+    m.insert("compact_running".to_string(), "正在压缩对话上下文...".to_string());
+    m.insert("compact_done".to_string(), "上下文已压缩: 总结了 {} 条消息, ~{} -> ~{} tokens".to_string());
+    m.insert("compact_nothing".to_string(), "暂无可压缩的内容".to_string());
+    m.insert("compact_failed".to_string(), "上下文压缩失败".to_string());
+
+    // 检查点
+    m.insert("cmd_checkpoint_list".to_string(), "列出本会话的检查点".to_string());
+    m.insert("cmd_checkpoint_restore".to_string(), "将文件和对话恢复到第 <n> 轮之前".to_string());
+    m.insert("cmd_undo".to_string(), "撤销上一轮的文件修改和消息".to_string());
+    m.insert("checkpoint_title".to_string(), "检查点".to_string());
+    m.insert("checkpoint_files".to_string(), "个文件".to_string());
+    m.insert("checkpoint_none".to_string(), "本会话还没有检查点".to_string());
+    m.insert("checkpoint_restored".to_string(), "已恢复到第 {} 轮之前（{} 个文件）".to_string());
+    m.insert("checkpoint_messages_kept".to_string(), "该检查点之后对话已被压缩，消息未回滚".to_string());
+    m.insert("checkpoint_create_failed".to_string(), "创建检查点失败".to_string());
+
+    // 无头模式
+    m.insert("headless_no_prompt".to_string(), "未提供提示词。用法：friendev -p \"提示词\" 或通过管道传给 friendev -p".to_string());
+    m.insert("headless_no_config".to_string(), "未找到配置。请先以交互方式运行一次 friendev 完成配置".to_string());
+    m.insert("headless_approval_denied".to_string(), "已拒绝（无终端，可使用 --yolo 或 --allow）：".to_string());
+    m.insert("headless_denied_summary".to_string(), "有 {} 个操作因需要审批而被拒绝".to_string());
+    m.insert("headless_bad_output_format".to_string(), "未知的输出格式：{}（应为 text 或 stream-json）".to_string());
+
+    // Git 工具
+    m.insert("git_status_brief".to_string(), "分支 {}：{} 个已暂存，{} 个未暂存，{} 个未跟踪".to_string());
+    m.insert("git_diff_brief".to_string(), "{} 个文件变更 (+{} -{})".to_string());
+    m.insert("git_log_brief".to_string(), "列出 {} 个提交".to_string());
+    m.insert("git_blame_brief".to_string(), "已追溯 {} 行：{}".to_string());
+    m.insert("git_show_brief".to_string(), "已显示 {}".to_string());
+    m.insert("git_invalid_ref".to_string(), "无效的 git 版本 `{}`：必须是已存在的提交、分支或标签，且不能以 `-` 开头".to_string());
+    m.insert("git_commit_brief".to_string(), "已提交 {}".to_string());
+    m.insert("git_commit_empty_message".to_string(), "提交信息不能为空".to_string());
+    m.insert("git_checkout_brief".to_string(), "已检出 {}".to_string());
+    m.insert("git_checkout_create_with_paths".to_string(), "`create` 不能与 `paths` 同时使用".to_string());
+
+    // MCP 服务模式
+    m.insert("mcp_serve_started".to_string(), "正在通过 MCP stdio 提供 {} 个工具（审批策略：{}）".to_string());
+
+    // LSP 工具
+    m.insert("lsp_no_server".to_string(), "没有为 .{} 文件配置语言服务器，请在 lsp.json 中添加".to_string());
+    m.insert("lsp_line_out_of_range".to_string(), "第 {} 行超出文件末尾（共 {} 行）".to_string());
+    m.insert("lsp_symbol_not_found".to_string(), "未找到 '{}'（第 {} 行）".to_string());
+    m.insert("lsp_definition_brief".to_string(), "找到 {} 处定义".to_string());
+    m.insert("lsp_references_brief".to_string(), "找到 {} 处引用".to_string());
+    m.insert("lsp_hover_brief".to_string(), "{} 处的悬停信息".to_string());
+    m.insert("lsp_hover_empty".to_string(), "该位置没有悬停信息".to_string());
+    m.insert("lsp_workspace_symbols_brief".to_string(), "找到 {} 个匹配 '{}' 的符号".to_string());
+    m.insert("lsp_diagnostics_brief".to_string(), "{} 个错误，{} 个警告，位于 {}".to_string());
+
+    // 编辑后校验
+    m.insert("verification_new_errors".to_string(), "本次编辑后 {} 报告了 {} 个新错误，请先修复再继续：".to_string());
+    m.insert("verification_brief".to_string(), "{} 个新错误".to_string());
+
+    // apply_patch 工具
+    m.insert("apply_patch_brief".to_string(), "已修改 {} 个文件".to_string());
+    m.insert("apply_patch_empty".to_string(), "补丁中没有任何文件修改".to_string());
+    m.insert("apply_patch_parse_error".to_string(), "无法解析补丁：{}".to_string());
+    m.insert("apply_patch_failed".to_string(), "补丁未应用，没有文件被修改：{}".to_string());
+    m.insert("apply_patch_write_failed".to_string(), "写入补丁失败，所有文件已恢复：{}".to_string());
+
+    // 过期编辑保护
+    m.insert("stale_edit_unread".to_string(), "本会话中尚未读取 {}，请先用 file_read 读取再编辑".to_string());
+    m.insert("stale_edit_changed".to_string(), "{} 在上次读取后已被修改，未写入任何内容。以下是编辑位置附近的最新内容，请基于它重试（或用 file_read 查看更多）：".to_string());
+    m.insert("stale_edit_brief".to_string(), "{} 已在磁盘上被修改".to_string());
+
+    // 权限策略
+    m.insert("permission_denied".to_string(), "被权限规则阻止：{}".to_string());
+    m.insert("permission_policy_invalid".to_string(), "无法加载权限策略，请修复后再运行工具：{}".to_string());
+    m.insert("permission_policy_tampered".to_string(), "策略文件在沙箱命令可写入期间被修改，请检查后重启 Friendev 以使用新策略：{}".to_string());
+    m.insert("approval_opt_always_pattern".to_string(), "总是允许 {} (保存)".to_string());
+    m.insert("approval_pattern_saved".to_string(), "已保存到 .friendev/permissions.json".to_string());
+
+    // 沙箱
+    m.insert("sandbox_config_invalid".to_string(), "沙箱配置无效: {}".to_string());
+    m.insert("sandbox_timed_out".to_string(), "命令超过沙箱时间限制 {} 秒，已被终止".to_string());
+
+    // 后台命令工具
+    m.insert("command_not_found".to_string(), "未找到后台命令: {}".to_string());
+    m.insert("command_status_brief".to_string(), "{} 个后台命令".to_string());
+    m.insert("command_status_empty".to_string(), "没有后台命令".to_string());
+    m.insert("command_output_brief".to_string(), "读取了 {} 字节输出".to_string());
+    m.insert("command_output_no_log".to_string(), "后台命令 {} 没有日志文件".to_string());
+    m.insert("command_output_header".to_string(), "运行 {} ({})，字节 {}-{}，共 {}，下次偏移: {}".to_string());
+    m.insert("command_kill_brief".to_string(), "已终止 {}".to_string());
+    m.insert("command_kill_not_running".to_string(), "后台命令 {} 未在运行".to_string());
+
+    // /runcommand ps、logs 和 kill
+    m.insert("cmd_runcommand_ps".to_string(), "列出后台命令".to_string());
+    m.insert("cmd_runcommand_logs".to_string(), "显示后台命令日志的末尾几行".to_string());
+    m.insert("cmd_runcommand_kill".to_string(), "终止正在运行的后台命令".to_string());
+    m.insert("runcommand_ps_header".to_string(), "后台命令".to_string());
+    m.insert("runcommand_ps_empty".to_string(), "没有后台命令".to_string());
+
+    // 持久 shell
+    m.insert("shell_brief".to_string(), "退出码 {}，目录 {}".to_string());
+    m.insert("shell_output_header".to_string(), "退出码: {}
+目录: {}".to_string());
+    m.insert("shell_timed_out".to_string(), "命令超过 {} 秒未结束，shell 已重启，目录和环境变量已重置".to_string());
+    m.insert("shell_exited".to_string(), "shell 已退出，下一条命令将在工作区中启动新的 shell".to_string());
+    m.insert("shell_syntax_error".to_string(), "shell 语法错误，未执行任何命令: {}".to_string());
+    m.insert("shell_start_failed".to_string(), "启动 shell 失败: {}".to_string());
+    m.insert("shell_reset".to_string(), "shell 已重启".to_string());
+    m.insert("shell_sandbox_restarted".to_string(), "沙箱设置已更改，shell 已按新设置在工作区中重启，目录和环境变量已重置".to_string());
+    m.insert("shell_unsupported".to_string(), "Windows 上不支持持久 shell，请使用 run_command".to_string());
+
+    // 计划模式
+    m.insert("plan_mode_blocked".to_string(), "计划模式已开启：{} 可能修改工作区，当前不可用。请使用只读工具调查，并以供用户批准的计划结束。".to_string());
+    m.insert("plan_mode_on".to_string(), "计划模式已开启：只运行只读工具，AI 最后会给出计划供你批准".to_string());
+    m.insert("plan_mode_off".to_string(), "计划模式已关闭".to_string());
+    m.insert("plan_review_prompt".to_string(), "执行此计划吗？".to_string());
+    m.insert("plan_opt_approve".to_string(), "批准并执行".to_string());
+    m.insert("plan_opt_edit".to_string(), "编辑计划后执行".to_string());
+    m.insert("plan_opt_revise".to_string(), "要求修改".to_string());
+    m.insert("plan_opt_reject".to_string(), "拒绝".to_string());
+    m.insert("plan_revise_prompt".to_string(), "需要修改的内容".to_string());
+    m.insert("plan_edit_cancelled".to_string(), "已取消编辑".to_string());
+    m.insert("plan_rejected".to_string(), "已拒绝计划，仍处于计划模式".to_string());
+    m.insert("cmd_plan".to_string(), "切换计划模式（只读，先计划后执行）".to_string());
+    m.insert("hint_plan_mode".to_string(), "Shift+Tab 或 /plan = 切换计划模式".to_string());
+
+    // 双人模式
+    m.insert("duet_enabled".to_string(), "双人模式：{} 会审查每一轮完成的工作".to_string());
+    m.insert("duet_review_wait".to_string(), "评审模型正在审查本轮工作...".to_string());
+    m.insert("duet_signed_off".to_string(), "评审模型已认可".to_string());
+    m.insert("duet_objections".to_string(), "评审意见（第 {}/{} 轮）：".to_string());
+    m.insert("duet_round_limit".to_string(), "已达到轮数上限，剩余评审意见未发回".to_string());
+    m.insert("duet_critic_failed".to_string(), "评审失败".to_string());
+
+    // 子代理
+    m.insert("subagent_tool_not_allowed".to_string(), "此子代理不能使用 {}".to_string());
+    m.insert("subagent_turn_limit".to_string(), "子代理已达到 {} 轮的上限".to_string());
+    m.insert("output_lines_hidden".to_string(), "... 已折叠前面 {} 行".to_string());
+    m.insert("subagent_output_saved".to_string(), "完整输出：{}".to_string());
+    m.insert("subagents_running".to_string(), "正在运行 {} 个子代理，最多同时运行 {} 个".to_string());
+
     m
 }
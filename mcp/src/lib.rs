@@ -173,6 +173,28 @@ impl McpIntegration {
         Ok(server_tools)
     }
 
+    /// Whether a tool is marked read-only by its server through the `readOnlyHint` annotation
+    ///
+    /// Accepts `server/tool` or a bare tool name looked up in every connected server.
+    pub async fn is_read_only_tool(&self, name: &str) -> bool {
+        let (servers, tool_name) = match name.split_once('/') {
+            Some((server, tool)) => (vec![server.to_string()], tool),
+            None => (self.list_servers(), name),
+        };
+
+        for server_name in servers {
+            let Some(client) = self.manager.clients.get(&server_name) else {
+                continue;
+            };
+            if let Ok(response) = client.list_tools(Default::default()).await {
+                if let Some(tool) = response.tools.iter().find(|t| t.name == tool_name) {
+                    return tool.annotations.as_ref().and_then(|a| a.read_only_hint) == Some(true);
+                }
+            }
+        }
+        false
+    }
+
     /// Get tool definitions for all connected servers
     pub async fn get_server_tools_definitions(&self) -> Vec<ToolDefinition> {
         let mut all_tools = Vec::new();
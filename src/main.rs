@@ -4,11 +4,25 @@ use anyhow::Result;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Serve the builtin tools to other agents: friendev mcp-serve
+    if std::env::args().nth(1).as_deref() == Some("mcp-serve") {
+        let code = app::run_mcp_server().await;
+        tools::background::kill_all();
+        std::process::exit(code?);
+    }
+
+    // Headless one-shot mode: friendev -p "prompt"
+    if let Some(args) = app::parse_headless_args() {
+        let code = app::run_headless(args).await;
+        tools::background::kill_all();
+        std::process::exit(code?);
+    }
+
     // Initialize application
     let state = app::initialize_app().await?;
 
-    // Run REPL loop
-    app::run_repl(state).await?;
-
-    Ok(())
+    // Run REPL loop, background commands don't outlive it
+    let result = app::run_repl(state).await;
+    tools::background::kill_all();
+    result
 }
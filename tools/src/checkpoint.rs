@@ -0,0 +1,305 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Checkpoint that mutating tools snapshot into, set for the duration of a user turn
+static ACTIVE_CHECKPOINT: Mutex<Option<ActiveCheckpoint>> = Mutex::new(None);
+
+struct ActiveCheckpoint {
+    dir: PathBuf,
+    manifest: CheckpointManifest,
+}
+
+/// Checkpoint of one user turn, stored as `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointManifest {
+    /// User turn number (1-based)
+    pub turn: usize,
+    /// Number of session messages before the turn started
+    pub message_count: usize,
+    /// `ChatSession::compacted_turns` when the turn started, message_count is
+    /// only meaningful while this is unchanged
+    pub compacted_turns: usize,
+    /// First line of the user prompt
+    pub prompt: String,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<FileSnapshot>,
+}
+
+/// Pre-image of a file modified during the turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSnapshot {
+    pub path: PathBuf,
+    /// False if the file was created during the turn
+    pub existed: bool,
+    /// Backup file name inside the checkpoint directory
+    pub backup: Option<String>,
+}
+
+/// Directory holding all checkpoints of a session
+pub fn checkpoints_dir(working_dir: &Path, session_id: &str) -> PathBuf {
+    working_dir
+        .join(".friendev")
+        .join("checkpoints")
+        .join(session_id)
+}
+
+/// Records into its checkpoint until dropped
+#[must_use = "the checkpoint stops recording when the guard is dropped"]
+pub struct TurnGuard {
+    dir: PathBuf,
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        let mut active = ACTIVE_CHECKPOINT.lock().unwrap();
+        // A newer turn may have replaced this one already
+        if active.as_ref().is_some_and(|a| a.dir == self.dir) {
+            *active = None;
+        }
+    }
+}
+
+/// Number of the user turn being recorded, if one is
+pub fn active_turn() -> Option<usize> {
+    ACTIVE_CHECKPOINT.lock().unwrap().as_ref().map(|a| a.manifest.turn)
+}
+
+/// Start a checkpoint for a new user turn
+pub fn begin_turn(
+    working_dir: &Path,
+    session_id: &str,
+    turn: usize,
+    message_count: usize,
+    compacted_turns: usize,
+    prompt: &str,
+) -> Result<TurnGuard> {
+    let dir = checkpoints_dir(working_dir, session_id).join(turn.to_string());
+    // Turn numbers are reused after an undo
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(dir.join("files"))?;
+
+    let manifest = CheckpointManifest {
+        turn,
+        message_count,
+        compacted_turns,
+        prompt: prompt.lines().next().unwrap_or("").chars().take(80).collect(),
+        created_at: Utc::now(),
+        files: Vec::new(),
+    };
+    write_manifest(&dir, &manifest)?;
+
+    *ACTIVE_CHECKPOINT.lock().unwrap() = Some(ActiveCheckpoint {
+        dir: dir.clone(),
+        manifest,
+    });
+    Ok(TurnGuard { dir })
+}
+
+/// Save the pre-image of a file before it is modified
+///
+/// Only the first modification in a turn is recorded. Does nothing outside a turn.
+pub fn snapshot(path: &Path) -> Result<()> {
+    let mut active = ACTIVE_CHECKPOINT.lock().unwrap();
+    let Some(checkpoint) = active.as_mut() else {
+        return Ok(());
+    };
+
+    if checkpoint.manifest.files.iter().any(|f| f.path == path) {
+        return Ok(());
+    }
+
+    let existed = path.is_file();
+    let backup = if existed {
+        let name = checkpoint.manifest.files.len().to_string();
+        fs::copy(path, checkpoint.dir.join("files").join(&name))?;
+        Some(name)
+    } else {
+        None
+    };
+
+    checkpoint.manifest.files.push(FileSnapshot {
+        path: path.to_path_buf(),
+        existed,
+        backup,
+    });
+    write_manifest(&checkpoint.dir, &checkpoint.manifest)
+}
+
+/// List checkpoints of a session, oldest first
+pub fn list(working_dir: &Path, session_id: &str) -> Result<Vec<CheckpointManifest>> {
+    let dir = checkpoints_dir(working_dir, session_id);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut manifests = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let manifest_path = entry?.path().join("manifest.json");
+        if let Ok(content) = fs::read_to_string(&manifest_path) {
+            if let Ok(manifest) = serde_json::from_str::<CheckpointManifest>(&content) {
+                manifests.push(manifest);
+            }
+        }
+    }
+    manifests.sort_by_key(|m| m.turn);
+    Ok(manifests)
+}
+
+/// Restore files to their state before `turn` and drop that checkpoint and all later ones
+///
+/// Returns the manifest of `turn` so the caller can roll back the session messages.
+pub fn restore(working_dir: &Path, session_id: &str, turn: usize) -> Result<CheckpointManifest> {
+    let manifests = list(working_dir, session_id)?;
+    let target = manifests
+        .iter()
+        .find(|m| m.turn == turn)
+        .cloned()
+        .ok_or_else(|| anyhow!("Checkpoint {} not found", turn))?;
+
+    *ACTIVE_CHECKPOINT.lock().unwrap() = None;
+    let base = checkpoints_dir(working_dir, session_id);
+
+    // Newest first, so the oldest pre-image of a file is written last
+    for manifest in manifests.iter().rev().filter(|m| m.turn >= turn) {
+        let dir = base.join(manifest.turn.to_string());
+        for file in manifest.files.iter().rev() {
+            match &file.backup {
+                Some(backup) if file.existed => {
+                    if let Some(parent) = file.path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::copy(dir.join("files").join(backup), &file.path)?;
+                }
+                _ => {
+                    if file.path.is_file() {
+                        fs::remove_file(&file.path)?;
+                    }
+                }
+            }
+        }
+        fs::remove_dir_all(&dir)?;
+    }
+
+    Ok(target)
+}
+
+/// Unified diff of the files a turn changed, from their pre-images to what is on disk now
+///
+/// Only edits made through the file tools are covered, files changed by commands are not.
+pub fn diff(working_dir: &Path, session_id: &str, manifest: &CheckpointManifest) -> Result<String> {
+    let dir = checkpoints_dir(working_dir, session_id).join(manifest.turn.to_string());
+    let null = PathBuf::from("/dev/null");
+    let mut out = String::new();
+
+    for file in &manifest.files {
+        let old = match &file.backup {
+            Some(backup) if file.existed => dir.join("files").join(backup),
+            _ => null.clone(),
+        };
+        let new = if file.path.is_file() { file.path.clone() } else { null.clone() };
+        if old == new {
+            continue;
+        }
+
+        let output = Command::new("git")
+            .args(["diff", "--no-index", "--no-color", "--no-ext-diff", "--"])
+            .arg(&old)
+            .arg(&new)
+            .output()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let name = file.path.strip_prefix(working_dir).unwrap_or(&file.path).display().to_string();
+
+        if let Some(start) = text.find("\n@@") {
+            // git labels the sides with the backup path, name them after the file instead
+            let label = |path: &Path, side: &str| {
+                if *path == null { "/dev/null".to_string() } else { format!("{}/{}", side, name) }
+            };
+            out.push_str(&format!("--- {}\n+++ {}\n", label(&old, "a"), label(&new, "b")));
+            out.push_str(&text[start + 1..]);
+        } else if text.contains("Binary files") {
+            out.push_str(&format!("Binary file {} changed\n", name));
+        }
+    }
+    Ok(out)
+}
+
+fn write_manifest(dir: &Path, manifest: &CheckpointManifest) -> Result<()> {
+    let content = serde_json::to_string_pretty(manifest)?;
+    fs::write(dir.join("manifest.json"), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The active checkpoint is global, tests recording into it take turns
+    static TURN_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_restore_rolls_back_edits_and_creations() {
+        let _lock = TURN_LOCK.lock().unwrap();
+        let working_dir = std::env::temp_dir().join(format!("friendev-cp-{}", std::process::id()));
+        fs::create_dir_all(&working_dir).unwrap();
+        let edited = working_dir.join("edited.txt");
+        let created = working_dir.join("created.txt");
+        fs::write(&edited, "original").unwrap();
+
+        let turn = begin_turn(&working_dir, "s", 1, 0, 0, "first").unwrap();
+        snapshot(&edited).unwrap();
+        fs::write(&edited, "turn 1").unwrap();
+        drop(turn);
+
+        let turn = begin_turn(&working_dir, "s", 2, 2, 0, "second").unwrap();
+        assert_eq!(active_turn(), Some(2));
+        snapshot(&edited).unwrap();
+        fs::write(&edited, "turn 2").unwrap();
+        snapshot(&created).unwrap();
+        fs::write(&created, "new").unwrap();
+        drop(turn);
+        assert_eq!(active_turn(), None);
+
+        assert_eq!(list(&working_dir, "s").unwrap().len(), 2);
+
+        let manifest = restore(&working_dir, "s", 1).unwrap();
+        assert_eq!(manifest.message_count, 0);
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "original");
+        assert!(!created.exists());
+        assert!(list(&working_dir, "s").unwrap().is_empty());
+
+        fs::remove_dir_all(&working_dir).unwrap();
+    }
+
+    #[test]
+    fn test_diff_covers_edited_and_created_files() {
+        let _lock = TURN_LOCK.lock().unwrap();
+        let working_dir = std::env::temp_dir().join(format!("friendev-cp-diff-{}", std::process::id()));
+        fs::create_dir_all(&working_dir).unwrap();
+        let edited = working_dir.join("edited.txt");
+        let created = working_dir.join("created.txt");
+        fs::write(&edited, "one\ntwo\n").unwrap();
+
+        let turn = begin_turn(&working_dir, "s", 1, 0, 0, "edit").unwrap();
+        snapshot(&edited).unwrap();
+        fs::write(&edited, "one\n2\n").unwrap();
+        snapshot(&created).unwrap();
+        fs::write(&created, "new\n").unwrap();
+        drop(turn);
+
+        let manifest = list(&working_dir, "s").unwrap().pop().unwrap();
+        let diff = diff(&working_dir, "s", &manifest).unwrap();
+        assert!(diff.contains("--- a/edited.txt\n+++ b/edited.txt\n"));
+        assert!(diff.contains("-two\n+2\n"));
+        assert!(diff.contains("--- /dev/null\n+++ b/created.txt\n"));
+        assert!(diff.contains("+new\n"));
+
+        fs::remove_dir_all(&working_dir).unwrap();
+    }
+}
@@ -1,7 +1,11 @@
+pub mod checkpoint;
 pub mod hooks;
+pub mod permissions;
+pub mod plan_mode;
 pub mod tools;
 
 pub use hooks::{HookType, execute_hook, HookContext};
+pub use plan_mode::{is_plan_mode, set_plan_mode};
 
 pub use tools::{
     execute_tool,
@@ -9,8 +13,15 @@ pub use tools::{
     get_available_tools_with_mcp,  // 来自feat分支
     get_tools_description,
     get_tools_description_with_mcp,  // 来自feat分支
+    is_read_only_tool,
+    is_investigator,
+    subagent_tools,
+    custom_agent,
+    load_custom_agents,
+    MAX_PARALLEL_SUBAGENTS,
     types::{Tool, ToolFunction, ToolResult},
     command_manager::CommandConfig,
+    background,
 };
 
 // Re-export MCP-enabled executor functions
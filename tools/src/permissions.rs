@@ -0,0 +1,482 @@
+use anyhow::{Context, Result};
+use config::{Permission, PermissionPolicy, PermissionRule, SandboxConfig};
+use globset::{Glob, GlobBuilder};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::tools::executor::file_operations::apply_patch_paths;
+use crate::tools::executor::file_operations::file_common::normalize_path;
+use crate::tools::shell_parser;
+
+tokio::task_local! {
+    /// Policy decision and workspace of the tool call running in this task
+    static SCOPE: CallScope;
+}
+
+/// Contents of each policy file, `None` when it doesn't exist
+type PolicySnapshot = Vec<Option<Vec<u8>>>;
+
+/// Policy files of each workspace as they were before a sandboxed command could write them
+static POLICY_SNAPSHOTS: Mutex<Option<HashMap<PathBuf, PolicySnapshot>>> = Mutex::new(None);
+
+struct CallScope {
+    working_dir: PathBuf,
+    permission: Option<Permission>,
+}
+
+/// Strictest rule matching a tool call
+pub struct Verdict {
+    pub permission: Permission,
+    /// The rule as written in the policy file
+    pub rule: String,
+}
+
+/// What a tool call touches, as far as the rules can see
+struct Request {
+    tool: String,
+    paths: Vec<PathBuf>,
+    command: Option<String>,
+    /// Each program the command line runs, `None` when it can't be parsed
+    invocations: Option<Vec<String>>,
+    /// A program is only known once variables or substitutions are expanded
+    unknown_program: bool,
+    /// The command writes to paths nobody can locate: relative ones while its directory
+    /// is unknown, or ones named by variables or substitutions
+    unresolved_paths: bool,
+}
+
+/// Match a tool call against the global and project rules, `None` when no rule applies
+///
+/// `command_dir` is where a command runs, which for a persistent shell is wherever
+/// it last `cd`-ed to, `None` when that is unknown.
+pub fn evaluate(tool: &str, arguments: &str, working_dir: &Path, command_dir: Option<&Path>) -> Result<Option<Verdict>> {
+    let policy = PermissionPolicy::load(working_dir)?;
+    if policy.rules.is_empty() {
+        return Ok(None);
+    }
+    let request = Request::new(tool, arguments, working_dir, command_dir);
+
+    let mut verdict: Option<Verdict> = None;
+    for rule in &policy.rules {
+        if !rule_matches(rule, &request, working_dir)? {
+            continue;
+        }
+        if verdict.as_ref().is_none_or(|v| rule.permission > v.permission) {
+            verdict = Some(Verdict {
+                permission: rule.permission,
+                rule: serde_json::to_string(rule)?,
+            });
+        }
+    }
+
+    // Path rules can't be checked against targets nobody can locate, so ask
+    if request.unresolved_paths && verdict.as_ref().is_none_or(|v| v.permission < Permission::Ask) {
+        for rule in policy.rules.iter().filter(|r| r.path.is_some() || r.outside_workspace) {
+            if tool_matches(rule, &request)? {
+                verdict = Some(Verdict {
+                    permission: Permission::Ask,
+                    rule: serde_json::to_string(rule)?,
+                });
+                break;
+            }
+        }
+    }
+    Ok(verdict)
+}
+
+/// Run a tool call with its policy decision visible to the approval code
+pub async fn scoped<F: Future>(working_dir: &Path, permission: Option<Permission>, call: F) -> F::Output {
+    let scope = CallScope {
+        working_dir: working_dir.to_path_buf(),
+        permission,
+    };
+    SCOPE.scope(scope, call).await
+}
+
+/// An `ask` rule matched, session-wide approvals don't apply
+pub fn must_ask() -> bool {
+    SCOPE
+        .try_with(|s| s.permission == Some(Permission::Ask))
+        .unwrap_or(false)
+}
+
+/// An `allow` rule matched, the call runs without a prompt
+pub fn is_allowed() -> bool {
+    SCOPE
+        .try_with(|s| s.permission == Some(Permission::Allow))
+        .unwrap_or(false)
+}
+
+/// Who approved a call that runs without a prompt, `rule` when an allow rule matched and
+/// `auto` for `--yolo`, `--allow` or a session approval
+pub fn auto_reviewer() -> &'static str {
+    if is_allowed() {
+        "rule"
+    } else {
+        "auto"
+    }
+}
+
+/// Allow rule offered at the approval prompt for a file action, and how it is shown
+///
+/// Covers files with the same extension in the same directory.
+pub fn suggest_file_rule(tool: &str, path: &Path) -> Option<(PermissionRule, String)> {
+    let working_dir = SCOPE.try_with(|s| s.working_dir.clone()).ok()?;
+    let relative = lexical_normalize(path).strip_prefix(lexical_normalize(&working_dir)).ok()?.to_path_buf();
+    let name = relative.file_name()?.to_str()?;
+
+    let file_glob = match Path::new(name).extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("*.{}", ext),
+        None => name.to_string(),
+    };
+    let glob = match relative.parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(dir) => format!("{}/{}", dir.to_string_lossy().replace('\\', "/"), file_glob),
+        None => format!("/{}", file_glob),
+    };
+
+    let shown = format!("{} {}", tool, glob);
+    let rule = PermissionRule {
+        permission: Permission::Allow,
+        tool: Some(tool.to_string()),
+        path: Some(glob),
+        command: None,
+        outside_workspace: false,
+    };
+    Some((rule, shown))
+}
+
+/// Allow rule offered at the approval prompt for a command: the program and its subcommand
+pub fn suggest_command_rule(tool: &str, command: &str) -> Option<(PermissionRule, String)> {
+    let mut words = command.split_whitespace();
+    let program = words.next()?;
+    let mut pattern = format!("^{}", regex::escape(program));
+    if let Some(sub) = words.next().filter(|w| w.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') && !w.starts_with('-')) {
+        pattern.push_str(&format!(r"\s+{}", regex::escape(sub)));
+    }
+    pattern.push_str(r"(\s|$)");
+
+    let shown = format!("{} /{}/", tool, pattern);
+    let rule = PermissionRule {
+        permission: Permission::Allow,
+        tool: Some(tool.to_string()),
+        path: None,
+        command: Some(pattern),
+        outside_workspace: false,
+    };
+    Some((rule, shown))
+}
+
+/// Save a rule chosen at the approval prompt to the project policy
+pub fn save_rule(rule: PermissionRule) -> Result<()> {
+    let working_dir = SCOPE
+        .try_with(|s| s.working_dir.clone())
+        .context("no tool call in progress")?;
+    PermissionPolicy::add_project_rule(&working_dir, rule)?;
+    refresh_policy(&working_dir);
+    Ok(())
+}
+
+/// Files deciding what tool calls may do, they live in the workspace a sandbox lets commands write
+fn policy_files(working_dir: &Path) -> [PathBuf; 3] {
+    [
+        PermissionPolicy::project_path(working_dir),
+        SandboxConfig::project_path(working_dir),
+        working_dir.join(".friendev").join("hooks.json"),
+    ]
+}
+
+fn read_policy(working_dir: &Path) -> PolicySnapshot {
+    policy_files(working_dir).iter().map(|path| std::fs::read(path).ok()).collect()
+}
+
+/// Remember the workspace's policy files before the first sandboxed command starts
+pub fn protect_policy(working_dir: &Path) {
+    let mut snapshots = POLICY_SNAPSHOTS.lock().unwrap();
+    snapshots
+        .get_or_insert_with(HashMap::new)
+        .entry(working_dir.to_path_buf())
+        .or_insert_with(|| read_policy(working_dir));
+}
+
+/// Accept the current policy files after Friendev itself changed them
+fn refresh_policy(working_dir: &Path) {
+    if let Some(snapshot) = POLICY_SNAPSHOTS.lock().unwrap().as_mut().and_then(|s| s.get_mut(working_dir)) {
+        *snapshot = read_policy(working_dir);
+    }
+}
+
+/// Fail when policy files changed since sandboxed commands could write them, the
+/// change may come from a command trying to lift its own restrictions
+pub fn check_policy(working_dir: &Path) -> Result<()> {
+    let snapshots = POLICY_SNAPSHOTS.lock().unwrap();
+    let Some(snapshot) = snapshots.as_ref().and_then(|s| s.get(working_dir)) else {
+        return Ok(());
+    };
+    let changed: Vec<String> = policy_files(working_dir)
+        .iter()
+        .zip(read_policy(working_dir).iter().zip(snapshot))
+        .filter(|(_, (now, before))| now != before)
+        .map(|(path, _)| path.display().to_string())
+        .collect();
+    if !changed.is_empty() {
+        anyhow::bail!("{}", changed.join(", "));
+    }
+    Ok(())
+}
+
+impl Request {
+    fn new(tool: &str, arguments: &str, working_dir: &Path, command_dir: Option<&Path>) -> Self {
+        let args: Value = serde_json::from_str(arguments).unwrap_or(Value::Null);
+        let mut paths: Vec<String> = args
+            .get("path")
+            .and_then(Value::as_str)
+            .filter(|p| !p.starts_with("mcp://"))
+            .map(|p| vec![p.to_string()])
+            .unwrap_or_default();
+        if let Some(patch) = args.get("patch").and_then(Value::as_str) {
+            paths.extend(apply_patch_paths(patch));
+        }
+        let mut paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|p| lexical_normalize(&normalize_path(p, working_dir)))
+            .collect();
+
+        let command = args.get("command").and_then(Value::as_str).map(str::to_string);
+        // Redirect and `tee` targets count as paths the command writes, relative to where it runs
+        let analysis = command.as_deref().and_then(|c| shell_parser::analyze(c).ok());
+        let mut unresolved_paths = analysis.as_ref().is_some_and(|a| a.unknown_write);
+        if let Some(analysis) = &analysis {
+            for target in &analysis.write_targets {
+                match command_dir {
+                    Some(dir) => paths.push(lexical_normalize(&dir.join(target))),
+                    None if target.is_absolute() => paths.push(lexical_normalize(target)),
+                    None => unresolved_paths = true,
+                }
+            }
+        }
+
+        Self {
+            tool: tool.to_string(),
+            paths,
+            command,
+            unknown_program: analysis.as_ref().is_some_and(|a| a.unknown_program),
+            invocations: analysis.map(|a| a.invocations.iter().map(|inv| inv.text()).collect()),
+            unresolved_paths,
+        }
+    }
+}
+
+fn tool_matches(rule: &PermissionRule, request: &Request) -> Result<bool> {
+    let Some(tool) = &rule.tool else {
+        return Ok(true);
+    };
+    let matcher = Glob::new(tool)
+        .with_context(|| format!("invalid tool pattern {}", tool))?
+        .compile_matcher();
+    Ok(matcher.is_match(&request.tool))
+}
+
+fn rule_matches(rule: &PermissionRule, request: &Request, working_dir: &Path) -> Result<bool> {
+    if !tool_matches(rule, request)? {
+        return Ok(false);
+    }
+
+    if let Some(command) = &rule.command {
+        let pattern = Regex::new(command).with_context(|| format!("invalid command regex {}", command))?;
+        let Some(line) = &request.command else {
+            return Ok(false);
+        };
+        // Allowing needs every program of the line known and covered, one is enough to ask or deny
+        let covered = match &request.invocations {
+            Some(invocations) if rule.permission == Permission::Allow => {
+                !request.unknown_program && pattern.is_match(line) && invocations.iter().all(|c| pattern.is_match(c))
+            }
+            Some(invocations) => pattern.is_match(line) || invocations.iter().any(|c| pattern.is_match(c)),
+            None => pattern.is_match(line) && rule.permission != Permission::Allow,
+        };
+        if !covered {
+            return Ok(false);
+        }
+    }
+
+    if rule.path.is_some() || rule.outside_workspace {
+        if request.paths.is_empty() || (request.unresolved_paths && rule.permission == Permission::Allow) {
+            return Ok(false);
+        }
+        let root = lexical_normalize(working_dir);
+        let matched = request
+            .paths
+            .iter()
+            .map(|path| path_matches(rule, path, &root))
+            .collect::<Result<Vec<_>>>()?;
+        // Allowing needs every path covered, one path is enough to ask or deny
+        let covered = if rule.permission == Permission::Allow {
+            matched.iter().all(|m| *m)
+        } else {
+            matched.iter().any(|m| *m)
+        };
+        if !covered {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+fn path_matches(rule: &PermissionRule, path: &Path, root: &Path) -> Result<bool> {
+    let relative = path.strip_prefix(root).ok();
+    if rule.outside_workspace && relative.is_some() {
+        return Ok(false);
+    }
+    let Some(pattern) = &rule.path else {
+        return Ok(true);
+    };
+
+    // Like gitignore: a leading `/` anchors to the workspace, no `/` matches any file name
+    let anchored = pattern.strip_prefix('/').filter(|_| relative.is_some());
+    let glob = GlobBuilder::new(anchored.unwrap_or(pattern))
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("invalid path pattern {}", pattern))?
+        .compile_matcher();
+    let target = relative.unwrap_or(path);
+    if glob.is_match(target) {
+        return Ok(true);
+    }
+    let bare_name = anchored.is_none() && !pattern.contains('/');
+    Ok(bare_name && path.file_name().is_some_and(|name| glob.is_match(name)))
+}
+
+/// Resolve `.` and `..` without touching the filesystem, the file may not exist yet
+fn lexical_normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(permission: Permission, tool: Option<&str>, path: Option<&str>, command: Option<&str>) -> PermissionRule {
+        PermissionRule {
+            permission,
+            tool: tool.map(str::to_string),
+            path: path.map(str::to_string),
+            command: command.map(str::to_string),
+            outside_workspace: false,
+        }
+    }
+
+    fn matches(rule: &PermissionRule, tool: &str, arguments: &str) -> bool {
+        let root = Path::new("/work");
+        rule_matches(rule, &Request::new(tool, arguments, root, Some(root)), root).unwrap()
+    }
+
+    #[test]
+    fn test_path_rules() {
+        let env = rule(Permission::Deny, None, Some(".env*"), None);
+        assert!(matches(&env, "file_write", r#"{"path": "config/.env.local"}"#));
+        assert!(!matches(&env, "file_write", r#"{"path": "src/env.rs"}"#));
+
+        let anchored = rule(Permission::Allow, Some("file_*"), Some("/src/*.rs"), None);
+        assert!(matches(&anchored, "file_replace", r#"{"path": "./src/main.rs"}"#));
+        assert!(!matches(&anchored, "file_replace", r#"{"path": "lib/src/main.rs"}"#));
+        assert!(!matches(&anchored, "file_replace", r#"{"path": "src/bin/tool.rs"}"#));
+        assert!(!matches(&anchored, "run_command", r#"{"path": "src/main.rs"}"#));
+
+        let mut outside = rule(Permission::Deny, Some("file_write"), None, None);
+        outside.outside_workspace = true;
+        assert!(matches(&outside, "file_write", r#"{"path": "../elsewhere/a.txt"}"#));
+        assert!(matches(&outside, "file_write", r#"{"path": "/etc/hosts"}"#));
+        assert!(!matches(&outside, "file_write", r#"{"path": "a/../b.txt"}"#));
+    }
+
+    #[test]
+    fn test_command_rules() {
+        let cargo = rule(Permission::Allow, Some("run_command"), None, Some(r"^cargo\s+(check|test)\b"));
+        assert!(matches(&cargo, "run_command", r#"{"command": "cargo test --all"}"#));
+        assert!(!matches(&cargo, "run_command", r#"{"command": "cargo publish"}"#));
+        assert!(!matches(&cargo, "file_read", r#"{"path": "Cargo.toml"}"#));
+
+        let (suggested, shown) = suggest_command_rule("run_command", "cargo test -p tools").unwrap();
+        assert_eq!(shown, r"run_command /^cargo\s+test(\s|$)/");
+        assert!(matches(&suggested, "run_command", r#"{"command": "cargo test"}"#));
+        assert!(!matches(&suggested, "run_command", r#"{"command": "cargo tester"}"#));
+
+        // Every program of the line must be allowed, any one of them can be denied
+        assert!(!matches(&cargo, "run_command", r#"{"command": "cargo test && rm -rf /"}"#));
+        assert!(!matches(&cargo, "run_command", r#"{"command": "cargo test 'unterminated"}"#));
+        let rm = rule(Permission::Deny, Some("run_command"), None, Some(r"^rm\s"));
+        assert!(matches(&rm, "run_command", r#"{"command": "cd build && sudo rm -rf out"}"#));
+        assert!(matches(&rm, "run_command", r#"{"command": "rm 'unterminated"}"#));
+
+        // Programs and targets behind variables or substitutions satisfy no allow rule
+        let any = rule(Permission::Allow, Some("run_command"), None, Some(".*"));
+        assert!(matches(&any, "run_command", r#"{"command": "ls -la"}"#));
+        assert!(!matches(&any, "run_command", r#"{"command": "$(echo rm) -rf x"}"#));
+        assert!(!matches(&any, "run_command", r#"{"command": "\"$CMD\" x"}"#));
+        let anywhere = rule(Permission::Allow, None, Some("**"), None);
+        assert!(matches(&anywhere, "run_command", r#"{"command": "echo a > out.txt"}"#));
+        assert!(!matches(&anywhere, "run_command", r#"{"command": "echo a > \"$PWD/.env\""}"#));
+    }
+
+    #[test]
+    fn test_command_write_targets() {
+        let env = rule(Permission::Deny, None, Some(".env*"), None);
+        assert!(matches(&env, "run_command", r#"{"command": "cd config && echo KEY=1 >> .env"}"#));
+        assert!(matches(&env, "run_command", r#"{"command": "echo KEY=1 | tee .env.local"}"#));
+        assert!(!matches(&env, "run_command", r#"{"command": "cat .env > out.txt"}"#));
+    }
+
+    #[test]
+    fn test_policy_changed_by_sandboxed_command() {
+        let root = std::env::temp_dir().join(format!("friendev-policy-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".friendev")).unwrap();
+        let sandbox = SandboxConfig::project_path(&root);
+        std::fs::write(&sandbox, r#"{"enabled": true}"#).unwrap();
+
+        assert!(check_policy(&root).is_ok());
+        protect_policy(&root);
+        std::fs::write(&sandbox, r#"{"enabled": false}"#).unwrap();
+        let err = check_policy(&root).unwrap_err().to_string();
+        assert!(err.contains("sandbox.json"));
+
+        refresh_policy(&root);
+        assert!(check_policy(&root).is_ok());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_shell_directory() {
+        let root = Path::new("/work");
+        let args = r#"{"command": "echo KEY=1 > .env"}"#;
+        let env = rule(Permission::Deny, None, Some("/config/.env"), None);
+        let mut outside = rule(Permission::Deny, None, None, None);
+        outside.outside_workspace = true;
+
+        // Targets resolve against the directory the shell has `cd`-ed to
+        let request = Request::new("shell", args, root, Some(Path::new("/work/config")));
+        assert!(rule_matches(&env, &request, root).unwrap());
+        let request = Request::new("shell", args, root, Some(Path::new("/tmp")));
+        assert!(rule_matches(&outside, &request, root).unwrap());
+
+        // An unknown directory satisfies no path allow rule
+        let request = Request::new("shell", args, root, None);
+        assert!(request.unresolved_paths && request.paths.is_empty());
+        let allow = rule(Permission::Allow, None, Some("*"), None);
+        assert!(!rule_matches(&allow, &request, root).unwrap());
+    }
+}
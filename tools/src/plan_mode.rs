@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::tools::is_read_only_tool;
+
+static PLAN_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Tools that change nothing in the workspace besides the read-only ones,
+/// `todo_write` is where the plan is kept
+const PLAN_TOOLS: &[&str] = &["todo_write"];
+
+/// Set plan mode (read-only investigation that ends with a plan)
+pub fn set_plan_mode(enabled: bool) {
+    PLAN_MODE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether plan mode is on
+pub fn is_plan_mode() -> bool {
+    PLAN_MODE.load(Ordering::Relaxed)
+}
+
+/// Whether a tool may run in plan mode
+///
+/// MCP server tools are allowed only when the server marks them read-only.
+pub async fn is_allowed(name: &str, mcp_integration: Option<&mcp::McpIntegration>) -> bool {
+    if is_read_only_tool(name) || PLAN_TOOLS.contains(&name) {
+        return true;
+    }
+    match mcp_integration {
+        Some(integration) => integration.is_read_only_tool(name).await,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_read_only_tools_allowed() {
+        for name in ["file_read", "file_search", "git_diff", "todo_write", "todo_read"] {
+            assert!(is_allowed(name, None).await, "{} should be allowed", name);
+        }
+        for name in ["file_write", "file_replace", "file_diff_edit", "apply_patch", "run_command", "shell", "server/tool"] {
+            assert!(!is_allowed(name, None).await, "{} should be blocked", name);
+        }
+    }
+}
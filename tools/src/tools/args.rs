@@ -113,6 +113,11 @@ pub struct FileDiffEditArgs {
     pub hunks: Vec<DiffHunk>, // 多个 hunk 编辑
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ApplyPatchArgs {
+    pub patch: String, // unified diff，可包含多个文件
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct RunCommandArgs {
     pub command: String,
@@ -120,9 +125,132 @@ pub struct RunCommandArgs {
     pub background: bool, // 是否后台运行
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ShellArgs {
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>, // 单条命令的超时时间
+    #[serde(default)]
+    pub reset: bool, // 先重启 shell
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandStatusArgs {
+    #[serde(default)]
+    pub id: Option<String>, // 省略时列出所有后台命令
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandOutputArgs {
+    pub id: String,
+    #[serde(default)]
+    pub offset: Option<u64>, // 从该字节偏移继续读取
+    #[serde(default)]
+    pub lines: Option<usize>, // 未指定偏移时返回的末尾行数
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandKillArgs {
+    pub id: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FetchUrlArgs {
     pub url: String,
     #[serde(default)]
     pub max_bytes: Option<usize>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct GitDiffArgs {
+    #[serde(default)]
+    pub staged: bool, // 只看暂存区
+    #[serde(rename = "ref")]
+    pub reference: Option<String>, // 与某个提交/分支比较
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitLogArgs {
+    #[serde(default = "default_git_log_count")]
+    pub max_count: usize,
+    #[serde(rename = "ref")]
+    pub reference: Option<String>,
+    pub path: Option<String>,
+    pub author: Option<String>,
+    pub since: Option<String>,
+}
+
+pub fn default_git_log_count() -> usize {
+    20
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitBlameArgs {
+    pub path: String,
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    #[serde(rename = "ref")]
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitShowArgs {
+    #[serde(rename = "ref", default = "default_git_ref")]
+    pub reference: String,
+    pub path: Option<String>, // 指定时返回该版本的文件内容
+}
+
+pub fn default_git_ref() -> String {
+    "HEAD".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitCommitArgs {
+    pub message: String,
+    #[serde(default)]
+    pub paths: Vec<String>, // 提交前先 git add
+    #[serde(default)]
+    pub all: bool, // git commit -a
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitCheckoutArgs {
+    pub target: String, // 分支、标签或提交
+    #[serde(default)]
+    pub create: bool, // git checkout -b
+    #[serde(default)]
+    pub paths: Vec<String>, // 指定时只恢复这些文件
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LspPositionArgs {
+    pub path: String,
+    pub line: u32,              // 1-indexed
+    pub column: Option<u32>,    // 1-indexed，字符计数
+    pub symbol: Option<String>, // 指定时定位到该行中第一次出现的位置
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LspReferencesArgs {
+    #[serde(flatten)]
+    pub position: LspPositionArgs,
+    #[serde(default = "default_include_declaration")]
+    pub include_declaration: bool,
+}
+
+pub fn default_include_declaration() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LspWorkspaceSymbolsArgs {
+    pub query: String,
+    pub path: String, // 用于选择语言服务器的源文件
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LspDiagnosticsArgs {
+    pub path: String,
+}
@@ -0,0 +1,221 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::command_manager::{BackgroundCommand, CommandConfig};
+
+/// Most bytes returned by one log read
+pub const MAX_LOG_READ: u64 = 32 * 1024;
+
+/// How long a killed process group gets to exit before SIGKILL
+#[cfg(unix)]
+const KILL_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Background commands started by this process, run id to process id
+static PROCESSES: Mutex<Option<HashMap<String, u32>>> = Mutex::new(None);
+
+/// A slice of a background command's log
+pub struct LogChunk {
+    pub text: String,
+    /// Byte offset of the first byte returned
+    pub start: u64,
+    /// Offset to pass next time to continue reading
+    pub end: u64,
+    /// Current log size
+    pub total: u64,
+}
+
+/// Log file of a background command, `.friendev/runs/<id>.log` in the workspace
+pub fn log_path(working_dir: &Path, id: &str) -> PathBuf {
+    working_dir.join(".friendev").join("runs").join(format!("{}.log", id))
+}
+
+/// Remember a spawned process so it can be killed on exit
+pub fn register(id: &str, pid: u32) {
+    let mut guard = PROCESSES.lock().unwrap();
+    guard.get_or_insert_with(HashMap::new).insert(id.to_string(), pid);
+}
+
+/// Forget a background command once it has exited
+pub fn unregister(id: &str) {
+    let mut guard = PROCESSES.lock().unwrap();
+    if let Some(processes) = guard.as_mut() {
+        processes.remove(id);
+    }
+}
+
+/// Status to show for a command, `running` entries left by an earlier process show
+/// as `lost`: its process id may since belong to something else
+pub fn effective_status(cmd: &BackgroundCommand) -> String {
+    let owned = PROCESSES
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|p| p.contains_key(&cmd.id));
+    if cmd.status == "running" && !owned {
+        "lost".to_string()
+    } else {
+        cmd.status.clone()
+    }
+}
+
+/// Stop a running background command with its child processes and mark it `killed`
+///
+/// Only commands started by this process are killed. Returns false when the command
+/// is not running.
+pub fn kill(id: &str) -> Result<bool> {
+    let mut config = CommandConfig::load()?;
+    if config.get_background_command(id).is_none_or(|cmd| cmd.status != "running") {
+        return Ok(false);
+    }
+    let Some(pid) = PROCESSES.lock().unwrap().as_mut().and_then(|p| p.remove(id)) else {
+        return Ok(false);
+    };
+    kill_group(pid);
+
+    config.update_background_command(id, |cmd| cmd.status = "killed".to_string());
+    config.save()?;
+    Ok(true)
+}
+
+/// Kill a process registered by this process that has no background command entry
+pub fn kill_registered(id: &str) {
+    let pid = PROCESSES.lock().unwrap().as_mut().and_then(|p| p.remove(id));
+    if let Some(pid) = pid {
+        kill_group(pid);
+    }
+}
+
+/// Kill every background command started by this process, called on exit
+pub fn kill_all() {
+    let processes = PROCESSES.lock().unwrap().take().unwrap_or_default();
+    if processes.is_empty() {
+        return;
+    }
+    for pid in processes.values() {
+        kill_group(*pid);
+    }
+    if let Ok(mut config) = CommandConfig::load() {
+        for id in processes.keys() {
+            config.update_background_command(id, |cmd| cmd.status = "killed".to_string());
+        }
+        let _ = config.save();
+    }
+}
+
+/// Read a log from `offset`, or its last `lines` lines when no offset is given
+pub fn read_log(path: &Path, offset: Option<u64>, lines: usize) -> Result<LogChunk> {
+    let mut file = File::open(path)?;
+    let total = file.metadata()?.len();
+
+    let start = match offset {
+        Some(offset) => offset.min(total),
+        None => tail_start(&mut file, total, lines)?,
+    };
+    let end = total.min(start + MAX_LOG_READ);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.take(end - start).read_to_end(&mut bytes)?;
+
+    Ok(LogChunk {
+        text: String::from_utf8_lossy(&bytes).to_string(),
+        start,
+        end,
+        total,
+    })
+}
+
+/// Offset where the last `lines` lines begin, looking at most `MAX_LOG_READ` bytes back
+fn tail_start(file: &mut File, total: u64, lines: usize) -> Result<u64> {
+    let window = total.min(MAX_LOG_READ);
+    file.seek(SeekFrom::Start(total - window))?;
+    let mut bytes = Vec::new();
+    file.take(window).read_to_end(&mut bytes)?;
+
+    // A trailing newline ends the last line rather than starting an empty one
+    let body = bytes.strip_suffix(b"\n").unwrap_or(&bytes);
+    let cut = body
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, b)| **b == b'\n')
+        .nth(lines.saturating_sub(1))
+        .map_or(0, |(i, _)| i + 1);
+    Ok(total - window + cut as u64)
+}
+
+/// SIGTERM the process group, SIGKILL whatever is left after a grace period
+#[cfg(unix)]
+fn kill_group(pid: u32) {
+    use std::time::{Duration, Instant};
+
+    let group = -(pid as i32);
+    // SAFETY: background commands run in their own process group led by `pid`
+    unsafe {
+        if libc::kill(group, libc::SIGTERM) != 0 {
+            return;
+        }
+        let deadline = Instant::now() + KILL_GRACE;
+        while Instant::now() < deadline {
+            if libc::kill(group, 0) != 0 {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        libc::kill(group, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_group(pid: u32) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_log() {
+        let path = std::env::temp_dir().join(format!("friendev-bg-{}.log", std::process::id()));
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        let tail = read_log(&path, None, 2).unwrap();
+        assert_eq!(tail.text, "two\nthree\n");
+        assert_eq!((tail.start, tail.end, tail.total), (4, 14, 14));
+
+        let all = read_log(&path, None, 10).unwrap();
+        assert_eq!(all.text, "one\ntwo\nthree\n");
+
+        let from = read_log(&path, Some(8), 0).unwrap();
+        assert_eq!(from.text, "three\n");
+        assert_eq!(read_log(&path, Some(99), 0).unwrap().text, "");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_effective_status() {
+        let mut cmd = BackgroundCommand {
+            id: "friendev-status-test".to_string(),
+            command: "sleep 10".to_string(),
+            start_time: chrono::Utc::now(),
+            status: "running".to_string(),
+            exit_code: None,
+            output: None,
+            pid: Some(std::process::id()),
+            log_path: None,
+        };
+        // A live process id alone doesn't make an earlier process's command ours
+        assert_eq!(effective_status(&cmd), "lost");
+        register(&cmd.id, std::process::id());
+        assert_eq!(effective_status(&cmd), "running");
+        unregister(&cmd.id);
+        cmd.status = "completed".to_string();
+        assert_eq!(effective_status(&cmd), "completed");
+    }
+}
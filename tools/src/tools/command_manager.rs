@@ -5,6 +5,8 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 
+use super::shell_parser;
+
 /// 默认的总是需要确认的命令
 const DEFAULT_ALWAYS_APPROVE_COMMANDS: &[&str] = &["rm", "del", "rmdir", "format", "fdisk"];
 
@@ -26,12 +28,18 @@ pub struct BackgroundCommand {
     pub command: String,
     /// 启动时间
     pub start_time: chrono::DateTime<chrono::Utc>,
-    /// 命令状态：running, completed, failed
+    /// 命令状态：running, completed, failed, killed
     pub status: String,
     /// 命令退出码（如果已完成）
     pub exit_code: Option<i32>,
-    /// 命令输出（如果已收集）
+    /// 命令输出（旧版本在结束后收集，现在写入日志文件）
     pub output: Option<String>,
+    /// 进程ID，也是其进程组ID
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// 标准输出和标准错误的日志文件
+    #[serde(default)]
+    pub log_path: Option<PathBuf>,
 }
 
 impl Default for CommandConfig {
@@ -87,14 +95,31 @@ impl CommandConfig {
     }
 
     /// 检查命令是否总是需要确认
+    ///
+    /// 解析整条命令行，管道、`&&`、子 shell、`$(...)`、`sudo`/`xargs` 包装中的程序都会检查；
+    /// 无法解析的命令，以及程序名或写入的文件要展开变量、替换后才知道的命令一律需要确认
     pub fn needs_approval(&self, command: &str) -> bool {
-        // 提取主命令（第一个词）
-        let main_command = command
-            .split_whitespace()
-            .next()
-            .unwrap_or("")
-            .to_lowercase();
-        self.always_approve_commands.contains(&main_command)
+        match shell_parser::analyze(command) {
+            Ok(analysis) => {
+                analysis.unknown_program
+                    || analysis.unknown_write
+                    || analysis
+                        .invocations
+                        .iter()
+                        .any(|inv| self.always_approve_commands.contains(&inv.program))
+            }
+            Err(_) => true,
+        }
+    }
+
+    /// 命令行中调用的、在总是确认列表里的程序
+    pub fn dangerous_programs(&self, command: &str) -> Vec<String> {
+        let mut programs: Vec<String> = shell_parser::analyze(command)
+            .map(|a| a.invocations.into_iter().map(|inv| inv.program).collect())
+            .unwrap_or_default();
+        programs.retain(|p| self.always_approve_commands.contains(p));
+        programs.dedup();
+        programs
     }
 
     /// 添加后台命令
@@ -120,6 +145,13 @@ impl CommandConfig {
         self.running_commands.iter().find(|c| c.id == id)
     }
 
+    /// 列出后台命令，最新的在前
+    pub fn list_background_commands(&self) -> Vec<&BackgroundCommand> {
+        let mut commands: Vec<&BackgroundCommand> = self.running_commands.iter().collect();
+        commands.sort_by_key(|c| std::cmp::Reverse(c.start_time));
+        commands
+    }
+
     /// 列出所有总是需要确认的命令
     pub fn list_always_approve_commands(&self) -> Vec<String> {
         let mut commands: Vec<String> = self.always_approve_commands.iter().cloned().collect();
@@ -1,6 +1,105 @@
 use super::{Tool, ToolFunction};
 use serde_json::json;
 use mcp::McpIntegration;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// User-defined subagents of the workspace, reloaded at the start of each user turn
+static CUSTOM_AGENTS: Mutex<Vec<config::AgentDefinition>> = Mutex::new(Vec::new());
+
+/// Builtin tools that never change the workspace and can run concurrently
+const READ_ONLY_TOOLS: &[&str] = &[
+    "file_list",
+    "file_read",
+    "file_search",
+    "file_outline",
+    "file_search_by_outline",
+    "network_search_auto",
+    "network_search_duckduckgo",
+    "network_search_bing",
+    "network_get_content",
+    "git_status",
+    "git_diff",
+    "git_log",
+    "git_blame",
+    "git_show",
+    "lsp_definition",
+    "lsp_references",
+    "lsp_hover",
+    "lsp_workspace_symbols",
+    "lsp_diagnostics",
+    "todo_read",
+    "command_status",
+    "command_output",
+    "mcp_resource_list",
+    "mcp_resource_read",
+];
+
+/// Whether a tool is read-only. Unknown and MCP server tools count as mutating.
+pub fn is_read_only_tool(name: &str) -> bool {
+    READ_ONLY_TOOLS.contains(&name)
+}
+
+/// Tools of the investigator subagent (Yinlin): reading, searching, outlines, the index and LSP
+const INVESTIGATOR_TOOLS: &[&str] = &[
+    "file_list",
+    "file_read",
+    "file_search",
+    "file_outline",
+    "file_search_by_outline",
+    "index_file",
+    "lsp_definition",
+    "lsp_references",
+    "lsp_hover",
+    "lsp_workspace_symbols",
+    "lsp_diagnostics",
+];
+
+/// Upper bound on subagents running at the same time, per `task` call and per batch of `task` calls
+pub const MAX_PARALLEL_SUBAGENTS: usize = 4;
+
+/// Whether a subagent type names the investigator subagent
+pub fn is_investigator(subagent_type: &str) -> bool {
+    matches!(subagent_type, "investigator" | "yinlin")
+}
+
+/// Tools a subagent type is restricted to, None when it may use every tool
+pub fn subagent_tools(subagent_type: &str) -> Option<Vec<String>> {
+    if is_investigator(subagent_type) {
+        return Some(INVESTIGATOR_TOOLS.iter().map(|t| t.to_string()).collect());
+    }
+    None
+}
+
+/// Reload the user-defined subagents of `working_dir`, offered to the model until the next reload
+pub fn load_custom_agents(working_dir: &Path) {
+    *CUSTOM_AGENTS.lock().unwrap() = config::AgentDefinition::load_all(working_dir);
+}
+
+/// The loaded user-defined subagent called `name`
+pub fn custom_agent(name: &str) -> Option<config::AgentDefinition> {
+    CUSTOM_AGENTS.lock().unwrap().iter().find(|agent| agent.name == name).cloned()
+}
+
+/// The loaded user-defined subagents, listed in the `task` schema
+fn custom_agents_description() -> String {
+    let agents = CUSTOM_AGENTS.lock().unwrap();
+    if agents.is_empty() {
+        return String::new();
+    }
+    let list = agents
+        .iter()
+        .map(|agent| {
+            if agent.description.is_empty() {
+                format!("'{}'", agent.name)
+            } else {
+                format!("'{}' ({})", agent.name, agent.description)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(" Custom subagents defined for this project: {}.", list)
+}
 
 pub fn get_available_tools() -> Vec<Tool> {
     get_builtin_tools()
@@ -47,6 +146,8 @@ pub fn get_available_tools_with_mcp(mcp_integration: Option<&McpIntegration>) ->
 }
 
 pub fn get_builtin_tools() -> Vec<Tool> {
+    let subagent_type_description = format!("Type of subagent to use: 'general', 'coder', 'reviewer', 'planner', or 'investigator' (read-only codebase investigation that returns a condensed report with file:line citations). Defaults to 'general'.{}", custom_agents_description());
+
     vec![
         Tool {
             tool_type: "function".to_string(),
@@ -382,6 +483,23 @@ pub fn get_builtin_tools() -> Vec<Tool> {
                 }),
             },
         },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "apply_patch".to_string(),
+                description: "Apply a unified diff (plain or git-style) that may touch many files, including creating (--- /dev/null), deleting (+++ /dev/null) and renaming files. Hunks are located by their context lines, so line numbers in @@ headers may be approximate. All files are changed together or not at all: if any hunk does not match, nothing is written.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "patch": {
+                            "type": "string",
+                            "description": "The patch text, with ---/+++ file headers and @@ hunks whose lines start with ' ', '-' or '+'"
+                        }
+                    },
+                    "required": ["patch"]
+                }),
+            },
+        },
         Tool {
             tool_type: "function".to_string(),
             function: ToolFunction {
@@ -404,6 +522,399 @@ pub fn get_builtin_tools() -> Vec<Tool> {
                 }),
             },
         },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "shell".to_string(),
+                description: "Run a command in a persistent shell kept for this session: the working directory, exported variables and activated virtualenvs carry over to the next call, so `cd` once instead of prefixing every command. Returns the combined stdout/stderr, exit code and current directory. Stdin is closed; use run_command with background for servers and watchers.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "command": {
+                            "type": "string",
+                            "description": "The shell command to run"
+                        },
+                        "timeout_secs": {
+                            "type": "integer",
+                            "description": "Seconds to wait before the shell is killed and restarted (max 600)",
+                            "default": 120
+                        },
+                        "reset": {
+                            "type": "boolean",
+                            "description": "Restart the shell in the workspace before running the command, clearing directory and environment changes",
+                            "default": false
+                        }
+                    },
+                    "required": ["command"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "command_status".to_string(),
+                description: "Show background commands started with run_command: run ID, status (running, completed, failed, killed, lost), exit code and start time. Pass an id for one command.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {
+                            "type": "string",
+                            "description": "Run ID returned by run_command; omit to list all background commands"
+                        }
+                    },
+                    "required": []
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "command_output".to_string(),
+                description: "Read the combined stdout and stderr log of a background command while it runs or after it exits. Without offset returns the last lines; the result includes the next offset, pass it back to read only new output.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {
+                            "type": "string",
+                            "description": "Run ID returned by run_command"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "description": "Byte offset to continue reading from, as returned by a previous call"
+                        },
+                        "lines": {
+                            "type": "integer",
+                            "description": "Number of trailing lines to return when no offset is given",
+                            "default": 100
+                        }
+                    },
+                    "required": ["id"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "command_kill".to_string(),
+                description: "Stop a running background command and the processes it started.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {
+                            "type": "string",
+                            "description": "Run ID returned by run_command"
+                        }
+                    },
+                    "required": ["id"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "git_status".to_string(),
+                description: "Show the current branch, upstream ahead/behind counts, and staged, unstaged, untracked and conflicted files as JSON.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "git_diff".to_string(),
+                description: "Show a per-file change summary followed by the unified diff. Defaults to unstaged changes.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "staged": {
+                            "type": "boolean",
+                            "description": "Show staged changes instead of unstaged ones",
+                            "default": false
+                        },
+                        "ref": {
+                            "type": "string",
+                            "description": "Compare against this commit, branch or tag (e.g. 'HEAD~3', 'main')"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Limit the diff to a file or directory"
+                        }
+                    },
+                    "required": []
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "git_log".to_string(),
+                description: "List commits as JSON (hash, author, date, subject), newest first.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "max_count": {
+                            "type": "integer",
+                            "description": "Maximum number of commits (1-200)",
+                            "default": 20
+                        },
+                        "ref": {
+                            "type": "string",
+                            "description": "Branch, tag or range to list (e.g. 'main..HEAD'), defaults to HEAD"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Only commits touching this file or directory"
+                        },
+                        "author": {
+                            "type": "string",
+                            "description": "Only commits whose author matches this pattern"
+                        },
+                        "since": {
+                            "type": "string",
+                            "description": "Only commits after this date (e.g. '2 weeks ago', '2025-01-01')"
+                        }
+                    },
+                    "required": []
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "git_blame".to_string(),
+                description: "Show which commit last changed each line of a file, grouped into line ranges as JSON.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "File to blame"
+                        },
+                        "start_line": {
+                            "type": "integer",
+                            "description": "First line (1-indexed, optional)"
+                        },
+                        "end_line": {
+                            "type": "integer",
+                            "description": "Last line (inclusive, optional)"
+                        },
+                        "ref": {
+                            "type": "string",
+                            "description": "Blame the file as of this revision, defaults to the working tree"
+                        }
+                    },
+                    "required": ["path"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "git_show".to_string(),
+                description: "Show a commit's metadata, message and diff, or with `path` the content of a file at that revision.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "ref": {
+                            "type": "string",
+                            "description": "Commit, branch or tag",
+                            "default": "HEAD"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Return this file's content at the revision instead of the commit"
+                        }
+                    },
+                    "required": []
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "git_commit".to_string(),
+                description: "Create a commit with approval prompts. Commits what is staged, optionally staging `paths` or all tracked changes first.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "message": {
+                            "type": "string",
+                            "description": "Commit message"
+                        },
+                        "paths": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Files to stage before committing"
+                        },
+                        "all": {
+                            "type": "boolean",
+                            "description": "Stage all modified and deleted tracked files (git commit -a)",
+                            "default": false
+                        }
+                    },
+                    "required": ["message"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "git_checkout".to_string(),
+                description: "Switch branches or restore files from a revision, with approval prompts. Restoring files discards their local changes.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "target": {
+                            "type": "string",
+                            "description": "Branch, tag or commit to check out"
+                        },
+                        "create": {
+                            "type": "boolean",
+                            "description": "Create `target` as a new branch (git checkout -b)",
+                            "default": false
+                        },
+                        "paths": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Only restore these files from `target` instead of switching branches"
+                        }
+                    },
+                    "required": ["target"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "lsp_definition".to_string(),
+                description: "Go to the definition of a symbol using the language server for the file's language (configured in lsp.json). Returns path:line:column with the source line.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Source file containing the symbol"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Line of the symbol (1-indexed)"
+                        },
+                        "symbol": {
+                            "type": "string",
+                            "description": "Symbol name as written on that line, used to find the column"
+                        },
+                        "column": {
+                            "type": "integer",
+                            "description": "Column (1-indexed), only needed when symbol is not given"
+                        }
+                    },
+                    "required": ["path", "line"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "lsp_references".to_string(),
+                description: "Find all references to a symbol across the workspace using the language server. Returns one path:line:column per reference.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Source file containing the symbol"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Line of the symbol (1-indexed)"
+                        },
+                        "symbol": {
+                            "type": "string",
+                            "description": "Symbol name as written on that line, used to find the column"
+                        },
+                        "column": {
+                            "type": "integer",
+                            "description": "Column (1-indexed), only needed when symbol is not given"
+                        },
+                        "include_declaration": {
+                            "type": "boolean",
+                            "description": "Include the declaration itself (default: true)"
+                        }
+                    },
+                    "required": ["path", "line"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "lsp_hover".to_string(),
+                description: "Show the type signature and documentation of a symbol using the language server.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Source file containing the symbol"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "Line of the symbol (1-indexed)"
+                        },
+                        "symbol": {
+                            "type": "string",
+                            "description": "Symbol name as written on that line, used to find the column"
+                        },
+                        "column": {
+                            "type": "integer",
+                            "description": "Column (1-indexed), only needed when symbol is not given"
+                        }
+                    },
+                    "required": ["path", "line"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "lsp_workspace_symbols".to_string(),
+                description: "Search symbols (functions, types, constants...) by name across the whole workspace using the language server.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "Symbol name or part of it"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Any source file of the language to search, selects the language server"
+                        }
+                    },
+                    "required": ["query", "path"]
+                }),
+            },
+        },
+        Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: "lsp_diagnostics".to_string(),
+                description: "Get the language server's errors and warnings for a file, e.g. to check an edit compiles.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "File to check"
+                        }
+                    },
+                    "required": ["path"]
+                }),
+            },
+        },
         Tool {
             tool_type: "function".to_string(),
             function: ToolFunction {
@@ -463,7 +974,7 @@ pub fn get_builtin_tools() -> Vec<Tool> {
             tool_type: "function".to_string(),
             function: ToolFunction {
                 name: "task".to_string(),
-                description: "Delegate a complex task to a specialized subagent. The subagent runs in a separate session with its own context.".to_string(),
+                description: format!("Delegate a complex task to a specialized subagent. The subagent runs in a separate session with its own context. To run several independent tasks concurrently (at most {} at a time), pass them in `tasks` or make several `task` calls in one reply; all reports come back together.", MAX_PARALLEL_SUBAGENTS),
                 parameters: json!({
                     "type": "object",
                     "properties": {
@@ -477,11 +988,34 @@ pub fn get_builtin_tools() -> Vec<Tool> {
                         },
                         "subagent_type": {
                             "type": "string",
-                            "description": "Type of subagent to use (e.g., 'general', 'coder', 'reviewer'). Defaults to 'general'.",
+                            "description": subagent_type_description,
                             "default": "general"
+                        },
+                        "tasks": {
+                            "type": "array",
+                            "description": "Several independent tasks to run concurrently, instead of the single task given by `description` and `prompt`. Only use it for tasks that do not depend on each other's results or edit the same files.",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "description": {
+                                        "type": "string",
+                                        "description": "A short description of the task (3-5 words)"
+                                    },
+                                    "prompt": {
+                                        "type": "string",
+                                        "description": "Detailed instructions for the subagent, with all necessary context."
+                                    },
+                                    "subagent_type": {
+                                        "type": "string",
+                                        "description": subagent_type_description,
+                                        "default": "general"
+                                    }
+                                },
+                                "required": ["description", "prompt"]
+                            }
                         }
                     },
-                    "required": ["description", "prompt"]
+                    "required": []
                 }),
             },
         },
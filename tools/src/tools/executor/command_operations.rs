@@ -1,14 +1,37 @@
-use anyhow::Result;
-
-use crate::tools::args::RunCommandArgs;
+use anyhow::{bail, Result};
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::permissions;
+use super::sandbox::Sandbox;
+use crate::tools::background;
+use crate::tools::args::{CommandKillArgs, CommandOutputArgs, CommandStatusArgs, RunCommandArgs};
+use crate::tools::command_manager::{BackgroundCommand, CommandConfig};
 use crate::tools::types::{approve_action_for_session, is_action_approved, ToolResult};
-use ui::{get_i18n, prompt_approval};
+use ui::{get_i18n, prompt_approval, ApprovalChoice};
 
-pub async fn execute_run_command(arguments: &str, require_approval: bool) -> Result<ToolResult> {
+pub async fn execute_run_command(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+) -> Result<ToolResult> {
     let args: RunCommandArgs = serde_json::from_str(arguments)?;
 
+    // 项目沙箱配置，开启后命令在受限环境中运行
+    let sandbox = match Sandbox::load(working_dir) {
+        Ok(sandbox) => sandbox,
+        Err(e) => {
+            let i18n = get_i18n();
+            return Ok(ToolResult::error(i18n.get("sandbox_config_invalid").replace("{}", &e.to_string())));
+        }
+    };
+    let sandbox_text = sandbox
+        .as_ref()
+        .map(Sandbox::describe)
+        .unwrap_or_else(|| "off".to_string());
+
     // 加载命令配置
-    let config = crate::tools::command_manager::CommandConfig::load()?;
+    let config = CommandConfig::load()?;
     
     // Hook integration
     use crate::hooks::{HookType, execute_hook, HookContext};
@@ -21,58 +44,21 @@ pub async fn execute_run_command(arguments: &str, require_approval: bool) -> Res
          }
     }
 
-    // 检查是否需要审批
-    let needs_approval = require_approval || config.needs_approval(&args.command);
-
-    if needs_approval && !is_action_approved("run_command") {
-        // 提取主命令用于显示
-        let main_command = args.command.split_whitespace().next().unwrap_or("");
-
-        let (approved, always, view_details) = prompt_approval(
-            "RunCommand",
-            &args.command.to_string(),
-            Some(&format!(
-                "Command: {}\nMode: {}",
-                main_command,
-                if args.background {
-                    "background"
-                } else {
-                    "foreground"
-                }
-            )),
-        )?;
-
-        if view_details {
-            let continue_operation = ui::show_detailed_content(
-                "RunCommand",
-                &format!("Command: {}", args.command),
-                &format!(
-                    "Full command:\n{}\n\nThis command will be executed in {} mode.",
-                    args.command,
-                    if args.background {
-                        "background"
-                    } else {
-                        "foreground"
-                    }
-                ),
-            )?;
-
-            if !continue_operation {
-                let i18n = get_i18n();
-                return Ok(ToolResult::error(i18n.get("run_command_user_cancelled")));
-            }
-        } else if !approved {
-            let i18n = get_i18n();
-            return Ok(ToolResult::error(i18n.get("run_command_user_rejected")));
-        }
-
-        if always {
-            approve_action_for_session("run_command");
-        }
+    let mode = if args.background { "background" } else { "foreground" };
+    if let Some(rejected) = check_command_approval(
+        "run_command",
+        "RunCommand",
+        &args.command,
+        mode,
+        &sandbox_text,
+        &config,
+        require_approval,
+    ).await? {
+        return Ok(rejected);
     }
 
     if args.background {
-        let result = execute_background_command(args.clone(), config).await;
+        let result = execute_background_command(args.clone(), config, working_dir, sandbox).await;
         // Post-Hook for background command (executed immediately after spawn)
         if let Ok(cwd) = env::current_dir() {
              let hook_ctx = HookContext::new(cwd)
@@ -82,7 +68,7 @@ pub async fn execute_run_command(arguments: &str, require_approval: bool) -> Res
         }
         result
     } else {
-        let result = execute_foreground_command(args.clone()).await;
+        let result = execute_foreground_command(args.clone(), sandbox).await;
         // Post-Hook for foreground command
         if let Ok(cwd) = env::current_dir() {
              let hook_ctx = HookContext::new(cwd)
@@ -94,97 +80,124 @@ pub async fn execute_run_command(arguments: &str, require_approval: bool) -> Res
     }
 }
 
+/// 检查命令是否需要审批并询问用户，返回 `Some` 时命令不执行
+///
+/// 权限规则优先；危险命令不受"本会话总是批准"影响
+pub(super) async fn check_command_approval(
+    tool: &str,
+    action: &str,
+    command: &str,
+    mode: &str,
+    sandbox_text: &str,
+    config: &CommandConfig,
+    require_approval: bool,
+) -> Result<Option<ToolResult>> {
+    let dangerous = config.dangerous_programs(command);
+    let flagged = config.needs_approval(command);
+    let needs_approval = !permissions::is_allowed() && (require_approval || flagged);
+
+    if !needs_approval || !(flagged || !is_action_approved(tool) || permissions::must_ask()) {
+        ui::report_auto_approval(action, command, permissions::auto_reviewer());
+        return Ok(None);
+    }
+
+    // 提取主命令用于显示，列出其中的危险程序
+    let main_command = command.split_whitespace().next().unwrap_or("");
+    let main_command = if dangerous.is_empty() {
+        main_command.to_string()
+    } else {
+        format!("{} ({})", main_command, dangerous.join(", "))
+    };
+    let suggestion = permissions::suggest_command_rule(tool, command);
+
+    // 并行运行的子代理依次询问
+    let _prompt = ui::lock_prompt().await;
+    let choice = prompt_approval(
+        action,
+        command,
+        Some(&format!(
+            "Command: {}\nMode: {}\nSandbox: {}",
+            main_command, mode, sandbox_text
+        )),
+        suggestion.as_ref().map(|(_, shown)| shown.as_str()),
+    )?;
+
+    match choice {
+        ApprovalChoice::ViewDetails => {
+            let continue_operation = ui::show_detailed_content(
+                action,
+                &format!("Command: {}", command),
+                &format!(
+                    "Full command:\n{}\n\nThis command will be executed in {} mode.\nSandbox: {}",
+                    command, mode, sandbox_text
+                ),
+            )?;
+
+            if !continue_operation {
+                let i18n = get_i18n();
+                return Ok(Some(ToolResult::error(i18n.get("run_command_user_cancelled"))));
+            }
+        }
+        ApprovalChoice::Reject => {
+            let i18n = get_i18n();
+            return Ok(Some(ToolResult::error(i18n.get("run_command_user_rejected"))));
+        }
+        ApprovalChoice::Approve => {}
+        ApprovalChoice::AlwaysForSession => approve_action_for_session(tool),
+        ApprovalChoice::AlwaysForPattern => {
+            if let Some((rule, _)) = suggestion {
+                permissions::save_rule(rule)?;
+            }
+        }
+    }
+    Ok(None)
+}
+
 async fn execute_background_command(
     args: RunCommandArgs,
-    mut config: crate::tools::command_manager::CommandConfig,
+    mut config: CommandConfig,
+    working_dir: &Path,
+    sandbox: Option<Sandbox>,
 ) -> Result<ToolResult> {
-    use tokio::process::Command as TokioCommand;
     use uuid::Uuid;
 
     let run_id = Uuid::new_v4().to_string();
-    let run_id_for_async = run_id.clone();
-    let command_for_async = args.command.clone();
+
+    // 标准输出和标准错误实时写入日志文件
+    let log_path = background::log_path(working_dir, &run_id);
+    let child = match spawn_logged(&args.command, &log_path, sandbox.as_ref()) {
+        Ok(child) => child,
+        Err(e) => {
+            let i18n = get_i18n();
+            let tmpl = i18n.get("run_command_execute_error");
+            return Ok(ToolResult::error(tmpl.replace("{}", &e.to_string())));
+        }
+    };
+    let pid = child.id();
 
     // 创建后台命令
-    let bg_cmd = crate::tools::command_manager::BackgroundCommand {
+    let bg_cmd = BackgroundCommand {
         id: run_id.clone(),
         command: args.command.clone(),
         start_time: chrono::Utc::now(),
         status: "running".to_string(),
         exit_code: None,
         output: None,
+        pid,
+        log_path: Some(log_path),
     };
 
     // 保存到配置
     config.add_background_command(bg_cmd);
     config.save()?;
+    if let Some(pid) = pid {
+        background::register(&run_id, pid);
+    }
 
-    // 在后台启动命令
-    tokio::spawn(async move {
-        let mut cmd = if cfg!(target_os = "windows") {
-            TokioCommand::new("cmd")
-        } else {
-            TokioCommand::new("sh")
-        };
-
-        if cfg!(target_os = "windows") {
-            cmd.arg("/C");
-        } else {
-            cmd.arg("-c");
-        }
-
-        cmd.arg(&command_for_async);
-
-        match cmd.output().await {
-            Ok(output) => {
-                // 更新命令状态
-                let mut config = match crate::tools::command_manager::CommandConfig::load() {
-                    Ok(c) => c,
-                    Err(_) => return, // 如果无法加载配置，直接返回
-                };
-
-                let status = if output.status.success() {
-                    "completed"
-                } else {
-                    "failed"
-                };
-                let exit_code = output.status.code();
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                let combined_output = if !stdout.is_empty() && !stderr.is_empty() {
-                    format!("STDOUT:\n{}\nSTDERR:\n{}", stdout, stderr)
-                } else if !stdout.is_empty() {
-                    stdout
-                } else {
-                    stderr
-                };
-
-                config.update_background_command(&run_id_for_async, |cmd| {
-                    cmd.status = status.to_string();
-                    cmd.exit_code = exit_code;
-                    cmd.output = Some(combined_output);
-                });
-
-                let _ = config.save();
-            }
-            Err(_) => {
-                // 更新命令状态为失败
-                let mut config = match crate::tools::command_manager::CommandConfig::load() {
-                    Ok(c) => c,
-                    Err(_) => return,
-                };
-
-                config.update_background_command(&run_id_for_async, |cmd| {
-                    cmd.status = "failed".to_string();
-                    cmd.exit_code = None;
-                    cmd.output = Some("Failed to execute command".to_string());
-                });
-
-                let _ = config.save();
-            }
-        }
-    });
+    // 等待命令结束并更新状态
+    let run_id_for_async = run_id.clone();
+    let timeout = sandbox.as_ref().and_then(Sandbox::timeout);
+    tokio::spawn(wait_background(child, run_id_for_async, timeout));
 
     let i18n = get_i18n();
 
@@ -199,24 +212,79 @@ async fn execute_background_command(
     Ok(ToolResult::ok(brief, output))
 }
 
-async fn execute_foreground_command(args: RunCommandArgs) -> Result<ToolResult> {
-    use std::process::Command;
+/// 启动后台命令，输出写入日志文件，命令在独立的进程组中运行以便整体终止
+fn spawn_logged(
+    command: &str,
+    log_path: &Path,
+    sandbox: Option<&Sandbox>,
+) -> Result<tokio::process::Child> {
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let log = std::fs::File::create(log_path)?;
+
+    let mut cmd = shell_command(command);
+    cmd.stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let ruleset = match sandbox {
+        Some(sandbox) => Some(sandbox.install(&mut cmd)?),
+        None => None,
+    };
+    let child = cmd.spawn()?;
+    drop(ruleset);
+    Ok(child)
+}
 
-    let mut cmd = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-    } else {
-        Command::new("sh")
+async fn wait_background(
+    mut child: tokio::process::Child,
+    run_id: String,
+    timeout: Option<std::time::Duration>,
+) {
+    let status = match timeout {
+        Some(limit) => match tokio::time::timeout(limit, child.wait()).await {
+            Ok(status) => status.ok(),
+            Err(_) => {
+                // 超过沙箱时间限制，终止后状态为 killed；等待进程组退出会阻塞，放到阻塞线程中
+                let _ = tokio::task::spawn_blocking(move || background::kill(&run_id)).await;
+                return;
+            }
+        },
+        None => child.wait().await.ok(),
     };
+    background::unregister(&run_id);
 
-    if cfg!(target_os = "windows") {
-        cmd.arg("/C");
-    } else {
-        cmd.arg("-c");
-    }
+    // 更新命令状态
+    let mut config = match CommandConfig::load() {
+        Ok(c) => c,
+        Err(_) => return, // 如果无法加载配置，直接返回
+    };
+
+    config.update_background_command(&run_id, |cmd| {
+        // 已被终止的命令保持 killed
+        if cmd.status != "running" {
+            return;
+        }
+        match status {
+            Some(status) => {
+                cmd.status = if status.success() { "completed" } else { "failed" }.to_string();
+                cmd.exit_code = status.code();
+            }
+            None => {
+                cmd.status = "failed".to_string();
+                cmd.exit_code = None;
+            }
+        }
+    });
 
-    cmd.arg(&args.command);
+    let _ = config.save();
+}
 
-    match cmd.output() {
+async fn execute_foreground_command(args: RunCommandArgs, sandbox: Option<Sandbox>) -> Result<ToolResult> {
+    match run_shell(&args.command, sandbox.as_ref()).await {
         Ok(output) => {
             let status = if output.status.success() {
                 "success"
@@ -261,3 +329,154 @@ async fn execute_foreground_command(args: RunCommandArgs) -> Result<ToolResult>
         }
     }
 }
+
+pub async fn execute_command_status(arguments: &str) -> Result<ToolResult> {
+    let args: CommandStatusArgs = serde_json::from_str(arguments)?;
+    let config = CommandConfig::load()?;
+    let i18n = get_i18n();
+
+    let commands: Vec<&BackgroundCommand> = match &args.id {
+        Some(id) => match config.get_background_command(id) {
+            Some(cmd) => vec![cmd],
+            None => return Ok(ToolResult::error(i18n.get("command_not_found").replace("{}", id))),
+        },
+        None => config.list_background_commands(),
+    };
+    if commands.is_empty() {
+        return Ok(ToolResult::ok(i18n.get("command_status_empty"), i18n.get("command_status_empty")));
+    }
+
+    let lines: Vec<String> = commands
+        .iter()
+        .map(|cmd| {
+            let mut line = format!(
+                "{}  {}  exit: {}  started: {}  {}",
+                cmd.id,
+                background::effective_status(cmd),
+                cmd.exit_code.map_or("-".to_string(), |c| c.to_string()),
+                cmd.start_time.format("%Y-%m-%d %H:%M:%S UTC"),
+                cmd.command
+            );
+            if args.id.is_some() {
+                if let Some(pid) = cmd.pid {
+                    line.push_str(&format!("\npid: {}", pid));
+                }
+                if let Some(log) = &cmd.log_path {
+                    line.push_str(&format!("\nlog: {}", log.display()));
+                }
+            }
+            line
+        })
+        .collect();
+
+    let brief = i18n.get("command_status_brief").replace("{}", &commands.len().to_string());
+    Ok(ToolResult::ok(brief, lines.join("\n")))
+}
+
+pub async fn execute_command_output(arguments: &str) -> Result<ToolResult> {
+    let args: CommandOutputArgs = serde_json::from_str(arguments)?;
+    let config = CommandConfig::load()?;
+    let i18n = get_i18n();
+
+    let Some(cmd) = config.get_background_command(&args.id) else {
+        return Ok(ToolResult::error(i18n.get("command_not_found").replace("{}", &args.id)));
+    };
+    let Some(log_path) = cmd.log_path.as_ref().filter(|p| p.exists()) else {
+        // 旧版本记录的命令只有结束后收集的输出
+        return Ok(match &cmd.output {
+            Some(output) => ToolResult::ok(
+                i18n.get("command_output_brief").replace("{}", &output.len().to_string()),
+                output.clone(),
+            ),
+            None => ToolResult::error(i18n.get("command_output_no_log").replace("{}", &args.id)),
+        });
+    };
+
+    let chunk = background::read_log(log_path, args.offset, args.lines.unwrap_or(100))?;
+    let header = i18n
+        .get("command_output_header")
+        .replacen("{}", &cmd.id, 1)
+        .replacen("{}", &background::effective_status(cmd), 1)
+        .replacen("{}", &chunk.start.to_string(), 1)
+        .replacen("{}", &chunk.end.to_string(), 1)
+        .replacen("{}", &chunk.total.to_string(), 1)
+        .replacen("{}", &chunk.end.to_string(), 1);
+
+    let brief = i18n
+        .get("command_output_brief")
+        .replace("{}", &(chunk.end - chunk.start).to_string());
+    Ok(ToolResult::ok(brief, format!("{}\n\n{}", header, chunk.text)))
+}
+
+pub async fn execute_command_kill(arguments: &str) -> Result<ToolResult> {
+    let args: CommandKillArgs = serde_json::from_str(arguments)?;
+    let i18n = get_i18n();
+
+    if CommandConfig::load()?.get_background_command(&args.id).is_none() {
+        return Ok(ToolResult::error(i18n.get("command_not_found").replace("{}", &args.id)));
+    }
+    let id = args.id.clone();
+    // 终止时会等待进程组退出
+    if !tokio::task::spawn_blocking(move || background::kill(&id)).await?? {
+        return Ok(ToolResult::error(i18n.get("command_kill_not_running").replace("{}", &args.id)));
+    }
+
+    let brief = i18n.get("command_kill_brief").replace("{}", &args.id);
+    Ok(ToolResult::ok(brief.clone(), brief))
+}
+
+/// 通过系统 shell 执行的命令
+fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = if cfg!(target_os = "windows") {
+        tokio::process::Command::new("cmd")
+    } else {
+        tokio::process::Command::new("sh")
+    };
+
+    if cfg!(target_os = "windows") {
+        cmd.arg("/C");
+    } else {
+        cmd.arg("-c");
+    }
+
+    cmd.arg(command);
+    cmd
+}
+
+/// 通过系统 shell 运行命令，沙箱开启时先施加限制，超时后终止整个进程组
+async fn run_shell(command: &str, sandbox: Option<&Sandbox>) -> Result<std::process::Output> {
+    let mut cmd = shell_command(command);
+
+    let Some(sandbox) = sandbox else {
+        return Ok(cmd.output().await?);
+    };
+
+    let ruleset = sandbox.install(&mut cmd)?;
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let child = cmd.spawn()?;
+    drop(ruleset);
+
+    let Some(limit) = sandbox.timeout() else {
+        return Ok(child.wait_with_output().await?);
+    };
+    let pid = child.id();
+    match tokio::time::timeout(limit, child.wait_with_output()).await {
+        Ok(output) => Ok(output?),
+        Err(_) => {
+            #[cfg(unix)]
+            if let Some(pid) = pid {
+                // SAFETY: signals the process group created for this command
+                unsafe {
+                    libc::kill(-(pid as i32), libc::SIGKILL);
+                }
+            }
+            let i18n = get_i18n();
+            bail!(i18n.get("sandbox_timed_out").replace("{}", &limit.as_secs().to_string()))
+        }
+    }
+}
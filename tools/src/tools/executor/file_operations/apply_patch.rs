@@ -0,0 +1,616 @@
+use anyhow::{anyhow, bail, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::file_common::{check_file_action_approval, normalize_path, report_auto_approval};
+use crate::tools::args::ApplyPatchArgs;
+use crate::tools::indexer::Indexer;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+/// Context lines a hunk may lose at each end when it doesn't match as written
+const MAX_FUZZ: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Default)]
+struct Hunk {
+    /// 1-indexed start in the old file, only a hint for where to look
+    old_start: Option<usize>,
+    lines: Vec<HunkLine>,
+    /// `\ No newline at end of file` after a line of the old / new side
+    old_no_newline: bool,
+    new_no_newline: bool,
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Add(s) => Some(s.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Changes to one file, `None` on a side means `/dev/null`
+#[derive(Debug, Default)]
+struct FilePatch {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+/// What the patch does to one file, computed before anything is written
+struct Change {
+    /// File the content is read from, `None` when created
+    source: Option<PathBuf>,
+    /// File the content is written to, `None` when deleted
+    target: Option<PathBuf>,
+    content: String,
+    added: usize,
+    removed: usize,
+    /// Unified diff of the hunks as they were placed
+    preview: String,
+}
+
+pub async fn execute_apply_patch(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+) -> Result<ToolResult> {
+    let args: ApplyPatchArgs = serde_json::from_str(arguments)?;
+    let i18n = get_i18n();
+
+    let patches = match parse_patch(&args.patch) {
+        Ok(patches) if !patches.is_empty() => patches,
+        Ok(_) => return Ok(ToolResult::error(i18n.get("apply_patch_empty"))),
+        Err(e) => {
+            return Ok(ToolResult::error(
+                i18n.get("apply_patch_parse_error").replace("{}", &e.to_string()),
+            ))
+        }
+    };
+
+    // Everything is resolved in memory first, a failing hunk leaves all files untouched
+    let mut changes = Vec::new();
+    for patch in &patches {
+        match plan_change(patch, working_dir) {
+            Ok(change) => changes.push(change),
+            Err(e) => {
+                return Ok(ToolResult::error(
+                    i18n.get("apply_patch_failed").replace("{}", &e.to_string()),
+                ))
+            }
+        }
+    }
+
+    let shown_path = match changes.as_slice() {
+        [only] => only.target.clone().or(only.source.clone()).unwrap_or_default(),
+        _ => working_dir.to_path_buf(),
+    };
+    if require_approval {
+        let preview = changes
+            .iter()
+            .map(|c| c.preview.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !check_file_action_approval("apply_patch", &shown_path, Some(&preview)).await? {
+            return Ok(ToolResult::error(i18n.get("approval_rejected")));
+        }
+    } else {
+        report_auto_approval("apply_patch", &shown_path);
+    }
+
+    if let Err(e) = write_changes(&changes) {
+        return Ok(ToolResult::error(
+            i18n.get("apply_patch_write_failed").replace("{}", &e.to_string()),
+        ));
+    }
+
+    // Auto-hook: Update outline index
+    if let Ok(indexer) = Indexer::new(working_dir) {
+        for target in changes.iter().filter_map(|c| c.target.as_ref()) {
+            let _ = indexer.index_file(target, working_dir, false, false).await;
+        }
+    }
+
+    let display = |p: &PathBuf| p.strip_prefix(working_dir).unwrap_or(p).display().to_string();
+    let summary = changes
+        .iter()
+        .map(|c| match (&c.source, &c.target) {
+            (None, Some(target)) => format!("A {} (+{})", display(target), c.added),
+            (Some(source), None) => format!("D {}", display(source)),
+            (Some(source), Some(target)) if source != target => format!(
+                "R {} -> {} (+{} -{})",
+                display(source),
+                display(target),
+                c.added,
+                c.removed
+            ),
+            (_, target) => format!(
+                "M {} (+{} -{})",
+                target.as_ref().map(display).unwrap_or_default(),
+                c.added,
+                c.removed
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(ToolResult::ok(
+        i18n.get("apply_patch_brief").replace("{}", &changes.len().to_string()),
+        summary,
+    ))
+}
+
+/// Write all changes, restoring every file already touched if one of them fails
+fn write_changes(changes: &[Change]) -> Result<()> {
+    // Original content of each path before this patch, `None` if it didn't exist
+    let mut originals: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+
+    let result = (|| -> Result<()> {
+        for change in changes {
+            for path in change.source.iter().chain(change.target.iter()) {
+                if !originals.iter().any(|(p, _)| p == path) {
+                    originals.push((path.clone(), fs::read(path).ok()));
+                    crate::checkpoint::snapshot(path)?;
+                }
+            }
+
+            if let Some(target) = &change.target {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(target, &change.content)?;
+            }
+            if let Some(source) = &change.source {
+                if change.target.as_ref() != Some(source) {
+                    fs::remove_file(source)?;
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        for (path, original) in originals.iter().rev() {
+            let _ = match original {
+                Some(bytes) => fs::write(path, bytes),
+                None => fs::remove_file(path),
+            };
+        }
+    }
+    result
+}
+
+/// Resolve one file's hunks against its current content
+fn plan_change(patch: &FilePatch, working_dir: &Path) -> Result<Change> {
+    let source = patch.old_path.as_deref().map(|p| normalize_path(p, working_dir));
+    let target = patch.new_path.as_deref().map(|p| normalize_path(p, working_dir));
+    let name = patch
+        .new_path
+        .as_deref()
+        .or(patch.old_path.as_deref())
+        .unwrap_or_default();
+
+    let original = match &source {
+        Some(path) => {
+            if !path.is_file() {
+                bail!("{}: file does not exist", name);
+            }
+            fs::read_to_string(path)?
+        }
+        None => {
+            if target.as_ref().is_some_and(|t| t.exists()) {
+                bail!("{}: file already exists", name);
+            }
+            String::new()
+        }
+    };
+    if let (Some(source), Some(target)) = (&source, &target) {
+        if source != target && target.exists() {
+            bail!("{}: rename target already exists", name);
+        }
+    }
+
+    let uses_crlf = original.contains("\r\n");
+    let normalized = original.replace("\r\n", "\n");
+    let mut ends_with_newline = normalized.is_empty() || normalized.ends_with('\n');
+    let mut lines: Vec<String> = normalized.lines().map(str::to_string).collect();
+
+    let mut preview = format!(
+        "--- {}\n+++ {}\n",
+        patch.old_path.as_deref().map_or("/dev/null".to_string(), |p| format!("a/{}", p)),
+        patch.new_path.as_deref().map_or("/dev/null".to_string(), |p| format!("b/{}", p)),
+    );
+    let (mut added, mut removed) = (0, 0);
+    // Line shift from earlier hunks, and where the next hunk may start
+    let mut offset: isize = 0;
+    let mut cursor = 0;
+
+    for (index, hunk) in patch.hunks.iter().enumerate() {
+        // With no old lines the header names the line to insert after
+        let pure_insert = hunk.old_lines().is_empty();
+        let hint = hunk
+            .old_start
+            .map(|s| if pure_insert { s } else { s.saturating_sub(1) })
+            .map(|s| (s as isize + offset).max(0) as usize)
+            .unwrap_or(cursor)
+            .max(cursor);
+        let (start, trimmed_front, trimmed_back) = locate_hunk(&lines, cursor, hint, hunk)
+            .ok_or_else(|| anyhow!("{}: hunk #{} does not match the file", name, index + 1))?;
+
+        let old = hunk.old_lines();
+        let new = hunk.new_lines();
+        let old_len = old.len() - trimmed_front - trimmed_back;
+        let new_slice = &new[trimmed_front..new.len() - trimmed_back];
+
+        preview.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            (start as isize - offset + 1).max(1),
+            old_len,
+            start + 1,
+            new_slice.len()
+        ));
+        let hunk_lines = &hunk.lines[trimmed_front..hunk.lines.len() - trimmed_back];
+        for line in hunk_lines {
+            match line {
+                HunkLine::Context(s) => preview.push_str(&format!(" {}\n", s)),
+                HunkLine::Remove(s) => preview.push_str(&format!("-{}\n", s)),
+                HunkLine::Add(s) => preview.push_str(&format!("+{}\n", s)),
+            }
+        }
+        added += hunk_lines.iter().filter(|l| matches!(l, HunkLine::Add(_))).count();
+        removed += hunk_lines.iter().filter(|l| matches!(l, HunkLine::Remove(_))).count();
+
+        let reaches_end = start + old_len == lines.len();
+        lines.splice(start..start + old_len, new_slice.iter().map(|s| s.to_string()));
+        offset += new_slice.len() as isize - old_len as isize;
+        cursor = start + new_slice.len();
+
+        if hunk.new_no_newline {
+            ends_with_newline = false;
+        } else if hunk.old_no_newline || (reaches_end && !new_slice.is_empty()) {
+            ends_with_newline = true;
+        }
+    }
+
+    let target_path = match &target {
+        Some(path) => path.clone(),
+        None => {
+            if !patch.hunks.is_empty() && !lines.is_empty() {
+                bail!("{}: deleted file still has lines after applying the hunks", name);
+            }
+            return Ok(Change {
+                source,
+                target: None,
+                content: String::new(),
+                added,
+                removed: removed.max(normalized.lines().count()),
+                preview,
+            });
+        }
+    };
+
+    let mut content = lines.join("\n");
+    if ends_with_newline && !lines.is_empty() {
+        content.push('\n');
+    }
+    if uses_crlf {
+        content = content.replace('\n', "\r\n");
+    }
+
+    Ok(Change {
+        source,
+        target: Some(target_path),
+        content,
+        added,
+        removed,
+        preview,
+    })
+}
+
+/// Find where a hunk applies, at or after `cursor` and as close to `hint` as possible
+///
+/// Tries an exact match, then ignoring trailing whitespace, then ignoring all surrounding
+/// whitespace, then the same with up to `MAX_FUZZ` context lines dropped from each end.
+/// Returns the start line and how many lines were dropped from the front and back.
+fn locate_hunk(lines: &[String], cursor: usize, hint: usize, hunk: &Hunk) -> Option<(usize, usize, usize)> {
+    let old = hunk.old_lines();
+    let leading = hunk.lines.iter().take_while(|l| matches!(l, HunkLine::Context(_))).count();
+    let trailing = hunk.lines.iter().rev().take_while(|l| matches!(l, HunkLine::Context(_))).count();
+
+    // Fewest dropped context lines first
+    let mut trims: Vec<(usize, usize)> = (0..=MAX_FUZZ.min(leading))
+        .flat_map(|front| (0..=MAX_FUZZ.min(trailing)).map(move |back| (front, back)))
+        .collect();
+    trims.sort_by_key(|&(front, back)| front + back);
+
+    for (front, back) in trims {
+        let wanted = &old[front..old.len() - back];
+
+        // A pure insertion goes where the header says, a hunk fuzzed down to nothing
+        // has no anchor left
+        if wanted.is_empty() {
+            if old.is_empty() {
+                return Some((hint.min(lines.len()), 0, 0));
+            }
+            continue;
+        }
+
+        let compare: [fn(&str, &str) -> bool; 3] = [
+            |a, b| a == b,
+            |a, b| a.trim_end() == b.trim_end(),
+            |a, b| a.trim() == b.trim(),
+        ];
+        for eq in compare {
+            let matches_at = |start: usize| {
+                wanted
+                    .iter()
+                    .zip(&lines[start..start + wanted.len()])
+                    .all(|(w, l)| eq(w, l))
+            };
+            let last = match lines.len().checked_sub(wanted.len()) {
+                Some(last) if last >= cursor => last,
+                _ => continue,
+            };
+            let found = (cursor..=last)
+                .filter(|&start| matches_at(start))
+                .min_by_key(|&start| start.abs_diff(hint));
+            if let Some(start) = found {
+                return Some((start, front, back));
+            }
+        }
+    }
+    None
+}
+
+/// Parse a unified diff, with or without git headers, into per-file patches
+fn parse_patch(text: &str) -> Result<Vec<FilePatch>> {
+    let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+    let mut patches = Vec::new();
+    let mut current: Option<FilePatch> = None;
+    // Paths from a `diff --git` line, used when the patch has no ---/+++ lines (pure renames)
+    let mut git_paths: Option<(String, String)> = None;
+    let mut i = 0;
+
+    let finish = |current: &mut Option<FilePatch>, patches: &mut Vec<FilePatch>| {
+        if let Some(patch) = current.take() {
+            patches.push(patch);
+        }
+    };
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            finish(&mut current, &mut patches);
+            git_paths = split_git_paths(rest);
+            let (old, new) = git_paths.clone().unwrap_or_default();
+            current = Some(FilePatch {
+                old_path: Some(old),
+                new_path: Some(new),
+                hunks: Vec::new(),
+            });
+        } else if line.starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ ")) {
+            let old_path = parse_header_path(&line[4..]);
+            let new_path = parse_header_path(&lines[i + 1][4..]);
+            // Without a `diff --git` line before it, each ---/+++ pair starts a new file
+            let continues_git = current.as_ref().is_some_and(|c| c.hunks.is_empty()) && git_paths.is_some();
+            if !continues_git {
+                finish(&mut current, &mut patches);
+                current = Some(FilePatch::default());
+            }
+            let patch = current.as_mut().unwrap();
+            patch.old_path = old_path;
+            patch.new_path = new_path;
+            git_paths = None;
+            i += 2;
+            continue;
+        } else if line.starts_with("new file mode") {
+            if let Some(patch) = current.as_mut() {
+                patch.old_path = None;
+            }
+        } else if line.starts_with("deleted file mode") {
+            if let Some(patch) = current.as_mut() {
+                patch.new_path = None;
+            }
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            if let Some(patch) = current.as_mut() {
+                patch.old_path = Some(path.to_string());
+            }
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            if let Some(patch) = current.as_mut() {
+                patch.new_path = Some(path.to_string());
+            }
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            bail!("binary patches are not supported");
+        } else if line.starts_with("@@") {
+            let patch = current
+                .as_mut()
+                .ok_or_else(|| anyhow!("hunk before any file header on line {}", i + 1))?;
+            let (hunk, next) = parse_hunk(&lines, i);
+            patch.hunks.push(hunk);
+            i = next;
+            continue;
+        }
+        i += 1;
+    }
+    finish(&mut current, &mut patches);
+
+    for patch in &mut patches {
+        strip_prefixes(patch);
+        if patch.old_path.is_none() && patch.new_path.is_none() {
+            bail!("file header without paths");
+        }
+    }
+    Ok(patches)
+}
+
+/// Every path a patch reads or writes, empty if it doesn't parse
+pub fn patch_paths(patch: &str) -> Vec<String> {
+    parse_patch(patch)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|p| p.old_path.into_iter().chain(p.new_path))
+        .collect()
+}
+
+/// Parse the hunk whose `@@` header is at `start`, returns it and the next line index
+fn parse_hunk(lines: &[&str], start: usize) -> (Hunk, usize) {
+    let mut hunk = Hunk {
+        old_start: parse_hunk_start(lines[start]),
+        ..Default::default()
+    };
+    let mut i = start + 1;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let is_file_header = line.starts_with("diff --git ")
+            || (line.starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ ")));
+        if line.starts_with("@@") || is_file_header {
+            break;
+        }
+
+        if let Some(rest) = line.strip_prefix('+') {
+            hunk.lines.push(HunkLine::Add(rest.to_string()));
+        } else if let Some(rest) = line.strip_prefix('-') {
+            hunk.lines.push(HunkLine::Remove(rest.to_string()));
+        } else if let Some(rest) = line.strip_prefix(' ') {
+            hunk.lines.push(HunkLine::Context(rest.to_string()));
+        } else if line.is_empty() {
+            // Editors and models often strip the space of empty context lines
+            hunk.lines.push(HunkLine::Context(String::new()));
+        } else if line.starts_with('\\') {
+            match hunk.lines.last() {
+                Some(HunkLine::Remove(_)) => hunk.old_no_newline = true,
+                Some(HunkLine::Add(_)) => hunk.new_no_newline = true,
+                _ => {
+                    hunk.old_no_newline = true;
+                    hunk.new_no_newline = true;
+                }
+            }
+        } else {
+            break;
+        }
+        i += 1;
+    }
+
+    // Blank lines between files aren't context
+    while matches!(hunk.lines.last(), Some(HunkLine::Context(s)) if s.is_empty()) {
+        hunk.lines.pop();
+    }
+    (hunk, i)
+}
+
+/// Old start line from `@@ -12,7 +12,8 @@`, `None` for a bare `@@`
+fn parse_hunk_start(header: &str) -> Option<usize> {
+    let old = header.split_whitespace().find(|part| part.starts_with('-'))?;
+    old[1..].split(',').next()?.parse().ok()
+}
+
+/// Path from a ---/+++ line, without the timestamp some tools append
+fn parse_header_path(value: &str) -> Option<String> {
+    let path = value.split('\t').next().unwrap_or(value).trim();
+    if path == "/dev/null" {
+        None
+    } else {
+        Some(path.to_string())
+    }
+}
+
+/// `a/old b/new` from a `diff --git` line
+fn split_git_paths(rest: &str) -> Option<(String, String)> {
+    let split = rest.find(" b/")?;
+    Some((rest[..split].to_string(), rest[split + 1..].to_string()))
+}
+
+/// Drop the `a/` and `b/` prefixes git and most diff tools add
+fn strip_prefixes(patch: &mut FilePatch) {
+    let prefixed = |p: &Option<String>, prefix: &str| p.as_ref().is_none_or(|p| p.starts_with(prefix));
+    if prefixed(&patch.old_path, "a/") && prefixed(&patch.new_path, "b/") {
+        if let Some(p) = patch.old_path.as_mut() {
+            *p = p[2..].to_string();
+        }
+        if let Some(p) = patch.new_path.as_mut() {
+            *p = p[2..].to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_git_patch() {
+        let patch = "diff --git a/src/old.rs b/src/new.rs\n\
+                     similarity index 90%\n\
+                     rename from src/old.rs\n\
+                     rename to src/new.rs\n\
+                     --- a/src/old.rs\n\
+                     +++ b/src/new.rs\n\
+                     @@ -1,2 +1,2 @@\n \
+                     fn a() {}\n\
+                     -fn b() {}\n\
+                     +fn c() {}\n\
+                     diff --git a/gone.txt b/gone.txt\n\
+                     deleted file mode 100644\n\
+                     --- a/gone.txt\n\
+                     +++ /dev/null\n\
+                     @@ -1 +0,0 @@\n\
+                     -bye\n\
+                     --- /dev/null\n\
+                     +++ b/hello.txt\n\
+                     @@ -0,0 +1 @@\n\
+                     +hello\n";
+
+        let patches = parse_patch(patch).unwrap();
+        assert_eq!(patches.len(), 3);
+        assert_eq!(patches[0].old_path.as_deref(), Some("src/old.rs"));
+        assert_eq!(patches[0].new_path.as_deref(), Some("src/new.rs"));
+        assert_eq!(patches[0].hunks[0].old_start, Some(1));
+        assert_eq!(patches[0].hunks[0].lines.len(), 3);
+        assert_eq!(patches[1].new_path, None);
+        assert_eq!(patches[2].old_path, None);
+        assert_eq!(patches[2].new_path.as_deref(), Some("hello.txt"));
+    }
+
+    #[test]
+    fn test_locate_hunk_fuzzy() {
+        let file = lines("one\ntwo\nthree  \nfour\nfive\ntwo\nthree\nsix\n");
+        let hunk = parse_hunk(&["@@ -5,3 +5,3 @@", " two", "-three", "+3", " six"], 0).0;
+
+        // Exact match near the hinted line wins over the earlier whitespace-only match
+        assert_eq!(locate_hunk(&file, 0, 4, &hunk), Some((5, 0, 0)));
+
+        // Stale context lines are dropped when nothing else matches
+        let stale = parse_hunk(&["@@ -3,3 +3,3 @@", " zero", "-four", "+4", " five"], 0).0;
+        assert_eq!(locate_hunk(&file, 0, 2, &stale), Some((3, 1, 0)));
+
+        let missing = parse_hunk(&["@@ -1 +1 @@", "-seven", "+7"], 0).0;
+        assert_eq!(locate_hunk(&file, 0, 0, &missing), None);
+    }
+}
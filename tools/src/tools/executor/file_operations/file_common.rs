@@ -53,44 +53,56 @@ pub fn verify_dir_exists(path: &Path) -> Result<ToolResult> {
     Ok(ToolResult::ok(String::new(), String::new()))
 }
 
+/// Report a file action that runs without a prompt
+pub fn report_auto_approval(action: &str, path: &Path) {
+    ui::report_auto_approval(action, &path.display().to_string(), crate::permissions::auto_reviewer());
+}
+
 /// Check if file action is approved
-pub fn check_file_action_approval(
+///
+/// Session approvals are skipped when an `ask` rule matched the call, and the prompt
+/// offers to save an allow rule for similar files.
+pub async fn check_file_action_approval(
     action: &str,
     path: &Path,
     preview: Option<&str>,
 ) -> Result<bool> {
+    use crate::permissions;
     use crate::tools::types::{approve_action_for_session, is_action_approved};
-    use ui::prompt_approval;
+    use ui::{prompt_approval, ApprovalChoice};
 
-    if is_action_approved(action) {
+    if is_action_approved(action) && !permissions::must_ask() {
+        report_auto_approval(action, path);
         return Ok(true);
     }
 
-    let (approved, always, view_details) = prompt_approval(
+    // Subagents running side by side ask one at a time
+    let _prompt = ui::lock_prompt().await;
+    let suggestion = permissions::suggest_file_rule(action, path);
+    let choice = prompt_approval(
         action,
         &path.display().to_string(),
         preview,
+        suggestion.as_ref().map(|(_, shown)| shown.as_str()),
     )?;
 
-    if view_details {
-        let continue_op = ui::show_detailed_content(
+    match choice {
+        ApprovalChoice::ViewDetails => Ok(ui::show_detailed_content(
             action,
             &path.display().to_string(),
             preview.unwrap_or("(No preview available)"),
-        )?;
-        if !continue_op {
-            return Ok(false);
+        )?),
+        ApprovalChoice::Reject => Ok(false),
+        ApprovalChoice::Approve => Ok(true),
+        ApprovalChoice::AlwaysForSession => {
+            approve_action_for_session(action);
+            Ok(true)
+        }
+        ApprovalChoice::AlwaysForPattern => {
+            if let Some((rule, _)) = suggestion {
+                permissions::save_rule(rule)?;
+            }
+            Ok(true)
         }
-        return Ok(true);
-    }
-
-    if !approved {
-        return Ok(false);
-    }
-
-    if always {
-        approve_action_for_session(action);
     }
-
-    Ok(true)
 }
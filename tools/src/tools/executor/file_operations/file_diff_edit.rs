@@ -80,10 +80,12 @@ pub async fn execute_file_diff_edit(
             "file_diff_edit",
             &target_path,
             Some(&details),
-        )? {
+        ).await? {
             let i18n = ui::get_i18n();
             return Ok(ToolResult::error(i18n.get("approval_rejected")));
         }
+    } else {
+        super::file_common::report_auto_approval("file_diff_edit", &target_path);
     }
 
     // 重建文件内容
@@ -94,6 +96,7 @@ pub async fn execute_file_diff_edit(
         new_content
     };
 
+    crate::checkpoint::snapshot(&target_path)?;
     fs::write(&target_path, &final_content)?;
 
     // Auto-hook: Update outline index
@@ -115,15 +118,7 @@ pub async fn execute_file_diff_edit(
         diff_merge_result
     );
 
-    let verification_prompt = "Please verify the DIFF merge result above. Check if all modifications are correct and there are no syntax errors (e.g., unclosed brackets, misaligned indentation). If everything looks good, you may continue. If there are any issues, describe the problem clearly.";
-
-    Ok(ToolResult {
-        success: true,
-        brief,
-        message: output,
-        verification_required: true,
-        verification_message: Some(verification_prompt.to_string()),
-    })
+    Ok(ToolResult::ok(brief, output))
 }
 
 fn generate_preview(args: &FileDiffEditArgs) -> String {
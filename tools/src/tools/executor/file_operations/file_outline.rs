@@ -1,33 +1,12 @@
 use anyhow::Result;
 use std::path::Path;
-use config::Config; // Changed from load_config as it is a static method on Config struct usually or we use Config::load()
 
 use super::super::parser;
-use super::super::lsp_client::LspClient;
+use super::super::lsp_operations::client_for;
 use super::file_common::normalize_path;
 use crate::tools::args::FileOutlineArgs;
 use crate::tools::types::ToolResult;
 
-fn get_lsp_command(ext: &str) -> Option<(String, Vec<String>)> {
-    // Load config to check for custom LSP servers
-    if let Ok(Some(lsp_settings)) = Config::load_lsp() {
-         if let Some(lsp_config) = lsp_settings.servers.get(ext) {
-             return Some((lsp_config.command.clone(), lsp_config.args.clone()));
-         }
-    }
-
-    // Fallback to defaults
-    match ext {
-        "rs" => Some(("rust-analyzer".to_string(), vec![])),
-        "py" => Some(("pylsp".to_string(), vec![])),
-        "go" => Some(("gopls".to_string(), vec![])),
-        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => 
-            Some(("typescript-language-server".to_string(), vec!["--stdio".to_string()])),
-        "c" | "cpp" | "h" | "hpp" | "cc" => Some(("clangd".to_string(), vec!["--stdio".to_string()])),
-        _ => None,
-    }
-}
-
 fn flatten_symbols(symbols: Vec<lsp_types::DocumentSymbol>) -> Vec<parser::Symbol> {
     let mut result = Vec::new();
     for sym in symbols {
@@ -48,24 +27,15 @@ fn flatten_symbols(symbols: Vec<lsp_types::DocumentSymbol>) -> Vec<parser::Symbo
 }
 
 async fn try_lsp_outline(path: &Path) -> Result<Vec<parser::Symbol>> {
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-    let (cmd, args) = get_lsp_command(ext).ok_or_else(|| anyhow::anyhow!("No LSP for extension"))?;
-    
-    let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-    let mut client = LspClient::new(&cmd, &args_refs).await?;
-    
-    // Use parent directory as root
-    let root = path.parent().unwrap_or(path);
-    
-    client.initialize(root).await?;
+    // The server stays running for later calls
+    let client = client_for(path).await?;
     let symbols = client.document_symbol(path).await?;
-    client.shutdown().await?;
-    
+
     let mut flat_symbols = flatten_symbols(symbols);
-    
+
     // Sort by line number
     flat_symbols.sort_by_key(|k| k.line);
-    
+
     Ok(flat_symbols)
 }
 
@@ -59,10 +59,12 @@ pub async fn execute_file_replace(
             "file_replace",
             &target_path,
             Some(&details),
-        )? {
+        ).await? {
             let i18n = ui::get_i18n();
             return Ok(ToolResult::error(i18n.get("approval_rejected")));
         }
+    } else {
+        super::file_common::report_auto_approval("file_replace", &target_path);
     }
 
     // 写回文件
@@ -71,6 +73,7 @@ pub async fn execute_file_replace(
     } else {
         content
     };
+    crate::checkpoint::snapshot(&target_path)?;
     fs::write(&target_path, &final_content)?;
 
     // Auto-hook: Update outline index
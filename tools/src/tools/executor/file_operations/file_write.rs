@@ -33,12 +33,17 @@ pub async fn execute_file_write(
             "file_write",
             &target_path,
             Some(&details),
-        )? {
+        ).await? {
             let i18n = get_i18n();
             return Ok(ToolResult::error(i18n.get("approval_rejected")));
         }
+    } else {
+        super::file_common::report_auto_approval("file_write", &target_path);
     }
 
+    // Save the pre-image so the turn can be undone
+    crate::checkpoint::snapshot(&target_path)?;
+
     // 创建父目录（如果不存在）
     if let Some(parent) = target_path.parent() {
         fs::create_dir_all(parent)?;
@@ -3,6 +3,7 @@ use std::path::Path;
 
 use crate::tools::types::ToolResult;
 
+mod apply_patch;
 pub mod file_common;
 mod file_diff_edit;
 mod file_list;
@@ -51,3 +52,16 @@ pub async fn execute_file_diff_edit(
 ) -> Result<ToolResult> {
     file_diff_edit::execute_file_diff_edit(arguments, working_dir, require_approval).await
 }
+
+pub async fn execute_apply_patch(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+) -> Result<ToolResult> {
+    apply_patch::execute_apply_patch(arguments, working_dir, require_approval).await
+}
+
+/// Paths a patch for `apply_patch` touches
+pub fn apply_patch_paths(patch: &str) -> Vec<String> {
+    apply_patch::patch_paths(patch)
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use chrono::DateTime;
+use serde::Serialize;
+use std::path::Path;
+
+use super::{check_reference, git_error, run_git};
+use crate::tools::args::GitBlameArgs;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+/// Consecutive lines last changed by the same commit
+#[derive(Debug, Serialize)]
+struct BlameRange {
+    commit: String,
+    author: String,
+    date: String,
+    summary: String,
+    start_line: usize,
+    end_line: usize,
+    content: String,
+}
+
+pub async fn execute_git_blame(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: GitBlameArgs = serde_json::from_str(arguments)?;
+
+    let range = match (args.start_line, args.end_line) {
+        (Some(start), Some(end)) => Some(format!("-L{},{}", start, end)),
+        (Some(start), None) => Some(format!("-L{},", start)),
+        (None, Some(end)) => Some(format!("-L1,{}", end)),
+        (None, None) => None,
+    };
+
+    let mut blame_args = vec!["blame", "--line-porcelain"];
+    blame_args.extend(range.as_deref());
+    if let Some(reference) = args.reference.as_deref() {
+        if let Err(e) = check_reference(working_dir, reference).await {
+            return Ok(git_error(e));
+        }
+        blame_args.push("--end-of-options");
+        blame_args.push(reference);
+    }
+    blame_args.push("--");
+    blame_args.push(&args.path);
+
+    let output = match run_git(working_dir, &blame_args).await {
+        Ok(output) => output,
+        Err(e) => return Ok(git_error(e)),
+    };
+
+    let ranges = parse_blame(&output);
+    let lines: usize = ranges.iter().map(|r| r.end_line - r.start_line + 1).sum();
+    let i18n = get_i18n();
+    let brief = i18n
+        .get("git_blame_brief")
+        .replacen("{}", &lines.to_string(), 1)
+        .replacen("{}", &args.path, 1);
+
+    Ok(ToolResult::ok(brief, serde_json::to_string_pretty(&ranges)?))
+}
+
+/// Parse `git blame --line-porcelain` into ranges
+fn parse_blame(output: &str) -> Vec<BlameRange> {
+    let mut ranges: Vec<BlameRange> = Vec::new();
+    let mut commit = String::new();
+    let mut line_number = 0;
+    let mut author = String::new();
+    let mut date = String::new();
+    let mut summary = String::new();
+
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            // The content line ends the record
+            match ranges.last_mut() {
+                Some(last) if last.commit == commit && last.end_line + 1 == line_number => {
+                    last.end_line = line_number;
+                    last.content.push('\n');
+                    last.content.push_str(content);
+                }
+                _ => ranges.push(BlameRange {
+                    commit: commit.clone(),
+                    author: author.clone(),
+                    date: date.clone(),
+                    summary: summary.clone(),
+                    start_line: line_number,
+                    end_line: line_number,
+                    content: content.to_string(),
+                }),
+            }
+        } else if let Some(value) = line.strip_prefix("author ") {
+            author = value.to_string();
+        } else if let Some(value) = line.strip_prefix("author-time ") {
+            date = value
+                .parse()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+        } else if let Some(value) = line.strip_prefix("summary ") {
+            summary = value.to_string();
+        } else {
+            // Header: <sha> <original line> <final line> [<group size>]
+            let fields: Vec<&str> = line.split(' ').collect();
+            if fields.len() >= 3 && fields[0].len() >= 40 {
+                commit = fields[0].chars().take(8).collect();
+                line_number = fields[2].parse().unwrap_or(0);
+            }
+        }
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blame_groups_lines() {
+        let sha_a = "a".repeat(40);
+        let sha_b = "b".repeat(40);
+        let record = |sha: &str, line: usize, author: &str, content: &str| {
+            format!(
+                "{sha} {line} {line} 1\nauthor {author}\nauthor-mail <x@y>\nauthor-time 1735689600\n\
+                 author-tz +0000\nsummary Change by {author}\nfilename a.rs\n\t{content}\n"
+            )
+        };
+        let output = [
+            record(&sha_a, 1, "Alice", "fn main() {"),
+            record(&sha_a, 2, "Alice", "    run();"),
+            record(&sha_b, 3, "Bob", "}"),
+        ]
+        .concat();
+
+        let ranges = parse_blame(&output);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].commit, "aaaaaaaa");
+        assert_eq!((ranges[0].start_line, ranges[0].end_line), (1, 2));
+        assert_eq!(ranges[0].content, "fn main() {\n    run();");
+        assert_eq!(ranges[0].date, "2025-01-01");
+        assert_eq!(ranges[1].author, "Bob");
+        assert_eq!(ranges[1].summary, "Change by Bob");
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::{git_error, run_git};
+use crate::tools::args::GitCheckoutArgs;
+use crate::tools::executor::file_operations::file_common::{
+    check_file_action_approval, normalize_path, report_auto_approval,
+};
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+pub async fn execute_git_checkout(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+) -> Result<ToolResult> {
+    let args: GitCheckoutArgs = serde_json::from_str(arguments)?;
+    let i18n = get_i18n();
+
+    if args.create && !args.paths.is_empty() {
+        return Ok(ToolResult::error(i18n.get("git_checkout_create_with_paths")));
+    }
+
+    // A target starting with `-` would be read as an option, and `git checkout` has no
+    // `--end-of-options` to stop that, so such targets are refused outright
+    let target = args.target.as_str();
+    if target.trim().is_empty() || target.starts_with('-') || target.chars().any(char::is_whitespace) {
+        return Ok(ToolResult::error(i18n.get("git_invalid_ref").replace("{}", target)));
+    }
+
+    let mut checkout_args = vec!["checkout"];
+    if args.create {
+        checkout_args.push("-b");
+    }
+    checkout_args.push(target);
+    if !args.paths.is_empty() {
+        checkout_args.push("--");
+        checkout_args.extend(args.paths.iter().map(|p| p.as_str()));
+    }
+
+    if require_approval {
+        let mut preview = format!("git {}", checkout_args.join(" "));
+        if !args.paths.is_empty() {
+            preview.push_str("\n\nLocal changes to these files will be overwritten.");
+        }
+        if !check_file_action_approval("git_checkout", working_dir, Some(&preview)).await? {
+            return Ok(ToolResult::error(i18n.get("approval_rejected")));
+        }
+    } else {
+        report_auto_approval("git_checkout", working_dir);
+    }
+
+    // Restoring files discards edits, keep them so the turn can be undone
+    for path in &args.paths {
+        crate::checkpoint::snapshot(&normalize_path(path, working_dir))?;
+    }
+
+    // git reports the switch on stderr, so describe the new state ourselves
+    if let Err(e) = run_git(working_dir, &checkout_args).await {
+        return Ok(git_error(e));
+    }
+    let head = run_git(working_dir, &["log", "-1", "--format=%h %s"])
+        .await
+        .unwrap_or_default();
+    let branch = run_git(working_dir, &["branch", "--show-current"])
+        .await
+        .unwrap_or_default();
+
+    let output = if args.paths.is_empty() {
+        format!(
+            "Branch: {}\nHEAD: {}",
+            if branch.trim().is_empty() { "(detached)" } else { branch.trim() },
+            head.trim()
+        )
+    } else {
+        format!(
+            "Restored from {}:\n{}",
+            args.target,
+            args.paths.join("\n")
+        )
+    };
+
+    Ok(ToolResult::ok(
+        i18n.get("git_checkout_brief").replace("{}", &args.target),
+        output,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejects_option_targets() {
+        let dir = std::env::current_dir().unwrap();
+        for args in [
+            r#"{"target": "--orphan=x"}"#,
+            r#"{"target": "-f", "paths": ["a.rs"]}"#,
+            r#"{"target": "-", "create": true}"#,
+            r#"{"target": "main --force"}"#,
+        ] {
+            let result = execute_git_checkout(args, &dir, false).await.unwrap();
+            assert!(!result.success, "{}", args);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::{git_error, run_git};
+use crate::tools::args::GitCommitArgs;
+use crate::tools::executor::file_operations::file_common::{check_file_action_approval, report_auto_approval};
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+pub async fn execute_git_commit(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+) -> Result<ToolResult> {
+    let args: GitCommitArgs = serde_json::from_str(arguments)?;
+    let i18n = get_i18n();
+
+    if args.message.trim().is_empty() {
+        return Ok(ToolResult::error(i18n.get("git_commit_empty_message")));
+    }
+
+    if require_approval {
+        let preview = match build_preview(&args, working_dir).await {
+            Ok(preview) => preview,
+            Err(e) => return Ok(git_error(e)),
+        };
+        if !check_file_action_approval("git_commit", working_dir, Some(&preview)).await? {
+            return Ok(ToolResult::error(i18n.get("approval_rejected")));
+        }
+    } else {
+        report_auto_approval("git_commit", working_dir);
+    }
+
+    if !args.paths.is_empty() {
+        let mut add_args = vec!["add", "--"];
+        add_args.extend(args.paths.iter().map(|p| p.as_str()));
+        if let Err(e) = run_git(working_dir, &add_args).await {
+            return Ok(git_error(e));
+        }
+    }
+
+    let mut commit_args = vec!["commit", "-m", args.message.as_str()];
+    if args.all {
+        commit_args.push("-a");
+    }
+    let output = match run_git(working_dir, &commit_args).await {
+        Ok(output) => output,
+        Err(e) => return Ok(git_error(e)),
+    };
+
+    let hash = run_git(working_dir, &["rev-parse", "--short", "HEAD"])
+        .await
+        .map(|h| h.trim().to_string())
+        .unwrap_or_default();
+
+    Ok(ToolResult::ok(
+        i18n.get("git_commit_brief").replace("{}", &hash),
+        output,
+    ))
+}
+
+/// Describe what the commit would contain without touching the index
+async fn build_preview(args: &GitCommitArgs, working_dir: &Path) -> Result<String> {
+    let mut preview = format!("Message:\n{}\n", args.message.trim());
+
+    let staged = run_git(working_dir, &["diff", "--cached", "--name-status"]).await?;
+    if !staged.trim().is_empty() {
+        preview.push_str(&format!("\nStaged:\n{}", staged));
+    }
+    if !args.paths.is_empty() {
+        preview.push_str(&format!("\nAdding:\n{}\n", args.paths.join("\n")));
+    }
+    if args.all {
+        let tracked = run_git(working_dir, &["diff", "--name-status"]).await?;
+        if !tracked.trim().is_empty() {
+            preview.push_str(&format!("\nTracked changes (-a):\n{}", tracked));
+        }
+    }
+
+    Ok(preview)
+}
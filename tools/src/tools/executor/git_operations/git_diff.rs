@@ -0,0 +1,51 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::{check_reference, format_patch, git_error, parse_numstat, run_git, total_lines};
+use crate::tools::args::GitDiffArgs;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+pub async fn execute_git_diff(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: GitDiffArgs = serde_json::from_str(arguments)?;
+
+    // Unstaged by default, --cached for the index, or the working tree against a ref
+    let mut diff_args = vec!["diff"];
+    if args.staged {
+        diff_args.push("--cached");
+    }
+    if let Some(reference) = args.reference.as_deref() {
+        if let Err(e) = check_reference(working_dir, reference).await {
+            return Ok(git_error(e));
+        }
+        diff_args.push("--end-of-options");
+        diff_args.push(reference);
+    }
+    diff_args.push("--");
+    if let Some(path) = args.path.as_deref() {
+        diff_args.push(path);
+    }
+
+    let mut numstat_args = diff_args.clone();
+    numstat_args.insert(1, "--numstat");
+
+    let numstat = match run_git(working_dir, &numstat_args).await {
+        Ok(output) => output,
+        Err(e) => return Ok(git_error(e)),
+    };
+    let patch = match run_git(working_dir, &diff_args).await {
+        Ok(output) => output,
+        Err(e) => return Ok(git_error(e)),
+    };
+
+    let stats = parse_numstat(&numstat);
+    let (added, deleted) = total_lines(&stats);
+    let i18n = get_i18n();
+    let brief = i18n
+        .get("git_diff_brief")
+        .replacen("{}", &stats.len().to_string(), 1)
+        .replacen("{}", &added.to_string(), 1)
+        .replacen("{}", &deleted.to_string(), 1);
+
+    Ok(ToolResult::ok(brief, format_patch(&stats, &patch)))
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+use super::{check_reference, git_error, run_git};
+use crate::tools::args::GitLogArgs;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+/// Upper bound for `max_count`
+const MAX_LOG_COUNT: usize = 200;
+
+/// Field and record separators unlikely to appear in commit metadata
+const FIELD_SEP: char = '\x1f';
+const RECORD_SEP: char = '\x1e';
+
+#[derive(Debug, Serialize)]
+struct Commit {
+    hash: String,
+    short_hash: String,
+    author: String,
+    email: String,
+    date: String,
+    subject: String,
+}
+
+pub async fn execute_git_log(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: GitLogArgs = serde_json::from_str(arguments)?;
+
+    let max_count = format!("--max-count={}", args.max_count.clamp(1, MAX_LOG_COUNT));
+    let format = format!(
+        "--format=%H{0}%h{0}%an{0}%ae{0}%aI{0}%s{1}",
+        FIELD_SEP, RECORD_SEP
+    );
+    let author = args.author.as_ref().map(|a| format!("--author={}", a));
+    let since = args.since.as_ref().map(|s| format!("--since={}", s));
+
+    let mut log_args = vec!["log", max_count.as_str(), format.as_str()];
+    log_args.extend(author.as_deref());
+    log_args.extend(since.as_deref());
+    if let Some(reference) = args.reference.as_deref() {
+        if let Err(e) = check_reference(working_dir, reference).await {
+            return Ok(git_error(e));
+        }
+        log_args.push("--end-of-options");
+        log_args.push(reference);
+    }
+    log_args.push("--");
+    log_args.extend(args.path.as_deref());
+
+    let output = match run_git(working_dir, &log_args).await {
+        Ok(output) => output,
+        Err(e) => return Ok(git_error(e)),
+    };
+
+    let commits = parse_log(&output);
+    let i18n = get_i18n();
+    let brief = i18n
+        .get("git_log_brief")
+        .replace("{}", &commits.len().to_string());
+
+    Ok(ToolResult::ok(brief, serde_json::to_string_pretty(&commits)?))
+}
+
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let fields: Vec<&str> = record.trim().split(FIELD_SEP).collect();
+            match fields.as_slice() {
+                [hash, short_hash, author, email, date, subject] => Some(Commit {
+                    hash: hash.to_string(),
+                    short_hash: short_hash.to_string(),
+                    author: author.to_string(),
+                    email: email.to_string(),
+                    date: date.to_string(),
+                    subject: subject.to_string(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log() {
+        let output = "aaaa\x1fa\x1fAlice\x1falice@example.com\x1f2025-01-02T03:04:05+00:00\x1fFix bug\x1e\n\
+                      bbbb\x1fb\x1fBob\x1fbob@example.com\x1f2025-01-01T00:00:00+00:00\x1fInitial commit\x1e\n";
+        let commits = parse_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "aaaa");
+        assert_eq!(commits[0].subject, "Fix bug");
+        assert_eq!(commits[1].author, "Bob");
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::{check_reference, format_patch, git_error, parse_numstat, run_git};
+use crate::tools::args::GitShowArgs;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+pub async fn execute_git_show(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: GitShowArgs = serde_json::from_str(arguments)?;
+    let i18n = get_i18n();
+    if let Err(e) = check_reference(working_dir, &args.reference).await {
+        return Ok(git_error(e));
+    }
+
+    // With a path, return the file content at that revision
+    if let Some(path) = args.path.as_deref() {
+        let object = format!("{}:{}", args.reference, path.trim_start_matches("./"));
+        return Ok(match run_git(working_dir, &["show", "--end-of-options", &object]).await {
+            Ok(content) => ToolResult::ok(
+                i18n.get("git_show_brief").replace("{}", &object),
+                content,
+            ),
+            Err(e) => git_error(e),
+        });
+    }
+
+    let header = match run_git(
+        working_dir,
+        &[
+            "show",
+            "--no-patch",
+            "--format=commit %H%nauthor: %an <%ae>%ndate: %aI%nparents: %P%n%n%B",
+            "--end-of-options",
+            &args.reference,
+        ],
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(e) => return Ok(git_error(e)),
+    };
+
+    // --format= drops the header so only the diff is left
+    let numstat = run_git(
+        working_dir,
+        &["show", "--numstat", "--format=", "--end-of-options", &args.reference],
+    )
+    .await;
+    let patch = run_git(working_dir, &["show", "--format=", "--end-of-options", &args.reference]).await;
+    let (numstat, patch) = match (numstat, patch) {
+        (Ok(numstat), Ok(patch)) => (numstat, patch),
+        (Err(e), _) | (_, Err(e)) => return Ok(git_error(e)),
+    };
+
+    let output = format!(
+        "{}\n\n{}",
+        header.trim_end(),
+        format_patch(&parse_numstat(&numstat), &patch)
+    );
+    Ok(ToolResult::ok(
+        i18n.get("git_show_brief").replace("{}", &args.reference),
+        output,
+    ))
+}
@@ -0,0 +1,162 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+use super::{git_error, run_git};
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+#[derive(Debug, Default, Serialize)]
+struct RepoStatus {
+    branch: Option<String>,
+    upstream: Option<String>,
+    ahead: u32,
+    behind: u32,
+    staged: Vec<FileChange>,
+    unstaged: Vec<FileChange>,
+    untracked: Vec<String>,
+    conflicted: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct FileChange {
+    path: String,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<String>,
+}
+
+pub async fn execute_git_status(working_dir: &Path) -> Result<ToolResult> {
+    let output = match run_git(working_dir, &["status", "--porcelain=v2", "--branch"]).await {
+        Ok(output) => output,
+        Err(e) => return Ok(git_error(e)),
+    };
+
+    let status = parse_status(&output);
+    let i18n = get_i18n();
+    let brief = i18n
+        .get("git_status_brief")
+        .replacen("{}", status.branch.as_deref().unwrap_or("(detached)"), 1)
+        .replacen("{}", &status.staged.len().to_string(), 1)
+        .replacen("{}", &status.unstaged.len().to_string(), 1)
+        .replacen("{}", &status.untracked.len().to_string(), 1);
+
+    Ok(ToolResult::ok(brief, serde_json::to_string_pretty(&status)?))
+}
+
+/// Parse `git status --porcelain=v2 --branch`
+fn parse_status(output: &str) -> RepoStatus {
+    let mut status = RepoStatus::default();
+
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(n) = part.strip_prefix('+') {
+                            status.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            status.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let fields: Vec<&str> = line.splitn(2, ' ').collect();
+        match fields.as_slice() {
+            ["?", path] => status.untracked.push(path.to_string()),
+            ["1", rest] => {
+                // XY sub mH mI mW hH hI path
+                let parts: Vec<&str> = rest.splitn(8, ' ').collect();
+                if let [xy, .., path] = parts.as_slice() {
+                    push_changes(&mut status, xy, path, None);
+                }
+            }
+            ["2", rest] => {
+                // XY sub mH mI mW hH hI Xscore path<TAB>origPath
+                let parts: Vec<&str> = rest.splitn(9, ' ').collect();
+                if let [xy, .., paths] = parts.as_slice() {
+                    let (path, from) = paths.split_once('\t').unwrap_or((paths, ""));
+                    push_changes(&mut status, xy, path, Some(from.to_string()));
+                }
+            }
+            ["u", rest] => {
+                if let Some(path) = rest.splitn(10, ' ').nth(9) {
+                    status.conflicted.push(path.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    status
+}
+
+fn push_changes(status: &mut RepoStatus, xy: &str, path: &str, from: Option<String>) {
+    let mut codes = xy.chars();
+    let staged = codes.next().unwrap_or('.');
+    let unstaged = codes.next().unwrap_or('.');
+
+    if staged != '.' {
+        status.staged.push(FileChange {
+            path: path.to_string(),
+            status: describe(staged),
+            from: from.clone(),
+        });
+    }
+    if unstaged != '.' {
+        status.unstaged.push(FileChange {
+            path: path.to_string(),
+            status: describe(unstaged),
+            from: None,
+        });
+    }
+}
+
+fn describe(code: char) -> &'static str {
+    match code {
+        'M' => "modified",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'T' => "type_changed",
+        _ => "changed",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let output = "\
+# branch.oid 1234567890abcdef
+# branch.head main
+# branch.upstream origin/main
+# branch.ab +2 -1
+1 M. N... 100644 100644 100644 aaa bbb src/lib.rs
+1 .M N... 100644 100644 100644 aaa aaa README.md
+2 R. N... 100644 100644 100644 aaa aaa R100 src/new name.rs\tsrc/old.rs
+u UU N... 100644 100644 100644 100644 aaa bbb ccc conflict.rs
+? notes.txt
+";
+        let status = parse_status(output);
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(status.staged.len(), 2);
+        assert_eq!(status.staged[1].path, "src/new name.rs");
+        assert_eq!(status.staged[1].from.as_deref(), Some("src/old.rs"));
+        assert_eq!(status.unstaged[0].path, "README.md");
+        assert_eq!(status.conflicted, vec!["conflict.rs"]);
+        assert_eq!(status.untracked, vec!["notes.txt"]);
+    }
+}
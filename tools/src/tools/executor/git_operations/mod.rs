@@ -0,0 +1,185 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::tools::types::ToolResult;
+
+mod git_blame;
+mod git_checkout;
+mod git_commit;
+mod git_diff;
+mod git_log;
+mod git_show;
+mod git_status;
+
+/// Patches longer than this are truncated before they reach the model
+const MAX_PATCH_CHARS: usize = 60_000;
+
+pub async fn execute_git_status(working_dir: &Path) -> Result<ToolResult> {
+    git_status::execute_git_status(working_dir).await
+}
+
+pub async fn execute_git_diff(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    git_diff::execute_git_diff(arguments, working_dir).await
+}
+
+pub async fn execute_git_log(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    git_log::execute_git_log(arguments, working_dir).await
+}
+
+pub async fn execute_git_blame(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    git_blame::execute_git_blame(arguments, working_dir).await
+}
+
+pub async fn execute_git_show(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    git_show::execute_git_show(arguments, working_dir).await
+}
+
+pub async fn execute_git_commit(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+) -> Result<ToolResult> {
+    git_commit::execute_git_commit(arguments, working_dir, require_approval).await
+}
+
+pub async fn execute_git_checkout(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+) -> Result<ToolResult> {
+    git_checkout::execute_git_checkout(arguments, working_dir, require_approval).await
+}
+
+/// Run git in the working directory and return stdout
+///
+/// A non-zero exit status becomes an error carrying git's own message.
+async fn run_git(working_dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(["-c", "core.quotepath=false", "--no-pager"])
+        .args(args)
+        .current_dir(working_dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .await
+        .map_err(|e| anyhow!("failed to run git: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if output.status.success() {
+        return Ok(stdout);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    Err(anyhow!("git {} failed: {}", args[0], message))
+}
+
+/// Check a model-supplied revision or `a..b` / `a...b` range before it reaches git
+///
+/// A value starting with `-` would be read as an option (`--output=<file>` writes
+/// anywhere), and every side of a range must name an existing object.
+async fn check_reference(working_dir: &Path, reference: &str) -> Result<()> {
+    let invalid = || anyhow!(ui::get_i18n().get("git_invalid_ref").replace("{}", reference));
+    let sides: Vec<&str> = match reference.split_once("...") {
+        Some((from, to)) => vec![from, to],
+        None => match reference.split_once("..") {
+            Some((from, to)) => vec![from, to],
+            None => vec![reference],
+        },
+    };
+    if reference.trim().is_empty() || sides.iter().all(|side| side.is_empty()) {
+        return Err(invalid());
+    }
+    for side in sides.into_iter().filter(|side| !side.is_empty()) {
+        if side.starts_with('-') || side.chars().any(char::is_whitespace) {
+            return Err(invalid());
+        }
+        let object = format!("{}^{{object}}", side);
+        run_git(working_dir, &["rev-parse", "--verify", "--quiet", "--end-of-options", &object])
+            .await
+            .map_err(|_| invalid())?;
+    }
+    Ok(())
+}
+
+/// Turn a git failure into a tool error the model can act on
+fn git_error(e: anyhow::Error) -> ToolResult {
+    ToolResult::error(e.to_string())
+}
+
+/// Per-file line counts from `git diff --numstat`
+struct FileStat {
+    path: String,
+    added: Option<u64>,
+    deleted: Option<u64>,
+}
+
+/// Parse `--numstat` output, binary files have no line counts
+fn parse_numstat(output: &str) -> Vec<FileStat> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let added = fields.next()?;
+            let deleted = fields.next()?;
+            let path = fields.next()?;
+            Some(FileStat {
+                path: path.to_string(),
+                added: added.parse().ok(),
+                deleted: deleted.parse().ok(),
+            })
+        })
+        .collect()
+}
+
+/// Summary header followed by the (possibly truncated) patch
+fn format_patch(stats: &[FileStat], patch: &str) -> String {
+    let mut output = format!("{} file(s) changed\n", stats.len());
+    for stat in stats {
+        match (stat.added, stat.deleted) {
+            (Some(added), Some(deleted)) => {
+                output.push_str(&format!("  {} (+{} -{})\n", stat.path, added, deleted))
+            }
+            _ => output.push_str(&format!("  {} (binary)\n", stat.path)),
+        }
+    }
+
+    if !patch.trim().is_empty() {
+        output.push('\n');
+        if patch.chars().count() > MAX_PATCH_CHARS {
+            let truncated: String = patch.chars().take(MAX_PATCH_CHARS).collect();
+            output.push_str(&truncated);
+            output.push_str("\n... [patch truncated, narrow it down with `path`]\n");
+        } else {
+            output.push_str(patch);
+        }
+    }
+    output
+}
+
+/// Total added and deleted lines
+fn total_lines(stats: &[FileStat]) -> (u64, u64) {
+    stats.iter().fold((0, 0), |(a, d), s| {
+        (a + s.added.unwrap_or(0), d + s.deleted.unwrap_or(0))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_reference_rejects_options() {
+        let dir = std::env::current_dir().unwrap();
+        for reference in ["--output=x", "-p", "HEAD..--output=x", "", ".."] {
+            assert!(check_reference(&dir, reference).await.is_err(), "{}", reference);
+        }
+        assert!(check_reference(&dir, "definitely-not-a-ref-xyz").await.is_err());
+        assert!(check_reference(&dir, "HEAD").await.is_ok());
+        assert!(check_reference(&dir, "HEAD..HEAD").await.is_ok());
+    }
+}
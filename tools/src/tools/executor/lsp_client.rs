@@ -1,21 +1,52 @@
 use anyhow::Result;
-use std::path::Path;
-use async_lsp_client::LspServer;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use async_lsp_client::{LspServer, ServerMessage};
+use tokio::sync::{Mutex, Notify};
 use lsp_types::{
     InitializeParams, ClientCapabilities, Url,
     DocumentSymbolParams, TextDocumentIdentifier,
-    DocumentSymbol,
+    DocumentSymbol, Diagnostic, Location, Position, SymbolInformation,
     DidOpenTextDocumentParams, TextDocumentItem,
-    request::DocumentSymbolRequest,
-    notification::{DidOpenTextDocument, Initialized},
+    DidChangeTextDocumentParams, VersionedTextDocumentIdentifier, TextDocumentContentChangeEvent,
+    TextDocumentPositionParams, GotoDefinitionParams, GotoDefinitionResponse,
+    ReferenceParams, ReferenceContext, HoverParams, HoverContents, MarkedString,
+    WorkspaceSymbolParams, WorkspaceSymbolResponse, WorkspaceFolder, OneOf,
+    PublishDiagnosticsParams, MarkupKind,
+    TextDocumentClientCapabilities, HoverClientCapabilities, DocumentSymbolClientCapabilities,
+    WindowClientCapabilities,
+    request::{
+        DocumentSymbolRequest, GotoDefinition, References, HoverRequest, WorkspaceSymbolRequest,
+        WorkspaceConfiguration, WorkDoneProgressCreate, RegisterCapability,
+    },
+    notification::{DidOpenTextDocument, DidChangeTextDocument, Initialized},
 };
 
+/// How long to wait for a response before treating the server as stuck
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Latest publishDiagnostics per document and how many times it was published
+type PublishedDiagnostics = HashMap<Url, (u64, Vec<Diagnostic>)>;
+
+/// A language server process kept alive for the whole session
 pub struct LspClient {
     server: LspServer,
+    root: PathBuf,
+    /// LspServer hands out request ids racily, so requests go one at a time
+    request_lock: Mutex<()>,
+    /// Version and text of every document sent to the server
+    documents: Mutex<HashMap<Url, (i32, String)>>,
+    diagnostics: Arc<std::sync::Mutex<PublishedDiagnostics>>,
+    diagnostics_updated: Arc<Notify>,
+    alive: Arc<AtomicBool>,
 }
 
 impl LspClient {
-    pub async fn new(cmd: &str, args: &[&str]) -> Result<Self> {
+    /// Spawn the server and run the initialize handshake with `root` as the workspace
+    pub async fn start(cmd: &str, args: &[String], root: &Path) -> Result<Self> {
         // Verify command exists before letting async-lsp-client panic
         // We try to spawn it with --version (or just check if we can spawn it)
         // Most LSP servers support --version or --help.
@@ -25,7 +56,7 @@ impl LspClient {
             .arg("--version")
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
-            .spawn() 
+            .spawn()
         {
             Ok(mut child) => {
                 let _ = child.kill();
@@ -41,61 +72,206 @@ impl LspClient {
             }
         }
 
-        // Collect args to vector of strings to satisfy interface
-        let args_vec: Vec<&str> = args.iter().copied().collect();
-        let (server, _) = LspServer::new(cmd, args_vec);
-        // Removed .await, as new() is not async (returns tuple directly)
-        
-        Ok(Self { server })
+        let args_vec: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+        let (server, messages) = LspServer::new(cmd, args_vec);
+
+        let client = Self {
+            server,
+            root: root.to_path_buf(),
+            request_lock: Mutex::new(()),
+            documents: Mutex::new(HashMap::new()),
+            diagnostics: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            diagnostics_updated: Arc::new(Notify::new()),
+            alive: Arc::new(AtomicBool::new(true)),
+        };
+        client.handle_server_messages(messages);
+        client.initialize().await?;
+
+        Ok(client)
+    }
+
+    /// False once the server process has exited
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Answer server requests and collect diagnostics until the server exits
+    ///
+    /// The receiver must be drained, async-lsp-client panics once it fills up.
+    fn handle_server_messages(&self, mut messages: tokio::sync::mpsc::Receiver<ServerMessage>) {
+        let server = self.server.clone();
+        let diagnostics = self.diagnostics.clone();
+        let updated = self.diagnostics_updated.clone();
+        let alive = self.alive.clone();
+
+        tokio::spawn(async move {
+            while let Some(message) = messages.recv().await {
+                match message {
+                    ServerMessage::Notification(notification) => {
+                        if notification.method != "textDocument/publishDiagnostics" {
+                            continue;
+                        }
+                        let Some(params) = notification
+                            .params
+                            .and_then(|p| serde_json::from_value::<PublishDiagnosticsParams>(p).ok())
+                        else {
+                            continue;
+                        };
+                        let mut diagnostics = diagnostics.lock().unwrap();
+                        let entry = diagnostics.entry(params.uri).or_insert((0, Vec::new()));
+                        entry.0 += 1;
+                        entry.1 = params.diagnostics;
+                        updated.notify_waiters();
+                    }
+                    ServerMessage::Request(request) => {
+                        let Some(id) = request.id().cloned() else { continue };
+                        // Accept whatever the server asks for, we have no settings to offer
+                        match request.method() {
+                            "workspace/configuration" => {
+                                let items = request
+                                    .params()
+                                    .and_then(|p| p.get("items"))
+                                    .and_then(|i| i.as_array())
+                                    .map_or(0, |i| i.len());
+                                server
+                                    .send_response::<WorkspaceConfiguration>(id, vec![serde_json::Value::Null; items])
+                                    .await;
+                            }
+                            "window/workDoneProgress/create" => {
+                                server.send_response::<WorkDoneProgressCreate>(id, ()).await;
+                            }
+                            _ => {
+                                server.send_response::<RegisterCapability>(id, ()).await;
+                            }
+                        }
+                    }
+                }
+            }
+            alive.store(false, Ordering::SeqCst);
+        });
     }
 
-    pub async fn initialize(&mut self, root_path: &Path) -> Result<()> {
-        let root_uri = Url::from_directory_path(root_path)
+    async fn initialize(&self) -> Result<()> {
+        let root_uri = Url::from_directory_path(&self.root)
             .map_err(|_| anyhow::anyhow!("Invalid root path"))?;
 
+        let capabilities = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                hover: Some(HoverClientCapabilities {
+                    content_format: Some(vec![MarkupKind::Markdown, MarkupKind::PlainText]),
+                    ..Default::default()
+                }),
+                document_symbol: Some(DocumentSymbolClientCapabilities {
+                    hierarchical_document_symbol_support: Some(true),
+                    ..Default::default()
+                }),
+                publish_diagnostics: Some(Default::default()),
+                ..Default::default()
+            }),
+            window: Some(WindowClientCapabilities {
+                work_done_progress: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let name = self
+            .root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
         let params = InitializeParams {
             process_id: Some(std::process::id()),
-            root_uri: Some(root_uri),
-            capabilities: ClientCapabilities::default(),
+            root_uri: Some(root_uri.clone()),
+            workspace_folders: Some(vec![WorkspaceFolder { uri: root_uri, name }]),
+            capabilities,
             ..Default::default()
         };
 
-        self.server.initialize(params).await
+        tokio::time::timeout(REQUEST_TIMEOUT, self.server.initialize(params))
+            .await
+            .map_err(|_| anyhow::anyhow!("LSP initialization timed out"))?
             .map_err(|e| anyhow::anyhow!("LSP initialization failed: {}", e))?;
-            
-        // send_notification likely returns () or Result<()> that we should handle if it errors?
-        // If it returns (), we can't map_err.
-        // Assuming it is fire-and-forget or panic-on-fail based on previous errors.
         self.server.send_notification::<Initialized>(lsp_types::InitializedParams {}).await;
 
         Ok(())
     }
 
-    pub async fn shutdown(&mut self) -> Result<()> {
+    pub async fn shutdown(&self) -> Result<()> {
         self.server.shutdown().await
             .map_err(|e| anyhow::anyhow!("LSP shutdown failed: {}", e))?;
-            
+
         self.server.exit().await;
-            
+
         Ok(())
     }
-    
-    pub async fn document_symbol(&mut self, path: &Path) -> Result<Vec<DocumentSymbol>> {
+
+    async fn request<R>(&self, params: R::Params) -> Result<R::Result>
+    where
+        R: lsp_types::request::Request,
+    {
+        let _guard = self.request_lock.lock().await;
+        tokio::time::timeout(REQUEST_TIMEOUT, self.server.send_request::<R>(params))
+            .await
+            .map_err(|_| anyhow::anyhow!("{} timed out", R::METHOD))?
+            .map_err(|e| anyhow::anyhow!("{} failed: {}", R::METHOD, e))
+    }
+
+    /// Send the current file content to the server, returns the URI and whether it changed
+    pub async fn sync_document(&self, path: &Path) -> Result<(Url, bool)> {
         let uri = Url::from_file_path(path)
             .map_err(|_| anyhow::anyhow!("Invalid file path"))?;
+        let text = tokio::fs::read_to_string(path).await?;
+
+        let mut documents = self.documents.lock().await;
+        match documents.get_mut(&uri) {
+            Some((_, known)) if *known == text => return Ok((uri, false)),
+            Some((version, known)) => {
+                *version += 1;
+                *known = text.clone();
+                let params = DidChangeTextDocumentParams {
+                    text_document: VersionedTextDocumentIdentifier {
+                        uri: uri.clone(),
+                        version: *version,
+                    },
+                    content_changes: vec![TextDocumentContentChangeEvent {
+                        range: None,
+                        range_length: None,
+                        text,
+                    }],
+                };
+                self.server.send_notification::<DidChangeTextDocument>(params).await;
+            }
+            None => {
+                documents.insert(uri.clone(), (1, text.clone()));
+                let params = DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem {
+                        uri: uri.clone(),
+                        language_id: language_id(path).to_string(),
+                        version: 1,
+                        text,
+                    },
+                };
+                self.server.send_notification::<DidOpenTextDocument>(params).await;
+            }
+        }
+
+        Ok((uri, true))
+    }
 
-        if let Ok(content) = tokio::fs::read_to_string(path).await {
-            let item = TextDocumentItem {
-                uri: uri.clone(),
-                language_id: "unknown".to_string(),
-                version: 1,
-                text: content,
-            };
-            let params = DidOpenTextDocumentParams {
-                text_document: item,
-            };
-            self.server.send_notification::<DidOpenTextDocument>(params).await;
+    fn position_params(uri: Url, position: Position) -> TextDocumentPositionParams {
+        TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri },
+            position,
         }
+    }
+
+    pub async fn document_symbol(&self, path: &Path) -> Result<Vec<DocumentSymbol>> {
+        let (uri, _) = self.sync_document(path).await?;
 
         let params = DocumentSymbolParams {
             text_document: TextDocumentIdentifier {
@@ -105,11 +281,10 @@ impl LspClient {
             partial_result_params: Default::default(),
         };
 
-        let result = self.server.send_request::<DocumentSymbolRequest>(params).await
-            .map_err(|e| anyhow::anyhow!("Document symbol request failed: {}", e))?;
-            
+        let result = self.request::<DocumentSymbolRequest>(params).await?;
+
         let mut symbols = Vec::new();
-        
+
         if let Some(response) = result {
             match response {
                 lsp_types::DocumentSymbolResponse::Flat(si) => {
@@ -145,4 +320,144 @@ impl LspClient {
 
         Ok(symbols)
     }
+
+    pub async fn definition(&self, path: &Path, position: Position) -> Result<Vec<Location>> {
+        let (uri, _) = self.sync_document(path).await?;
+        let params = GotoDefinitionParams {
+            text_document_position_params: Self::position_params(uri, position),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+
+        Ok(match self.request::<GotoDefinition>(params).await? {
+            None => Vec::new(),
+            Some(GotoDefinitionResponse::Scalar(location)) => vec![location],
+            Some(GotoDefinitionResponse::Array(locations)) => locations,
+            Some(GotoDefinitionResponse::Link(links)) => links
+                .into_iter()
+                .map(|link| Location::new(link.target_uri, link.target_selection_range))
+                .collect(),
+        })
+    }
+
+    pub async fn references(
+        &self,
+        path: &Path,
+        position: Position,
+        include_declaration: bool,
+    ) -> Result<Vec<Location>> {
+        let (uri, _) = self.sync_document(path).await?;
+        let params = ReferenceParams {
+            text_document_position: Self::position_params(uri, position),
+            context: ReferenceContext { include_declaration },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+
+        Ok(self.request::<References>(params).await?.unwrap_or_default())
+    }
+
+    /// Hover text as markdown, None when the server has nothing for this position
+    pub async fn hover(&self, path: &Path, position: Position) -> Result<Option<String>> {
+        let (uri, _) = self.sync_document(path).await?;
+        let params = HoverParams {
+            text_document_position_params: Self::position_params(uri, position),
+            work_done_progress_params: Default::default(),
+        };
+
+        let Some(hover) = self.request::<HoverRequest>(params).await? else {
+            return Ok(None);
+        };
+        let marked = |s: MarkedString| match s {
+            MarkedString::String(text) => text,
+            MarkedString::LanguageString(code) => format!("```{}\n{}\n```", code.language, code.value),
+        };
+        let text = match hover.contents {
+            HoverContents::Scalar(s) => marked(s),
+            HoverContents::Array(items) => items.into_iter().map(marked).collect::<Vec<_>>().join("\n\n"),
+            HoverContents::Markup(markup) => markup.value,
+        };
+
+        Ok(Some(text).filter(|t| !t.trim().is_empty()))
+    }
+
+    pub async fn workspace_symbols(&self, query: &str) -> Result<Vec<SymbolInformation>> {
+        let params = WorkspaceSymbolParams {
+            query: query.to_string(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+
+        Ok(match self.request::<WorkspaceSymbolRequest>(params).await? {
+            None => Vec::new(),
+            Some(WorkspaceSymbolResponse::Flat(symbols)) => symbols,
+            Some(WorkspaceSymbolResponse::Nested(symbols)) => symbols
+                .into_iter()
+                .map(|symbol| {
+                    let location = match symbol.location {
+                        OneOf::Left(location) => location,
+                        OneOf::Right(workspace) => Location::new(workspace.uri, Default::default()),
+                    };
+                    #[allow(deprecated)]
+                    SymbolInformation {
+                        name: symbol.name,
+                        kind: symbol.kind,
+                        tags: symbol.tags,
+                        deprecated: None,
+                        location,
+                        container_name: symbol.container_name,
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    /// Diagnostics of a file, waiting up to `wait` for the server to publish fresh ones
+    pub async fn diagnostics(&self, path: &Path, wait: Duration) -> Result<Vec<Diagnostic>> {
+        let published = |uri: &Url| {
+            self.diagnostics
+                .lock()
+                .unwrap()
+                .get(uri)
+                .map(|(count, diagnostics)| (*count, diagnostics.clone()))
+        };
+
+        let (uri, changed) = self.sync_document(path).await?;
+        let before = published(&uri);
+        if let (false, Some((_, diagnostics))) = (changed, &before) {
+            return Ok(diagnostics.clone());
+        }
+
+        let seen = before.map_or(0, |(count, _)| count);
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            // Created before checking so a publish in between is not missed
+            let updated = self.diagnostics_updated.notified();
+            if let Some((count, diagnostics)) = published(&uri) {
+                if count > seen {
+                    return Ok(diagnostics);
+                }
+            }
+            if tokio::time::timeout_at(deadline, updated).await.is_err() {
+                return Ok(published(&uri).map(|(_, d)| d).unwrap_or_default());
+            }
+        }
+    }
+}
+
+/// LSP language identifier for a file extension
+fn language_id(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "rs" => "rust",
+        "py" => "python",
+        "go" => "go",
+        "ts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "java" => "java",
+        _ => "plaintext",
+    }
 }
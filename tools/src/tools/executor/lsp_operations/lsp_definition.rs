@@ -0,0 +1,34 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::{format_locations, lsp_error, prepare};
+use crate::tools::args::LspPositionArgs;
+use crate::tools::executor::file_operations::file_common::normalize_path;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+pub async fn execute_lsp_definition(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: LspPositionArgs = serde_json::from_str(arguments)?;
+    let path = normalize_path(&args.path, working_dir);
+
+    let locations = match prepare(&path, &args).await {
+        Ok((client, position)) => client.definition(&path, position).await,
+        Err(e) => Err(e),
+    };
+    let locations = match locations {
+        Ok(locations) => locations,
+        Err(e) => return Ok(lsp_error(e)),
+    };
+
+    let output = if locations.is_empty() {
+        "No definition found.".to_string()
+    } else {
+        format_locations(&locations, working_dir)
+    };
+    Ok(ToolResult::ok(
+        get_i18n()
+            .get("lsp_definition_brief")
+            .replace("{}", &locations.len().to_string()),
+        output,
+    ))
+}
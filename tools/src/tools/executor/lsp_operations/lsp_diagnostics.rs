@@ -0,0 +1,84 @@
+use anyhow::Result;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use std::path::Path;
+use std::time::Duration;
+
+use super::{client_for, display_path, lsp_error};
+use crate::tools::args::LspDiagnosticsArgs;
+use crate::tools::executor::file_operations::file_common::normalize_path;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+/// How long to wait for the server to analyse a new or changed file
+const DIAGNOSTICS_WAIT: Duration = Duration::from_secs(10);
+
+pub async fn execute_lsp_diagnostics(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: LspDiagnosticsArgs = serde_json::from_str(arguments)?;
+    let path = normalize_path(&args.path, working_dir);
+
+    let diagnostics = match client_for(&path).await {
+        Ok(client) => client.diagnostics(&path, DIAGNOSTICS_WAIT).await,
+        Err(e) => Err(e),
+    };
+    let mut diagnostics = match diagnostics {
+        Ok(diagnostics) => diagnostics,
+        Err(e) => return Ok(lsp_error(e)),
+    };
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == Some(severity))
+            .count()
+    };
+    let file = display_path(&path, working_dir);
+    let brief = get_i18n()
+        .get("lsp_diagnostics_brief")
+        .replacen("{}", &count(DiagnosticSeverity::ERROR).to_string(), 1)
+        .replacen("{}", &count(DiagnosticSeverity::WARNING).to_string(), 1)
+        .replacen("{}", &file, 1);
+
+    let output = if diagnostics.is_empty() {
+        "No diagnostics.".to_string()
+    } else {
+        diagnostics
+            .iter()
+            .map(|d| format_diagnostic(&file, d))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    Ok(ToolResult::ok(brief, output))
+}
+
+/// `file:line:col: severity[source code]: message`, like compiler output
+fn format_diagnostic(file: &str, diagnostic: &Diagnostic) -> String {
+    let severity = match diagnostic.severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "diagnostic",
+    };
+    let code = match &diagnostic.code {
+        Some(NumberOrString::Number(n)) => Some(n.to_string()),
+        Some(NumberOrString::String(s)) => Some(s.clone()),
+        None => None,
+    };
+    let origin: Vec<String> = diagnostic.source.iter().cloned().chain(code).collect();
+    let origin = if origin.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", origin.join(" "))
+    };
+
+    format!(
+        "{}:{}:{}: {}{}: {}",
+        file,
+        diagnostic.range.start.line + 1,
+        diagnostic.range.start.character + 1,
+        severity,
+        origin,
+        diagnostic.message
+    )
+}
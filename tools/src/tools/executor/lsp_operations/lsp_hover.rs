@@ -0,0 +1,30 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::{display_path, lsp_error, prepare};
+use crate::tools::args::LspPositionArgs;
+use crate::tools::executor::file_operations::file_common::normalize_path;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+pub async fn execute_lsp_hover(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: LspPositionArgs = serde_json::from_str(arguments)?;
+    let path = normalize_path(&args.path, working_dir);
+
+    let hover = match prepare(&path, &args).await {
+        Ok((client, position)) => client.hover(&path, position).await,
+        Err(e) => Err(e),
+    };
+    let i18n = get_i18n();
+    let text = match hover {
+        Ok(Some(text)) => text,
+        Ok(None) => return Ok(ToolResult::error(i18n.get("lsp_hover_empty"))),
+        Err(e) => return Ok(lsp_error(e)),
+    };
+
+    let target = format!("{}:{}", display_path(&path, working_dir), args.line);
+    Ok(ToolResult::ok(
+        i18n.get("lsp_hover_brief").replace("{}", &target),
+        text,
+    ))
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::{format_locations, lsp_error, prepare};
+use crate::tools::args::LspReferencesArgs;
+use crate::tools::executor::file_operations::file_common::normalize_path;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+pub async fn execute_lsp_references(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: LspReferencesArgs = serde_json::from_str(arguments)?;
+    let path = normalize_path(&args.position.path, working_dir);
+
+    let locations = match prepare(&path, &args.position).await {
+        Ok((client, position)) => {
+            client
+                .references(&path, position, args.include_declaration)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    let mut locations = match locations {
+        Ok(locations) => locations,
+        Err(e) => return Ok(lsp_error(e)),
+    };
+
+    let output = if locations.is_empty() {
+        "No references found.".to_string()
+    } else {
+        locations.sort_by(|a, b| {
+            (a.uri.as_str(), a.range.start.line, a.range.start.character)
+                .cmp(&(b.uri.as_str(), b.range.start.line, b.range.start.character))
+        });
+        format_locations(&locations, working_dir)
+    };
+    Ok(ToolResult::ok(
+        get_i18n()
+            .get("lsp_references_brief")
+            .replace("{}", &locations.len().to_string()),
+        output,
+    ))
+}
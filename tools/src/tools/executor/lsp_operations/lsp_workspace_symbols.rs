@@ -0,0 +1,55 @@
+use anyhow::Result;
+use lsp_types::Location;
+use std::path::Path;
+
+use super::{client_for, format_locations, lsp_error};
+use crate::tools::args::LspWorkspaceSymbolsArgs;
+use crate::tools::executor::file_operations::file_common::normalize_path;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+/// Servers may return the whole workspace for short queries
+const MAX_SYMBOLS: usize = 200;
+
+pub async fn execute_lsp_workspace_symbols(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    let args: LspWorkspaceSymbolsArgs = serde_json::from_str(arguments)?;
+    let path = normalize_path(&args.path, working_dir);
+
+    let symbols = match client_for(&path).await {
+        Ok(client) => client.workspace_symbols(&args.query).await,
+        Err(e) => Err(e),
+    };
+    let symbols = match symbols {
+        Ok(symbols) => symbols,
+        Err(e) => return Ok(lsp_error(e)),
+    };
+
+    let shown = &symbols[..symbols.len().min(MAX_SYMBOLS)];
+    let locations: Vec<Location> = shown.iter().map(|s| s.location.clone()).collect();
+    let mut output = format_locations(&locations, working_dir)
+        .lines()
+        .zip(shown)
+        .map(|(location, symbol)| {
+            let container = symbol
+                .container_name
+                .as_deref()
+                .map(|c| format!(" in {}", c))
+                .unwrap_or_default();
+            format!("{:?} {}{} - {}", symbol.kind, symbol.name, container, location)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if symbols.is_empty() {
+        output = "No symbols found.".to_string();
+    } else if symbols.len() > MAX_SYMBOLS {
+        output.push_str(&format!("\n... {} more", symbols.len() - MAX_SYMBOLS));
+    }
+
+    Ok(ToolResult::ok(
+        get_i18n()
+            .get("lsp_workspace_symbols_brief")
+            .replacen("{}", &symbols.len().to_string(), 1)
+            .replacen("{}", &args.query, 1),
+        output,
+    ))
+}
@@ -0,0 +1,233 @@
+use anyhow::{anyhow, Result};
+use config::Config;
+use lsp_types::{Location, Position};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Mutex;
+
+use super::lsp_client::LspClient;
+use crate::tools::args::LspPositionArgs;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+mod lsp_definition;
+mod lsp_diagnostics;
+mod lsp_hover;
+mod lsp_references;
+mod lsp_workspace_symbols;
+
+/// Files that mark a project directory when there is no git repository
+const PROJECT_MARKERS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    "setup.py",
+    "compile_commands.json",
+    "CMakeLists.txt",
+];
+
+/// Running servers keyed by command and workspace root
+type ClientPool = Mutex<HashMap<(String, PathBuf), Arc<LspClient>>>;
+
+static CLIENTS: OnceLock<ClientPool> = OnceLock::new();
+
+pub async fn execute_lsp_definition(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    lsp_definition::execute_lsp_definition(arguments, working_dir).await
+}
+
+pub async fn execute_lsp_references(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    lsp_references::execute_lsp_references(arguments, working_dir).await
+}
+
+pub async fn execute_lsp_hover(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    lsp_hover::execute_lsp_hover(arguments, working_dir).await
+}
+
+pub async fn execute_lsp_workspace_symbols(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    lsp_workspace_symbols::execute_lsp_workspace_symbols(arguments, working_dir).await
+}
+
+pub async fn execute_lsp_diagnostics(arguments: &str, working_dir: &Path) -> Result<ToolResult> {
+    lsp_diagnostics::execute_lsp_diagnostics(arguments, working_dir).await
+}
+
+fn lsp_error(e: anyhow::Error) -> ToolResult {
+    ToolResult::error(e.to_string())
+}
+
+/// Server command for a file extension, `lsp.json` first, then the built-in defaults
+pub fn get_lsp_command(ext: &str) -> Option<(String, Vec<String>)> {
+    // Load config to check for custom LSP servers
+    if let Ok(Some(lsp_settings)) = Config::load_lsp() {
+         if let Some(lsp_config) = lsp_settings.servers.get(ext) {
+             return Some((lsp_config.command.clone(), lsp_config.args.clone()));
+         }
+    }
+
+    // Fallback to defaults
+    match ext {
+        "rs" => Some(("rust-analyzer".to_string(), vec![])),
+        "py" => Some(("pylsp".to_string(), vec![])),
+        "go" => Some(("gopls".to_string(), vec![])),
+        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" =>
+            Some(("typescript-language-server".to_string(), vec!["--stdio".to_string()])),
+        "c" | "cpp" | "h" | "hpp" | "cc" => Some(("clangd".to_string(), vec!["--stdio".to_string()])),
+        _ => None,
+    }
+}
+
+/// Repository root of a file, else its nearest project directory, else its directory
+fn workspace_root(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or(path);
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .or_else(|| {
+            dir.ancestors()
+                .find(|d| PROJECT_MARKERS.iter().any(|m| d.join(m).exists()))
+        })
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// The running server for this file's language and workspace, started on first use
+pub async fn client_for(path: &Path) -> Result<Arc<LspClient>> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let (cmd, args) = get_lsp_command(ext)
+        .ok_or_else(|| anyhow!(get_i18n().get("lsp_no_server").replace("{}", ext)))?;
+    let root = workspace_root(path);
+
+    // Held while starting so concurrent calls don't spawn the same server twice
+    let mut clients = CLIENTS.get_or_init(Default::default).lock().await;
+    let key = (cmd, root);
+    if let Some(client) = clients.get(&key) {
+        if client.is_alive() {
+            return Ok(client.clone());
+        }
+    }
+
+    let client = Arc::new(LspClient::start(&key.0, &args, &key.1).await?);
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+/// Turn a 1-indexed line plus a symbol name or column into an LSP position
+///
+/// Without either the first non-blank character of the line is used.
+fn resolve_position(text: &str, args: &LspPositionArgs) -> Result<Position> {
+    let i18n = get_i18n();
+    let line_count = text.lines().count();
+    let line_text = (args.line as usize)
+        .checked_sub(1)
+        .and_then(|index| text.lines().nth(index))
+        .ok_or_else(|| {
+            anyhow!(i18n
+                .get("lsp_line_out_of_range")
+                .replacen("{}", &args.line.to_string(), 1)
+                .replacen("{}", &line_count.to_string(), 1))
+        })?;
+
+    let char_index = if let Some(symbol) = args.symbol.as_deref().filter(|s| !s.is_empty()) {
+        let byte_index = line_text.find(symbol).ok_or_else(|| {
+            anyhow!(i18n
+                .get("lsp_symbol_not_found")
+                .replacen("{}", symbol, 1)
+                .replacen("{}", &args.line.to_string(), 1))
+        })?;
+        line_text[..byte_index].chars().count()
+    } else if let Some(column) = args.column {
+        column.saturating_sub(1) as usize
+    } else {
+        line_text.chars().take_while(|c| c.is_whitespace()).count()
+    };
+
+    // LSP columns count UTF-16 code units
+    let character = line_text.chars().take(char_index).map(char::len_utf16).sum::<usize>();
+    Ok(Position::new(args.line - 1, character as u32))
+}
+
+/// Read the file, resolve the position and get its server
+async fn prepare(path: &Path, args: &LspPositionArgs) -> Result<(Arc<LspClient>, Position)> {
+    let text = tokio::fs::read_to_string(path).await?;
+    let position = resolve_position(&text, args)?;
+    Ok((client_for(path).await?, position))
+}
+
+/// Path relative to the working directory when it is inside it
+fn display_path(path: &Path, working_dir: &Path) -> String {
+    path.strip_prefix(working_dir).unwrap_or(path).display().to_string()
+}
+
+/// One `path:line:column: source line` entry per location, 1-indexed
+fn format_locations(locations: &[Location], working_dir: &Path) -> String {
+    let mut files: HashMap<PathBuf, Vec<String>> = HashMap::new();
+
+    locations
+        .iter()
+        .map(|location| {
+            let Ok(path) = location.uri.to_file_path() else {
+                return location.uri.to_string();
+            };
+            let lines = files.entry(path.clone()).or_insert_with(|| {
+                std::fs::read_to_string(&path)
+                    .map(|t| t.lines().map(str::to_string).collect())
+                    .unwrap_or_default()
+            });
+            let start = location.range.start;
+            let line_text = lines.get(start.line as usize).map(String::as_str).unwrap_or("");
+            format!(
+                "{}:{}:{}: {}",
+                display_path(&path, working_dir),
+                start.line + 1,
+                utf16_to_column(line_text, start.character),
+                line_text.trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 1-indexed character column of a UTF-16 offset
+fn utf16_to_column(line_text: &str, offset: u32) -> usize {
+    let mut units = 0;
+    let mut column = 1;
+    for c in line_text.chars() {
+        if units >= offset as usize {
+            break;
+        }
+        units += c.len_utf16();
+        column += 1;
+    }
+    column
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: u32, column: Option<u32>, symbol: Option<&str>) -> LspPositionArgs {
+        LspPositionArgs {
+            path: "a.rs".to_string(),
+            line,
+            column,
+            symbol: symbol.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_resolve_position() {
+        let text = "fn main() {\n    let café = load();\n}\n";
+        assert_eq!(resolve_position(text, &args(2, None, None)).unwrap(), Position::new(1, 4));
+        assert_eq!(resolve_position(text, &args(2, Some(9), None)).unwrap(), Position::new(1, 8));
+        // é is a single UTF-16 unit
+        assert_eq!(resolve_position(text, &args(2, None, Some("load"))).unwrap(), Position::new(1, 15));
+        assert!(resolve_position(text, &args(2, None, Some("missing"))).is_err());
+        assert!(resolve_position(text, &args(9, None, None)).is_err());
+
+        let wide = "let 🦀 = crab;";
+        // The crab takes two UTF-16 units
+        assert_eq!(resolve_position(wide, &args(1, None, Some("crab"))).unwrap(), Position::new(0, 9));
+        assert_eq!(utf16_to_column(wide, 9), 9);
+    }
+}
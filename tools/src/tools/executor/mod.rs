@@ -2,17 +2,26 @@ use anyhow::Result;
 use std::path::Path;
 use serde_json::Value;
 
+use crate::permissions::{self, Verdict};
+use crate::plan_mode;
 use crate::tools::types::ToolResult;
+use config::Permission;
 use ui::get_i18n;
 
 mod command_operations;
 pub mod file_operations;
+mod git_operations;
+mod lsp_operations;
 pub mod network_operations;
 pub mod search_operations;
 mod utils;
 pub mod parser;
 mod todo_operations;
 pub mod lsp_client;
+mod read_tracker;
+mod sandbox;
+mod shell_session;
+mod verification;
 
 pub async fn execute_tool(
     name: &str,
@@ -31,10 +40,69 @@ pub async fn execute_tool_with_mcp(
     require_approval: bool,
     session_id: Option<&str>,
     mcp_integration: Option<&mcp::McpIntegration>,
+) -> Result<ToolResult> {
+    let i18n = get_i18n();
+    if plan_mode::is_plan_mode() && !plan_mode::is_allowed(name, mcp_integration).await {
+        return Ok(ToolResult::error(i18n.get("plan_mode_blocked").replace("{}", name)));
+    }
+
+    if let Err(e) = permissions::check_policy(working_dir) {
+        return Ok(ToolResult::error(
+            i18n.get("permission_policy_tampered").replace("{}", &format!("{:#}", e)),
+        ));
+    }
+
+    // A persistent shell runs wherever it last `cd`-ed to
+    let command_dir = if name == "shell" {
+        shell_session::current_dir(session_id, working_dir)
+    } else {
+        Some(working_dir.to_path_buf())
+    };
+    let verdict = match permissions::evaluate(name, arguments, working_dir, command_dir.as_deref()) {
+        Ok(verdict) => verdict,
+        Err(e) => {
+            return Ok(ToolResult::error(
+                i18n.get("permission_policy_invalid").replace("{}", &format!("{:#}", e)),
+            ))
+        }
+    };
+
+    // Rules override the session's approval mode in both directions
+    let permission = verdict.as_ref().map(|v| v.permission);
+    let require_approval = match verdict {
+        Some(Verdict { permission: Permission::Deny, rule }) => {
+            return Ok(ToolResult::error(i18n.get("permission_denied").replace("{}", &rule)))
+        }
+        Some(Verdict { permission: Permission::Ask, .. }) => true,
+        Some(Verdict { permission: Permission::Allow, .. }) => false,
+        None => require_approval,
+    };
+
+    permissions::scoped(
+        working_dir,
+        permission,
+        dispatch_tool(name, arguments, working_dir, require_approval, session_id, mcp_integration),
+    )
+    .await
+}
+
+async fn dispatch_tool(
+    name: &str,
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+    session_id: Option<&str>,
+    mcp_integration: Option<&mcp::McpIntegration>,
 ) -> Result<ToolResult> {
     match name {
         "file_list" => file_operations::execute_file_list(arguments, working_dir).await,
-        "file_read" => file_operations::execute_file_read(arguments, working_dir).await,
+        "file_read" => {
+            let result = file_operations::execute_file_read(arguments, working_dir).await?;
+            if result.success {
+                read_tracker::record_read(session_id, arguments, working_dir);
+            }
+            Ok(result)
+        }
         "file_search" => file_operations::execute_file_search(arguments, working_dir).await,
         "file_outline" => file_operations::execute_file_outline(arguments, working_dir).await,
         "file_search_by_outline" => {
@@ -42,13 +110,32 @@ pub async fn execute_tool_with_mcp(
         }
         "index_file" => search_operations::execute_index_file(arguments, working_dir).await,
         "file_write" => {
-            file_operations::execute_file_write(arguments, working_dir, require_approval).await
+            let edit = verification::verified_edit(
+                arguments,
+                working_dir,
+                file_operations::execute_file_write(arguments, working_dir, require_approval),
+            );
+            read_tracker::guarded_edit(name, arguments, working_dir, session_id, edit).await
         }
         "file_replace" => {
-            file_operations::execute_file_replace(arguments, working_dir, require_approval).await
+            let edit = verification::verified_edit(
+                arguments,
+                working_dir,
+                file_operations::execute_file_replace(arguments, working_dir, require_approval),
+            );
+            read_tracker::guarded_edit(name, arguments, working_dir, session_id, edit).await
         }
         "file_diff_edit" => {
-            file_operations::execute_file_diff_edit(arguments, working_dir, require_approval).await
+            let edit = verification::verified_edit(
+                arguments,
+                working_dir,
+                file_operations::execute_file_diff_edit(arguments, working_dir, require_approval),
+            );
+            read_tracker::guarded_edit(name, arguments, working_dir, session_id, edit).await
+        }
+        "apply_patch" => {
+            let edit = file_operations::execute_apply_patch(arguments, working_dir, require_approval);
+            read_tracker::guarded_edit(name, arguments, working_dir, session_id, edit).await
         }
         "network_search_auto" => search_operations::execute_search_auto(arguments).await,
         "network_search_duckduckgo" => {
@@ -56,7 +143,29 @@ pub async fn execute_tool_with_mcp(
         }
         "network_search_bing" => search_operations::execute_search_bing(arguments).await,
         "network_get_content" => network_operations::execute_fetch_content(arguments).await,
-        "run_command" => command_operations::execute_run_command(arguments, require_approval).await,
+        "run_command" => command_operations::execute_run_command(arguments, working_dir, require_approval).await,
+        "shell" => shell_session::execute_shell(arguments, working_dir, require_approval, session_id).await,
+        "command_status" => command_operations::execute_command_status(arguments).await,
+        "command_output" => command_operations::execute_command_output(arguments).await,
+        "command_kill" => command_operations::execute_command_kill(arguments).await,
+        "git_status" => git_operations::execute_git_status(working_dir).await,
+        "git_diff" => git_operations::execute_git_diff(arguments, working_dir).await,
+        "git_log" => git_operations::execute_git_log(arguments, working_dir).await,
+        "git_blame" => git_operations::execute_git_blame(arguments, working_dir).await,
+        "git_show" => git_operations::execute_git_show(arguments, working_dir).await,
+        "git_commit" => {
+            git_operations::execute_git_commit(arguments, working_dir, require_approval).await
+        }
+        "git_checkout" => {
+            git_operations::execute_git_checkout(arguments, working_dir, require_approval).await
+        }
+        "lsp_definition" => lsp_operations::execute_lsp_definition(arguments, working_dir).await,
+        "lsp_references" => lsp_operations::execute_lsp_references(arguments, working_dir).await,
+        "lsp_hover" => lsp_operations::execute_lsp_hover(arguments, working_dir).await,
+        "lsp_workspace_symbols" => {
+            lsp_operations::execute_lsp_workspace_symbols(arguments, working_dir).await
+        }
+        "lsp_diagnostics" => lsp_operations::execute_lsp_diagnostics(arguments, working_dir).await,
         "todo_write" => todo_operations::execute_todo_write(arguments, working_dir, session_id).await,
         "todo_read" => todo_operations::execute_todo_read(arguments, working_dir, session_id).await,
         "mcp_resource_list" => {
@@ -0,0 +1,234 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use super::file_operations::apply_patch_paths;
+use super::file_operations::file_common::normalize_path;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+/// Lines of the current content shown when an edit is rejected
+const EXCERPT_BEFORE: usize = 10;
+const EXCERPT_LINES: usize = 40;
+
+/// Coarsest mtime resolution we expect from a filesystem
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+/// Last known state of each file per session, from a read or the session's own edit
+static STAMPS: Mutex<Option<HashMap<(String, PathBuf), FileStamp>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+    taken: SystemTime,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let content = fs::read(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            hash: hash_bytes(&content),
+            taken: SystemTime::now(),
+        })
+    }
+
+    /// Whether the file still has the content this stamp was taken from
+    ///
+    /// An unchanged mtime skips hashing only when it is older than the stamp by more than
+    /// the mtime resolution, otherwise a write in the same tick would go unnoticed. A
+    /// touched but unchanged file still matches.
+    fn matches(&self, path: &Path) -> bool {
+        let Ok(metadata) = fs::metadata(path) else {
+            return false;
+        };
+        if metadata.len() != self.len {
+            return false;
+        }
+        let settled = self
+            .modified
+            .is_some_and(|m| m + MTIME_GRANULARITY < self.taken);
+        if settled && metadata.modified().ok() == self.modified {
+            return true;
+        }
+        fs::read(path).is_ok_and(|content| hash_bytes(&content) == self.hash)
+    }
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn record(session: &str, path: &Path) {
+    let Some(stamp) = FileStamp::of(path) else {
+        return;
+    };
+    STAMPS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert((session.to_string(), path.to_path_buf()), stamp);
+}
+
+fn stamp_for(session: &str, path: &Path) -> Option<FileStamp> {
+    STAMPS
+        .lock()
+        .unwrap()
+        .as_ref()?
+        .get(&(session.to_string(), path.to_path_buf()))
+        .cloned()
+}
+
+/// Remember what `file_read` saw so later edits can tell if the file changed since
+pub fn record_read(session_id: Option<&str>, arguments: &str, working_dir: &Path) {
+    let Some(session) = session_id else {
+        return;
+    };
+    let Some(path) = serde_json::from_str::<Value>(arguments)
+        .ok()
+        .and_then(|args| args.get("path")?.as_str().map(str::to_string))
+    else {
+        return;
+    };
+    if path.starts_with("mcp://") {
+        return;
+    }
+    record(session, &normalize_path(&path, working_dir));
+}
+
+/// Run an edit tool only if every existing file it touches was read in this session and
+/// hasn't changed since
+///
+/// Files that changed underneath get a fresh excerpt in the rejection and count as read
+/// again, so the model can retry with the current content. Outside a session (MCP
+/// server) edits are not checked.
+pub async fn guarded_edit(
+    tool: &str,
+    arguments: &str,
+    working_dir: &Path,
+    session_id: Option<&str>,
+    edit: impl Future<Output = Result<ToolResult>>,
+) -> Result<ToolResult> {
+    let Some(session) = session_id else {
+        return edit.await;
+    };
+    let args: Value = serde_json::from_str(arguments).unwrap_or(Value::Null);
+    let paths = edited_paths(tool, &args, working_dir);
+    let i18n = get_i18n();
+
+    for path in paths.iter().filter(|p| p.is_file()) {
+        let shown = path.strip_prefix(working_dir).unwrap_or(path).display().to_string();
+        match stamp_for(session, path) {
+            None => {
+                return Ok(ToolResult::error(
+                    i18n.get("stale_edit_unread").replace("{}", &shown),
+                ))
+            }
+            Some(stamp) if !stamp.matches(path) => {
+                record(session, path);
+                let content = fs::read_to_string(path).unwrap_or_default();
+                let message = format!(
+                    "{}\n{}",
+                    i18n.get("stale_edit_changed").replace("{}", &shown),
+                    excerpt(&content, focus_line(tool, &args, &content))
+                );
+                let mut result = ToolResult::error(message);
+                result.brief = i18n.get("stale_edit_brief").replace("{}", &shown);
+                return Ok(result);
+            }
+            Some(_) => {}
+        }
+    }
+
+    let result = edit.await?;
+    if result.success {
+        // The session's own edits are known content
+        for path in &paths {
+            record(session, path);
+        }
+    }
+    Ok(result)
+}
+
+fn edited_paths(tool: &str, args: &Value, working_dir: &Path) -> Vec<PathBuf> {
+    let paths = if tool == "apply_patch" {
+        apply_patch_paths(args.get("patch").and_then(Value::as_str).unwrap_or(""))
+    } else {
+        args.get("path")
+            .and_then(Value::as_str)
+            .map(|p| vec![p.to_string()])
+            .unwrap_or_default()
+    };
+    paths.iter().map(|p| normalize_path(p, working_dir)).collect()
+}
+
+/// 1-indexed line the edit was aimed at, where the excerpt is centered
+fn focus_line(tool: &str, args: &Value, content: &str) -> usize {
+    match tool {
+        "file_diff_edit" => args["hunks"]
+            .as_array()
+            .and_then(|hunks| hunks.iter().filter_map(|h| h["start_line"].as_u64()).min())
+            .unwrap_or(1) as usize,
+        "file_replace" => args["edits"][0]["old"]
+            .as_str()
+            .and_then(|old| old.lines().find(|l| !l.trim().is_empty()))
+            .and_then(|first| content.lines().position(|l| l.contains(first.trim())))
+            .map_or(1, |index| index + 1),
+        _ => 1,
+    }
+}
+
+/// Current lines around `focus`, in the same form as a ranged `file_read`
+fn excerpt(content: &str, focus: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return "(Lines 0-0)".to_string();
+    }
+    let start = focus.saturating_sub(EXCERPT_BEFORE).max(1).min(lines.len());
+    let end = (start + EXCERPT_LINES - 1).min(lines.len());
+    format!("(Lines {}-{})\n{}", start, end, lines[start - 1..end].join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excerpt_centers_on_focus() {
+        let content = (1..=100).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let text = excerpt(&content, 50);
+        assert!(text.starts_with("(Lines 40-79)\nline 40\n"));
+        assert!(text.ends_with("line 79"));
+        assert!(excerpt("a\nb", 9).starts_with("(Lines 1-2)"));
+    }
+
+    #[test]
+    fn test_stamp_detects_changes() {
+        let dir = std::env::temp_dir().join(format!("friendev-rt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.txt");
+        fs::write(&path, "one").unwrap();
+        let stamp = FileStamp::of(&path).unwrap();
+        assert!(stamp.matches(&path));
+
+        fs::write(&path, "two").unwrap();
+        assert!(!stamp.matches(&path));
+        fs::write(&path, "one").unwrap();
+        assert!(stamp.matches(&path));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
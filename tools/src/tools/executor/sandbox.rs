@@ -0,0 +1,312 @@
+use anyhow::{bail, Result};
+use config::SandboxConfig;
+use crate::permissions;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Device files commands commonly write to, kept writable inside the sandbox
+const WRITABLE_DEVICES: &[&str] = &["/dev/null", "/dev/zero", "/dev/tty"];
+
+/// Restrictions for one `run_command` call, from the project's `.friendev/sandbox.json`
+pub struct Sandbox {
+    config: SandboxConfig,
+    writable: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    /// The project sandbox, `None` when it is not enabled
+    pub fn load(working_dir: &Path) -> Result<Option<Self>> {
+        let config = SandboxConfig::load(working_dir)?;
+        if !config.enabled {
+            return Ok(None);
+        }
+        // The workspace is writable, so commands could rewrite the policy that confines them
+        permissions::protect_policy(working_dir);
+
+        let mut writable = vec![working_dir.to_path_buf(), std::env::temp_dir()];
+        for path in &config.writable_paths {
+            let path = match path.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+                None => working_dir.join(path),
+            };
+            writable.push(path);
+        }
+        Ok(Some(Self { config, writable }))
+    }
+
+    /// Wall clock limit of the command
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        self.config.timeout_secs.map(std::time::Duration::from_secs)
+    }
+
+    /// One line summary shown in the approval prompt
+    pub fn describe(&self) -> String {
+        let writable: Vec<String> = self.writable.iter().map(|p| p.display().to_string()).collect();
+        let mut parts = vec![
+            format!("writes: {}", writable.join(", ")),
+            format!("network: {}", if self.config.network { "on" } else { "off" }),
+        ];
+        if let Some(secs) = self.config.cpu_secs {
+            parts.push(format!("cpu: {}s", secs));
+        }
+        if let Some(mb) = self.config.memory_mb {
+            parts.push(format!("memory: {} MB", mb));
+        }
+        if let Some(secs) = self.config.timeout_secs {
+            parts.push(format!("timeout: {}s", secs));
+        }
+        format!("landlock ({})", parts.join("; "))
+    }
+
+    /// Restrict the command when it is spawned, keep the returned guard until then
+    #[cfg(target_os = "linux")]
+    pub fn install(&self, command: &mut Command) -> Result<landlock::Ruleset> {
+        let ruleset = landlock::Ruleset::create(&self.writable, !self.config.network)?;
+        let fd = ruleset.fd();
+        let net_by_landlock = ruleset.handles_network();
+        let deny_network = !self.config.network;
+        let cpu_secs = self.config.cpu_secs;
+        let memory_bytes = self.config.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+
+        // SAFETY: the closure runs between fork and exec and only makes syscalls
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(secs) = cpu_secs {
+                    landlock::set_limit(libc::RLIMIT_CPU, secs)?;
+                }
+                if let Some(bytes) = memory_bytes {
+                    landlock::set_limit(libc::RLIMIT_AS, bytes)?;
+                }
+                // A private network namespace has only a loopback that is down,
+                // landlock can still block TCP where user namespaces are disabled
+                if deny_network
+                    && libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0
+                    && !net_by_landlock
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                landlock::restrict_self(fd)
+            });
+        }
+        Ok(ruleset)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn install(&self, _command: &mut Command) -> Result<()> {
+        bail!("the sandbox needs Linux with landlock")
+    }
+}
+
+/// Raw landlock syscalls, the kernel ABI is stable and small enough to not need a crate
+#[cfg(target_os = "linux")]
+pub mod landlock {
+    use super::*;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::os::unix::ffi::OsStrExt;
+
+    const CREATE_RULESET_VERSION: u32 = 1;
+    const RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+    const ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+    const ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+    const ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+    const ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+    const ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+    const ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+    const ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+    const ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+    const ACCESS_NET_BIND_TCP: u64 = 1 << 0;
+    const ACCESS_NET_CONNECT_TCP: u64 = 1 << 1;
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+        handled_access_net: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    /// A ruleset allowing writes only below the given paths
+    pub struct Ruleset {
+        fd: OwnedFd,
+        handles_network: bool,
+    }
+
+    impl Ruleset {
+        pub fn create(writable: &[PathBuf], deny_network: bool) -> Result<Self> {
+            let abi = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_create_ruleset,
+                    std::ptr::null::<RulesetAttr>(),
+                    0usize,
+                    CREATE_RULESET_VERSION,
+                )
+            };
+            if abi < 1 {
+                bail!("landlock is not supported: {}", io::Error::last_os_error());
+            }
+
+            let mut handled_fs = ACCESS_FS_WRITE_FILE
+                | ACCESS_FS_REMOVE_DIR
+                | ACCESS_FS_REMOVE_FILE
+                | ACCESS_FS_MAKE_CHAR
+                | ACCESS_FS_MAKE_DIR
+                | ACCESS_FS_MAKE_REG
+                | ACCESS_FS_MAKE_SOCK
+                | ACCESS_FS_MAKE_FIFO
+                | ACCESS_FS_MAKE_BLOCK
+                | ACCESS_FS_MAKE_SYM;
+            if abi >= 2 {
+                handled_fs |= ACCESS_FS_REFER;
+            }
+            if abi >= 3 {
+                handled_fs |= ACCESS_FS_TRUNCATE;
+            }
+            let handles_network = deny_network && abi >= 4;
+            let attr = RulesetAttr {
+                handled_access_fs: handled_fs,
+                handled_access_net: if handles_network {
+                    ACCESS_NET_BIND_TCP | ACCESS_NET_CONNECT_TCP
+                } else {
+                    0
+                },
+            };
+            let fd = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_create_ruleset,
+                    &attr as *const RulesetAttr,
+                    std::mem::size_of::<RulesetAttr>(),
+                    0u32,
+                )
+            };
+            if fd < 0 {
+                bail!("landlock_create_ruleset failed: {}", io::Error::last_os_error());
+            }
+            let ruleset = Self {
+                fd: unsafe { OwnedFd::from_raw_fd(fd as RawFd) },
+                handles_network,
+            };
+
+            // Files only accept file rights
+            let file_access = handled_fs & (ACCESS_FS_WRITE_FILE | ACCESS_FS_TRUNCATE);
+            let devices = WRITABLE_DEVICES.iter().map(PathBuf::from);
+            for path in writable.iter().cloned().chain(devices) {
+                match std::fs::metadata(&path) {
+                    Ok(meta) if meta.is_dir() => ruleset.allow(&path, handled_fs)?,
+                    Ok(_) => ruleset.allow(&path, file_access)?,
+                    // Paths that don't exist yet can't be granted, and nothing can be written there
+                    Err(_) => {}
+                }
+            }
+            Ok(ruleset)
+        }
+
+        fn allow(&self, path: &Path, access: u64) -> Result<()> {
+            let c_path = CString::new(path.as_os_str().as_bytes())?;
+            let parent = unsafe { libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+            if parent < 0 {
+                bail!("cannot open {}: {}", path.display(), io::Error::last_os_error());
+            }
+            let parent = unsafe { OwnedFd::from_raw_fd(parent) };
+            let attr = PathBeneathAttr {
+                allowed_access: access,
+                parent_fd: parent.as_raw_fd(),
+            };
+            let result = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_add_rule,
+                    self.fd.as_raw_fd(),
+                    RULE_PATH_BENEATH,
+                    &attr as *const PathBeneathAttr,
+                    0u32,
+                )
+            };
+            if result != 0 {
+                bail!("landlock_add_rule for {} failed: {}", path.display(), io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub fn fd(&self) -> RawFd {
+            self.fd.as_raw_fd()
+        }
+
+        /// TCP is blocked by the ruleset itself
+        pub fn handles_network(&self) -> bool {
+            self.handles_network
+        }
+    }
+
+    /// Enforce the ruleset on the calling process, for use between fork and exec
+    pub fn restrict_self(fd: RawFd) -> io::Result<()> {
+        unsafe {
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::syscall(libc::SYS_landlock_restrict_self, fd, 0u32) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Type of `setrlimit`'s resource argument, which differs between C libraries
+    #[cfg(target_env = "gnu")]
+    pub type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(target_env = "gnu"))]
+    pub type Resource = libc::c_int;
+
+    /// Set both the soft and hard limit of a resource
+    pub fn set_limit(resource: Resource, value: u64) -> io::Result<()> {
+        let limit = libc::rlimit {
+            rlim_cur: value as libc::rlim_t,
+            rlim_max: value as libc::rlim_t,
+        };
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writes_limited_to_working_dir() {
+        let root = std::env::temp_dir().join(format!("friendev-sb-{}", std::process::id()));
+        let work = root.join("work");
+        std::fs::create_dir_all(&work).unwrap();
+        let sandbox = Sandbox {
+            config: SandboxConfig { enabled: true, ..Default::default() },
+            writable: vec![work.clone()],
+        };
+
+        let script = format!("echo ok > {0}/in.txt && echo bad > {1}/out.txt", work.display(), root.display());
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&script);
+        let ruleset = match sandbox.install(&mut command) {
+            Ok(ruleset) => ruleset,
+            // Kernels without landlock can't run this test
+            Err(_) => return,
+        };
+        let output = command.output().await.unwrap();
+        drop(ruleset);
+
+        assert!(!output.status.success());
+        assert!(work.join("in.txt").exists());
+        assert!(!root.join("out.txt").exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
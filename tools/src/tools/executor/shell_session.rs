@@ -0,0 +1,400 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+use super::command_operations::check_command_approval;
+use super::sandbox::Sandbox;
+use crate::tools::args::ShellArgs;
+use crate::tools::background;
+use crate::tools::command_manager::CommandConfig;
+use crate::tools::types::ToolResult;
+use ui::get_i18n;
+
+/// Default and largest per-command time limit in seconds
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+const MAX_TIMEOUT_SECS: u64 = 600;
+
+/// Output longer than this keeps its head and tail
+const MAX_OUTPUT_CHARS: usize = 30_000;
+
+/// Shell of each chat session, kept alive between tool calls
+static SHELLS: Mutex<Option<HashMap<String, Arc<tokio::sync::Mutex<ShellSession>>>>> = Mutex::new(None);
+
+/// A long-lived shell reading commands from a pipe
+struct ShellSession {
+    key: String,
+    /// Directory the next command starts in, `None` when the last command didn't report it
+    cwd: Option<PathBuf>,
+    /// Sandbox the shell was started under, as shown in approval prompts
+    sandbox: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+}
+
+enum Outcome {
+    Finished { output: String, exit_code: i32, cwd: String },
+    TimedOut { output: String },
+    Exited { output: String },
+}
+
+pub async fn execute_shell(
+    arguments: &str,
+    working_dir: &Path,
+    require_approval: bool,
+    session_id: Option<&str>,
+) -> Result<ToolResult> {
+    let args: ShellArgs = serde_json::from_str(arguments)?;
+    let i18n = get_i18n();
+    let key = session_id.unwrap_or("default").to_string();
+
+    if args.reset {
+        close(&key).await;
+        if args.command.trim().is_empty() {
+            let brief = i18n.get("shell_reset");
+            return Ok(ToolResult::ok(brief.clone(), brief));
+        }
+    }
+    if cfg!(windows) {
+        return Ok(ToolResult::error(i18n.get("shell_unsupported")));
+    }
+
+    let sandbox = match Sandbox::load(working_dir) {
+        Ok(sandbox) => sandbox,
+        Err(e) => return Ok(ToolResult::error(i18n.get("sandbox_config_invalid").replace("{}", &e.to_string()))),
+    };
+    let sandbox_text = describe(sandbox.as_ref());
+
+    let config = CommandConfig::load()?;
+    if let Some(rejected) = check_command_approval(
+        "shell",
+        "Shell",
+        &args.command,
+        "persistent shell",
+        &sandbox_text,
+        &config,
+        require_approval,
+    ).await? {
+        return Ok(rejected);
+    }
+
+    // An incomplete command would leave the shell waiting for the rest of it
+    if let Some(error) = syntax_error(&args.command).await {
+        return Ok(ToolResult::error(i18n.get("shell_syntax_error").replace("{}", &error)));
+    }
+
+    let mut limit = args.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS);
+    if let Some(sandbox_limit) = sandbox.as_ref().and_then(Sandbox::timeout) {
+        limit = limit.min(sandbox_limit.as_secs().max(1));
+    }
+
+    let (session, restarted) = match open_sandboxed(&key, working_dir, sandbox.as_ref()).await {
+        Ok(opened) => opened,
+        Err(e) => return Ok(ToolResult::error(i18n.get("shell_start_failed").replace("{}", &e.to_string()))),
+    };
+    let outcome = session.lock().await.run(&args.command, Duration::from_secs(limit)).await;
+    drop(session);
+    let outcome = outcome?;
+
+    Ok(match outcome {
+        Outcome::Finished { output, exit_code, cwd } => {
+            let brief = i18n
+                .get("shell_brief")
+                .replacen("{}", &exit_code.to_string(), 1)
+                .replacen("{}", &cwd, 1);
+            let header = i18n
+                .get("shell_output_header")
+                .replacen("{}", &exit_code.to_string(), 1)
+                .replacen("{}", &cwd, 1);
+            let header = if restarted {
+                format!("{}\n\n{}", i18n.get("shell_sandbox_restarted"), header)
+            } else {
+                header
+            };
+            ToolResult::ok(brief, format!("{}\n\n{}", header, truncate_output(&output)))
+        }
+        Outcome::TimedOut { output } => {
+            close(&key).await;
+            let message = i18n.get("shell_timed_out").replace("{}", &limit.to_string());
+            ToolResult::error(with_output(message, &output))
+        }
+        Outcome::Exited { output } => {
+            close(&key).await;
+            ToolResult::error(with_output(i18n.get("shell_exited"), &output))
+        }
+    })
+}
+
+/// The session's shell, started in the working directory if it isn't running
+fn open(key: &str, working_dir: &Path, sandbox: Option<&Sandbox>) -> Result<Arc<tokio::sync::Mutex<ShellSession>>> {
+    let mut guard = SHELLS.lock().unwrap();
+    let shells = guard.get_or_insert_with(HashMap::new);
+    if let Some(session) = shells.get(key) {
+        return Ok(session.clone());
+    }
+    let session = Arc::new(tokio::sync::Mutex::new(ShellSession::spawn(key, working_dir, sandbox)?));
+    shells.insert(key.to_string(), session.clone());
+    Ok(session)
+}
+
+/// The session's shell, replaced by a fresh one when it was started under other sandbox
+/// settings, which were fixed when it started. Also returns whether it was replaced.
+async fn open_sandboxed(
+    key: &str,
+    working_dir: &Path,
+    sandbox: Option<&Sandbox>,
+) -> Result<(Arc<tokio::sync::Mutex<ShellSession>>, bool)> {
+    let session = open(key, working_dir, sandbox)?;
+    if session.lock().await.sandbox == describe(sandbox) {
+        return Ok((session, false));
+    }
+    close_if(key, |current| Arc::ptr_eq(current, &session)).await;
+    Ok((open(key, working_dir, sandbox)?, true))
+}
+
+/// Sandbox settings as shown in approval prompts, `off` without one
+fn describe(sandbox: Option<&Sandbox>) -> String {
+    sandbox.map(Sandbox::describe).unwrap_or_else(|| "off".to_string())
+}
+
+/// Directory the session's next shell command starts in
+///
+/// A fresh shell starts in the working directory. `None` when it can't be known, while
+/// another command is running or when the last one didn't report where it ended up.
+pub fn current_dir(session_id: Option<&str>, working_dir: &Path) -> Option<PathBuf> {
+    let key = session_id.unwrap_or("default");
+    let session = SHELLS.lock().unwrap().as_ref().and_then(|s| s.get(key).cloned());
+    match session {
+        Some(session) => session.try_lock().ok()?.cwd.clone(),
+        None => Some(working_dir.to_path_buf()),
+    }
+}
+
+/// Stop the session's shell, the next command starts a fresh one
+async fn close(key: &str) {
+    close_if(key, |_| true).await;
+}
+
+/// Stop the session's shell unless `stale` rejects it, another call may have replaced
+/// the stale shell already
+async fn close_if(key: &str, stale: impl Fn(&Arc<tokio::sync::Mutex<ShellSession>>) -> bool) {
+    let session = {
+        let mut guard = SHELLS.lock().unwrap();
+        let shells = guard.get_or_insert_with(HashMap::new);
+        match shells.get(key) {
+            Some(current) if !stale(current) => return,
+            _ => shells.remove(key),
+        }
+    };
+    drop(session);
+    // Also stops what the shell started in the background, which may wait for it to exit
+    let id = process_id(key);
+    let _ = tokio::task::spawn_blocking(move || background::kill_registered(&id)).await;
+}
+
+/// Id the shell is registered under so it is killed on exit
+fn process_id(key: &str) -> String {
+    format!("shell:{}", key)
+}
+
+impl ShellSession {
+    fn spawn(key: &str, working_dir: &Path, sandbox: Option<&Sandbox>) -> Result<Self> {
+        let program = if which_bash() { "bash" } else { "sh" };
+        let mut cmd = Command::new(program);
+        cmd.current_dir(working_dir)
+            .env("TERM", "dumb")
+            .env("PAGER", "cat")
+            .env("GIT_PAGER", "cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let ruleset = match sandbox {
+            Some(sandbox) => Some(sandbox.install(&mut cmd)?),
+            None => None,
+        };
+        let mut child = cmd.spawn()?;
+        drop(ruleset);
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            bail!("shell pipes unavailable");
+        };
+        if let Some(pid) = child.id() {
+            background::register(&process_id(key), pid);
+        }
+        Ok(Self {
+            key: key.to_string(),
+            cwd: Some(working_dir.to_path_buf()),
+            sandbox: describe(sandbox),
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Run one command in the shell and wait for its end marker
+    async fn run(&mut self, command: &str, limit: Duration) -> Result<Outcome> {
+        let marker = format!("__FRIENDEV_DONE_{}__", uuid::Uuid::new_v4().simple());
+        // Braces keep `cd` and `export` in this shell, stdin is closed so the command
+        // can't swallow the marker line
+        let script = format!(
+            "exec 2>&1\n{{ {}\n}} < /dev/null\n__friendev_rc=$?; printf '%s%d %s\\n' '{}' \"$__friendev_rc\" \"$PWD\"\n",
+            command, marker
+        );
+        if self.stdin.write_all(script.as_bytes()).await.is_err() {
+            return Ok(Outcome::Exited { output: String::new() });
+        }
+        self.stdin.flush().await?;
+
+        let mut buffer = Vec::new();
+        let read = tokio::time::timeout(limit, async {
+            let mut chunk = [0u8; 8192];
+            loop {
+                if let Some(end) = find_marker(&buffer, marker.as_bytes()) {
+                    return Ok::<_, std::io::Error>(Some(end));
+                }
+                let n = self.stdout.read(&mut chunk).await?;
+                if n == 0 {
+                    return Ok(None);
+                }
+                buffer.extend_from_slice(&chunk[..n]);
+            }
+        })
+        .await;
+
+        let outcome = match read {
+            Err(_) => Outcome::TimedOut {
+                output: String::from_utf8_lossy(&buffer).to_string(),
+            },
+            Ok(Err(e)) => return Err(e.into()),
+            Ok(Ok(None)) => Outcome::Exited {
+                output: String::from_utf8_lossy(&buffer).to_string(),
+            },
+            Ok(Ok(Some((start, end)))) => {
+                let status = String::from_utf8_lossy(&buffer[start + marker.len()..end]).to_string();
+                let (code, cwd) = status.split_once(' ').unwrap_or((status.as_str(), ""));
+                Outcome::Finished {
+                    output: String::from_utf8_lossy(&buffer[..start]).to_string(),
+                    exit_code: code.trim().parse().unwrap_or(-1),
+                    cwd: cwd.trim_end().to_string(),
+                }
+            }
+        };
+        self.cwd = match &outcome {
+            Outcome::Finished { cwd, .. } if !cwd.is_empty() => Some(PathBuf::from(cwd)),
+            _ => None,
+        };
+        if matches!(outcome, Outcome::Exited { .. }) {
+            let _ = self.child.try_wait();
+            background::unregister(&process_id(&self.key));
+        }
+        Ok(outcome)
+    }
+}
+
+/// Start and end of the marker line once it has been read completely
+fn find_marker(buffer: &[u8], marker: &[u8]) -> Option<(usize, usize)> {
+    let start = buffer.windows(marker.len()).position(|w| w == marker)?;
+    let end = buffer[start..].iter().position(|b| *b == b'\n')? + start;
+    Some((start, end))
+}
+
+/// Parse the command without running it, `bash -n` reports unclosed quotes and blocks
+async fn syntax_error(command: &str) -> Option<String> {
+    let program = if which_bash() { "bash" } else { "sh" };
+    let output = Command::new(program).arg("-n").arg("-c").arg(command).output().await.ok()?;
+    if output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stderr).trim().to_string())
+}
+
+fn which_bash() -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join("bash").is_file()))
+}
+
+/// Error message followed by whatever the command printed
+fn with_output(message: String, output: &str) -> String {
+    if output.trim().is_empty() {
+        message
+    } else {
+        format!("{}\n\n{}", message, truncate_output(output))
+    }
+}
+
+/// Keep the head and tail of long output
+fn truncate_output(output: &str) -> String {
+    let total = output.chars().count();
+    if total <= MAX_OUTPUT_CHARS {
+        return output.to_string();
+    }
+    let head: String = output.chars().take(MAX_OUTPUT_CHARS / 3).collect();
+    let tail: String = output.chars().skip(total - MAX_OUTPUT_CHARS * 2 / 3).collect();
+    format!(
+        "{}\n... [{} characters truncated] ...\n{}",
+        head,
+        total - head.chars().count() - tail.chars().count(),
+        tail
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_state_persists_between_commands() {
+        let dir = std::env::temp_dir().join(format!("friendev-sh-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let mut shell = ShellSession::spawn("test", &dir, None).unwrap();
+        let limit = Duration::from_secs(10);
+
+        shell.run("cd sub && export GREETING=hi", limit).await.unwrap();
+        match shell.run("echo $GREETING; echo oops >&2; false", limit).await.unwrap() {
+            Outcome::Finished { output, exit_code, cwd } => {
+                assert_eq!(output, "hi\noops\n");
+                assert_eq!(exit_code, 1);
+                assert!(cwd.ends_with("sub"));
+            }
+            _ => panic!("command did not finish"),
+        }
+        assert!(shell.cwd.as_ref().is_some_and(|cwd| cwd.ends_with("sub")));
+        assert!(matches!(
+            shell.run("sleep 5", Duration::from_millis(200)).await.unwrap(),
+            Outcome::TimedOut { .. }
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restarts_when_sandbox_changes() {
+        let dir = std::env::temp_dir();
+        let key = format!("sandbox-test-{}", std::process::id());
+        let (first, restarted) = open_sandboxed(&key, &dir, None).await.unwrap();
+        assert!(!restarted);
+        // As if the shell had been started before the sandbox was turned off
+        first.lock().await.sandbox = "landlock (network: off)".to_string();
+
+        let (second, restarted) = open_sandboxed(&key, &dir, None).await.unwrap();
+        assert!(restarted && !Arc::ptr_eq(&first, &second));
+        let (third, restarted) = open_sandboxed(&key, &dir, None).await.unwrap();
+        assert!(!restarted && Arc::ptr_eq(&second, &third));
+        close(&key).await;
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        let long = "x".repeat(MAX_OUTPUT_CHARS + 100);
+        let truncated = truncate_output(&long);
+        assert!(truncated.contains("[100 characters truncated]"));
+        assert_eq!(truncate_output("short"), "short");
+    }
+}
@@ -34,7 +34,7 @@ pub async fn execute_todo_write(
     fs::write(&todo_file, &json_content)?;
 
     // Format output and print to user
-    println!("\n\x1b[1m{}\x1b[0m", "Current Todo List:");
+    ui::output::println(&format!("\n\x1b[1m{}\x1b[0m", "Current Todo List:"));
     
     // Calculate progress
     let total = args.todos.len();
@@ -43,14 +43,14 @@ pub async fn execute_todo_write(
         let percentage = (completed as f64 / total as f64 * 100.0).round() as u8;
         let bars = (percentage / 5) as usize;
         let empty = 20 - bars;
-        println!("  Progress: [{}{}] {}% ({}/{})", 
+        ui::output::println(&format!("  Progress: [{}{}] {}% ({}/{})", 
             "=".repeat(bars).cyan(), 
             " ".repeat(empty), 
             percentage, 
             completed, 
             total
-        );
-        println!();
+        ));
+        ui::output::println("");
     }
 
     // Sort by priority (High > Medium > Low) then status
@@ -100,9 +100,9 @@ pub async fn execute_todo_write(
             todo.content.clone()
         };
 
-        println!("  {} {} {} (ID: {})", status_icon, priority_label, content, todo.id.dimmed());
+        ui::output::println(&format!("  {} {} {} (ID: {})", status_icon, priority_label, content, todo.id.dimmed()));
     }
-    println!();
+    ui::output::println("");
 
     let brief = format!("Updated {} todo items.", args.todos.len());
     
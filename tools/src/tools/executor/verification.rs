@@ -0,0 +1,440 @@
+use anyhow::{anyhow, Result};
+use config::{Config, VerificationConfig};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::file_operations::file_common::normalize_path;
+use super::lsp_operations::{client_for, get_lsp_command};
+use crate::tools::types::ToolResult;
+use lsp_types::DiagnosticSeverity;
+use ui::get_i18n;
+
+/// At most this many errors are sent back per edit
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// How long the language server may take to publish diagnostics
+const LSP_WAIT: Duration = Duration::from_secs(10);
+
+/// Errors that existed before the first checked edit, per checker and scope
+static BASELINES: Mutex<Option<HashMap<String, HashSet<String>>>> = Mutex::new(None);
+
+/// How an edited file gets checked
+enum Checker {
+    Lsp,
+    /// `cargo check` in the workspace root, or the directory of the nearest Cargo.toml
+    Cargo(PathBuf),
+    /// `tsc --noEmit` in the directory of the nearest tsconfig.json
+    Tsc(PathBuf),
+    /// Shell command run in the working directory, fails on a non-zero exit
+    Command(String),
+}
+
+/// An error reported by a checker
+struct Problem {
+    file: String,
+    line: u32,
+    column: u32,
+    message: String,
+}
+
+impl Problem {
+    /// Identity across runs, without the position since edits move lines
+    fn key(&self) -> String {
+        format!("{}\u{1f}{}", self.file, self.message)
+    }
+
+    fn display(&self) -> String {
+        if self.file.is_empty() {
+            self.message.clone()
+        } else {
+            format!("{}:{}:{}: {}", self.file, self.line, self.column, self.message)
+        }
+    }
+}
+
+/// Run an edit tool and check the file afterwards when verification is enabled
+///
+/// The first edit under a checker records the errors that already exist, only errors
+/// beyond those are attached to the result.
+pub async fn verified_edit(
+    arguments: &str,
+    working_dir: &Path,
+    edit: impl Future<Output = Result<ToolResult>>,
+) -> Result<ToolResult> {
+    let Some(check) = Check::for_edit(arguments, working_dir) else {
+        return edit.await;
+    };
+
+    check.ensure_baseline().await;
+    let mut result = edit.await?;
+    if result.success {
+        check.attach_new_errors(&mut result).await;
+    }
+    Ok(result)
+}
+
+struct Check {
+    checker: Checker,
+    path: PathBuf,
+    working_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl Check {
+    fn for_edit(arguments: &str, working_dir: &Path) -> Option<Self> {
+        let config = Config::load().ok().flatten()?.verification;
+        if !config.enabled {
+            return None;
+        }
+        let args: Value = serde_json::from_str(arguments).ok()?;
+        let path = normalize_path(args.get("path")?.as_str()?, working_dir);
+
+        Some(Self {
+            checker: choose_checker(&config, &path)?,
+            path,
+            working_dir: working_dir.to_path_buf(),
+            timeout: Duration::from_secs(config.timeout_secs),
+        })
+    }
+
+    fn name(&self) -> String {
+        match &self.checker {
+            Checker::Lsp => "LSP".to_string(),
+            Checker::Cargo(_) => "cargo check".to_string(),
+            Checker::Tsc(_) => "tsc".to_string(),
+            Checker::Command(command) => command.clone(),
+        }
+    }
+
+    /// Scope the baseline applies to
+    fn baseline_key(&self) -> String {
+        match &self.checker {
+            Checker::Lsp => format!("lsp:{}", self.path.display()),
+            Checker::Cargo(root) => format!("cargo:{}", root.display()),
+            Checker::Tsc(root) => format!("tsc:{}", root.display()),
+            Checker::Command(command) => format!("command:{}:{}", command, self.working_dir.display()),
+        }
+    }
+
+    async fn ensure_baseline(&self) {
+        let key = self.baseline_key();
+        if BASELINES.lock().unwrap().get_or_insert_with(HashMap::new).contains_key(&key) {
+            return;
+        }
+
+        // A new file has no errors of its own yet
+        let known = if matches!(self.checker, Checker::Lsp) && !self.path.exists() {
+            HashSet::new()
+        } else {
+            self.run()
+                .await
+                .map(|problems| problems.iter().map(Problem::key).collect())
+                .unwrap_or_default()
+        };
+        BASELINES.lock().unwrap().get_or_insert_with(HashMap::new).insert(key, known);
+    }
+
+    async fn attach_new_errors(&self, result: &mut ToolResult) {
+        // A checker that can't run says nothing about the edit
+        let Ok(problems) = self.run().await else {
+            return;
+        };
+
+        let baseline = BASELINES
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|b| b.get(&self.baseline_key()).cloned())
+            .unwrap_or_default();
+        let mut seen = HashSet::new();
+        let new: Vec<String> = problems
+            .iter()
+            .filter(|p| !baseline.contains(&p.key()))
+            .map(Problem::display)
+            .filter(|line| seen.insert(line.clone()))
+            .collect();
+        if new.is_empty() {
+            return;
+        }
+
+        let i18n = get_i18n();
+        let mut note = i18n
+            .get("verification_new_errors")
+            .replacen("{}", &self.name(), 1)
+            .replacen("{}", &new.len().to_string(), 1);
+        for line in new.iter().take(MAX_REPORTED_ERRORS) {
+            note.push('\n');
+            note.push_str(line);
+        }
+        if new.len() > MAX_REPORTED_ERRORS {
+            note.push_str(&format!("\n... {} more", new.len() - MAX_REPORTED_ERRORS));
+        }
+
+        result.brief = format!(
+            "{} ({})",
+            result.brief,
+            i18n.get("verification_brief").replace("{}", &new.len().to_string())
+        );
+        result.verification_required = true;
+        result.verification_message = Some(note);
+    }
+
+    async fn run(&self) -> Result<Vec<Problem>> {
+        match &self.checker {
+            Checker::Lsp => self.run_lsp().await,
+            Checker::Cargo(root) => {
+                let (_, output) = run_program(
+                    "cargo",
+                    &["check", "--workspace", "--message-format=json", "--quiet"],
+                    root,
+                    self.timeout,
+                )
+                .await?;
+                Ok(self.relative_to_working_dir(root, parse_cargo_messages(&output)))
+            }
+            Checker::Tsc(root) => {
+                let (success, output) = run_program(
+                    "npx",
+                    &["--no-install", "tsc", "--noEmit", "--pretty", "false"],
+                    root,
+                    self.timeout,
+                )
+                .await?;
+                let problems = parse_tsc_output(&output);
+                if !success && problems.is_empty() {
+                    return Err(anyhow!("tsc failed: {}", output.trim()));
+                }
+                Ok(self.relative_to_working_dir(root, problems))
+            }
+            Checker::Command(command) => {
+                let (program, flag) = if cfg!(target_os = "windows") {
+                    ("cmd", "/C")
+                } else {
+                    ("sh", "-c")
+                };
+                let (success, output) =
+                    run_program(program, &[flag, command], &self.working_dir, self.timeout).await?;
+                if success {
+                    return Ok(Vec::new());
+                }
+                Ok(output
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| Problem {
+                        file: String::new(),
+                        line: 0,
+                        column: 0,
+                        message: line.trim_end().to_string(),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Rewrite paths reported relative to a project root so they match the tools' paths
+    fn relative_to_working_dir(&self, root: &Path, mut problems: Vec<Problem>) -> Vec<Problem> {
+        for problem in &mut problems {
+            let path = root.join(&problem.file);
+            if let Ok(relative) = path.strip_prefix(&self.working_dir) {
+                problem.file = relative.display().to_string();
+            }
+        }
+        problems
+    }
+
+    async fn run_lsp(&self) -> Result<Vec<Problem>> {
+        let client = client_for(&self.path).await?;
+        let diagnostics = client.diagnostics(&self.path, LSP_WAIT).await?;
+        let file = self
+            .path
+            .strip_prefix(&self.working_dir)
+            .unwrap_or(&self.path)
+            .display()
+            .to_string();
+
+        Ok(diagnostics
+            .into_iter()
+            .filter(|d| d.severity == Some(DiagnosticSeverity::ERROR))
+            .map(|d| Problem {
+                file: file.clone(),
+                line: d.range.start.line + 1,
+                column: d.range.start.character + 1,
+                message: d.message,
+            })
+            .collect())
+    }
+}
+
+/// Checker for a file, configured per extension or picked from the project layout
+fn choose_checker(config: &VerificationConfig, path: &Path) -> Option<Checker> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let dir = path.parent()?;
+
+    match config.checkers.get(ext).map(|c| c.trim()) {
+        Some("off") | Some("") => None,
+        Some("lsp") => Some(Checker::Lsp),
+        Some("cargo") => find_cargo_root(dir).map(Checker::Cargo),
+        Some("tsc") => find_project(dir, "tsconfig.json").map(Checker::Tsc),
+        Some(command) => Some(Checker::Command(command.to_string())),
+        None => {
+            let project = match ext {
+                "rs" => find_cargo_root(dir).map(Checker::Cargo),
+                "ts" | "tsx" | "mts" | "cts" => find_project(dir, "tsconfig.json").map(Checker::Tsc),
+                _ => None,
+            };
+            project.or_else(|| get_lsp_command(ext).map(|_| Checker::Lsp))
+        }
+    }
+}
+
+/// Nearest ancestor directory containing `marker`
+fn find_project(dir: &Path, marker: &str) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(marker).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Directory cargo reports paths relative to: the nearest workspace containing the
+/// nearest package, or that package when it is not part of one
+fn find_cargo_root(dir: &Path) -> Option<PathBuf> {
+    let package = find_project(dir, "Cargo.toml")?;
+    let workspace = package.ancestors().find(|d| {
+        std::fs::read_to_string(d.join("Cargo.toml")).is_ok_and(|manifest| {
+            manifest.lines().any(|line| {
+                let line = line.trim();
+                line == "[workspace]" || line.starts_with("[workspace.")
+            })
+        })
+    });
+    Some(workspace.map(Path::to_path_buf).unwrap_or(package))
+}
+
+/// Run a program, returns whether it succeeded and its stdout followed by stderr
+async fn run_program(
+    program: &str,
+    args: &[&str],
+    dir: &Path,
+    timeout: Duration,
+) -> Result<(bool, String)> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(timeout, output)
+        .await
+        .map_err(|_| anyhow!("{} timed out after {}s", program, timeout.as_secs()))??;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status.success(), text))
+}
+
+/// Errors from `cargo check --message-format=json`
+fn parse_cargo_messages(output: &str) -> Vec<Problem> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|v| v["reason"] == "compiler-message" && v["message"]["level"] == "error")
+        .filter_map(|v| {
+            let message = &v["message"];
+            // Summaries like "aborting due to previous error" have no span
+            let span = message["spans"]
+                .as_array()?
+                .iter()
+                .find(|s| s["is_primary"] == true)?;
+            let text = match message["code"]["code"].as_str() {
+                Some(code) => format!("{} [{}]", message["message"].as_str()?, code),
+                None => message["message"].as_str()?.to_string(),
+            };
+            Some(Problem {
+                file: span["file_name"].as_str()?.to_string(),
+                line: span["line_start"].as_u64()? as u32,
+                column: span["column_start"].as_u64()? as u32,
+                message: text,
+            })
+        })
+        .collect()
+}
+
+/// Errors from `tsc --pretty false`: `file(line,col): error TS1234: message`
+fn parse_tsc_output(output: &str) -> Vec<Problem> {
+    let pattern = Regex::new(r"^(.+?)\((\d+),(\d+)\): error (TS\d+): (.*)$").unwrap();
+    output
+        .lines()
+        .filter_map(|line| pattern.captures(line))
+        .map(|c| Problem {
+            file: c[1].to_string(),
+            line: c[2].parse().unwrap_or(0),
+            column: c[3].parse().unwrap_or(0),
+            message: format!("{} [{}]", &c[5], &c[4]),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_messages() {
+        let output = [
+            r#"{"reason":"compiler-artifact","target":{"name":"dep"}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","code":{"code":"E0308"},"spans":[{"file_name":"src/lib.rs","line_start":3,"column_start":9,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused variable","code":null,"spans":[{"file_name":"src/lib.rs","line_start":2,"column_start":5,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"aborting due to 1 previous error","code":null,"spans":[]}}"#,
+        ]
+        .join("\n");
+
+        let problems = parse_cargo_messages(&output);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].display(), "src/lib.rs:3:9: mismatched types [E0308]");
+    }
+
+    #[test]
+    fn test_cargo_paths_of_member_crate() {
+        let root = std::env::temp_dir().join(format!("friendev-verify-{}", std::process::id()));
+        let member = root.join("tools");
+        std::fs::create_dir_all(member.join("src")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"tools\"]\n").unwrap();
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"tools\"\n").unwrap();
+
+        let path = member.join("src/x.rs");
+        let Some(Checker::Cargo(cargo_root)) = choose_checker(&VerificationConfig::default(), &path) else {
+            panic!("expected cargo checker");
+        };
+        assert_eq!(cargo_root, root);
+
+        let check = Check {
+            checker: Checker::Cargo(cargo_root.clone()),
+            path,
+            working_dir: root.clone(),
+            timeout: Duration::from_secs(1),
+        };
+        let output = r#"{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","code":null,"spans":[{"file_name":"tools/src/x.rs","line_start":1,"column_start":1,"is_primary":true}]}}"#;
+        let problems = check.relative_to_working_dir(&cargo_root, parse_cargo_messages(output));
+        assert_eq!(problems[0].file, "tools/src/x.rs");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_tsc_output() {
+        let output = "src/a.ts(4,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+                      Found 1 error in src/a.ts:4\n";
+        let problems = parse_tsc_output(output);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].display(),
+            "src/a.ts:4:7: Type 'string' is not assignable to type 'number'. [TS2322]"
+        );
+    }
+}
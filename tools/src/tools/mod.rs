@@ -1,12 +1,17 @@
 pub mod args;
+pub mod background;
 pub mod command_manager;
 pub mod definitions;
 pub mod executor;
 pub mod types;
 pub mod utils;
 pub mod indexer;
+pub mod shell_parser;
 
-pub use self::definitions::{get_available_tools, get_available_tools_with_mcp};
+pub use self::definitions::{
+    custom_agent, get_available_tools, get_available_tools_with_mcp, is_investigator, is_read_only_tool,
+    load_custom_agents, subagent_tools, MAX_PARALLEL_SUBAGENTS,
+};
 pub use command_manager::CommandConfig;
 pub use executor::execute_tool;
 pub use types::{Tool, ToolFunction, ToolResult};
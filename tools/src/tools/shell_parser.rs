@@ -0,0 +1,699 @@
+use anyhow::{anyhow, bail, Result};
+use std::path::{Path, PathBuf};
+
+/// Deepest nesting of subshells, substitutions and `sh -c` strings we follow
+const MAX_DEPTH: usize = 16;
+
+/// Words that start or end a compound command, the program follows them
+const RESERVED_PREFIXES: &[&str] = &["if", "then", "else", "elif", "do", "while", "until", "!", "{", "time"];
+const RESERVED_ALONE: &[&str] = &["fi", "done", "esac", "}"];
+
+/// How two commands of a list are joined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    Pipe,
+    And,
+    Or,
+    Sequence,
+    Background,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    /// Operator without the file descriptor, `>`, `>>`, `<`, `&>`, ...
+    pub op: String,
+    pub target: String,
+}
+
+impl Redirect {
+    /// Whether the redirect writes to a file, not a descriptor duplication like `2>&1`
+    pub fn writes_file(&self) -> bool {
+        matches!(self.op.as_str(), ">" | ">>" | ">|" | "&>" | "&>>" | "<>")
+            && !self.target.starts_with('&')
+            && self.target != "/dev/null"
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimpleCommand {
+    /// `NAME=value` words before the program
+    pub assignments: Vec<String>,
+    /// Program and arguments, unquoted
+    pub words: Vec<String>,
+    pub redirects: Vec<Redirect>,
+    /// Commands inside `$(...)`, backticks and `<(...)` in the words
+    pub substitutions: Vec<Node>,
+}
+
+/// Shell syntax tree of a command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Simple(SimpleCommand),
+    /// `( ... )` or `{ ...; }` with the redirects that follow it
+    Group(Box<Node>, Vec<Redirect>),
+    /// Commands joined by connectors, `connectors.len() == items.len() - 1`
+    List(Vec<Node>, Vec<Connector>),
+}
+
+/// A program the command line runs, after unwrapping `sudo`, `env`, `xargs` and the like
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    /// Lowercase file name of the program, without directories or `.exe`
+    pub program: String,
+    /// The program word as written followed by its arguments
+    pub words: Vec<String>,
+}
+
+impl Invocation {
+    pub fn text(&self) -> String {
+        self.words.join(" ")
+    }
+}
+
+/// Everything a command line runs and writes, as far as it can be told without running it
+#[derive(Debug, Default)]
+pub struct CommandAnalysis {
+    pub invocations: Vec<Invocation>,
+    /// Files written by redirects or `tee`, relative to the starting directory unless absolute
+    pub write_targets: Vec<PathBuf>,
+    /// A program is only known once variables or substitutions are expanded, like `$CMD x`
+    pub unknown_program: bool,
+    /// A file is written whose name or directory is only known once variables or
+    /// substitutions are expanded, like `> "$OUT"` or `cd $DIR && echo > x`
+    pub unknown_write: bool,
+}
+
+/// Parse a command line into its syntax tree
+pub fn parse(command: &str) -> Result<Node> {
+    Parser::new(command, 0).parse_list(None)
+}
+
+/// Parse a command line and collect every program it runs and file it writes
+pub fn analyze(command: &str) -> Result<CommandAnalysis> {
+    let mut analysis = CommandAnalysis::default();
+    walk(&parse(command)?, &mut Some(PathBuf::new()), &mut analysis, 0)?;
+    Ok(analysis)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    /// Substitutions of the simple command being read
+    substitutions: Vec<Node>,
+}
+
+impl Parser {
+    fn new(text: &str, depth: usize) -> Self {
+        Self {
+            chars: text.chars().collect(),
+            pos: 0,
+            depth,
+            substitutions: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    /// Skip blanks and line continuations, not newlines
+    fn skip_blanks(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ') | Some('\t') | Some('\r') => self.pos += 1,
+                Some('\\') if self.peek_at(1) == Some('\n') => self.pos += 2,
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_list(&mut self, closer: Option<char>) -> Result<Node> {
+        if self.depth > MAX_DEPTH {
+            bail!("command nested too deeply");
+        }
+        let mut items = Vec::new();
+        let mut connectors = Vec::new();
+
+        loop {
+            self.skip_blanks();
+            let connector = match self.peek() {
+                None => {
+                    if let Some(c) = closer {
+                        bail!("missing `{}`", c);
+                    }
+                    break;
+                }
+                Some(c) if Some(c) == closer => {
+                    self.pos += 1;
+                    break;
+                }
+                Some('#') => {
+                    self.skip_comment();
+                    continue;
+                }
+                Some(';') | Some('\n') => {
+                    self.pos += if self.starts_with(";;") { 2 } else { 1 };
+                    Connector::Sequence
+                }
+                Some('&') if self.starts_with("&&") => {
+                    self.pos += 2;
+                    Connector::And
+                }
+                Some('&') if !self.starts_with("&>") => {
+                    self.pos += 1;
+                    Connector::Background
+                }
+                Some('|') if self.starts_with("||") => {
+                    self.pos += 2;
+                    Connector::Or
+                }
+                Some('|') => {
+                    self.pos += if self.starts_with("|&") { 2 } else { 1 };
+                    Connector::Pipe
+                }
+                Some(')') => bail!("unexpected `)`"),
+                _ => {
+                    let item = self.parse_item()?;
+                    if connectors.len() < items.len() {
+                        connectors.push(Connector::Sequence);
+                    }
+                    items.push(item);
+                    continue;
+                }
+            };
+            // A connector with nothing before it (`; cmd`) joins nothing
+            if connectors.len() < items.len() {
+                connectors.push(connector);
+            }
+        }
+
+        connectors.truncate(items.len().saturating_sub(1));
+        Ok(if items.len() == 1 && connectors.is_empty() {
+            items.pop().unwrap()
+        } else {
+            Node::List(items, connectors)
+        })
+    }
+
+    fn parse_item(&mut self) -> Result<Node> {
+        if self.starts_with("((") {
+            // Arithmetic runs nothing
+            self.skip_balanced('(', ')')?;
+            return Ok(Node::Simple(SimpleCommand::default()));
+        }
+
+        let body = if self.peek() == Some('(') {
+            self.pos += 1;
+            self.depth += 1;
+            let body = self.parse_list(Some(')'));
+            self.depth -= 1;
+            body?
+        } else if self.peek() == Some('{') && self.peek_at(1).is_some_and(char::is_whitespace) {
+            self.pos += 1;
+            self.depth += 1;
+            let body = self.parse_list(Some('}'));
+            self.depth -= 1;
+            body?
+        } else {
+            return self.parse_simple().map(Node::Simple);
+        };
+
+        // Redirects after the group apply to all of it
+        let trailing = self.parse_simple()?;
+        let mut redirects = trailing.redirects;
+        let mut body = body;
+        if !trailing.words.is_empty() || !trailing.substitutions.is_empty() {
+            body = Node::List(vec![body, Node::Simple(SimpleCommand { redirects: Vec::new(), ..trailing })], vec![Connector::Sequence]);
+            redirects = Vec::new();
+        }
+        Ok(Node::Group(Box::new(body), redirects))
+    }
+
+    fn parse_simple(&mut self) -> Result<SimpleCommand> {
+        let saved = std::mem::take(&mut self.substitutions);
+        let mut command = SimpleCommand::default();
+
+        loop {
+            self.skip_blanks();
+            let Some(c) = self.peek() else { break };
+            match c {
+                ';' | '\n' | '|' | ')' => break,
+                '&' if !self.starts_with("&>") => break,
+                '#' => {
+                    self.skip_comment();
+                    break;
+                }
+                _ => {}
+            }
+
+            if let Some(op) = self.redirect_op() {
+                if op == "<(" || op == ">(" {
+                    self.pos += 2;
+                    self.parse_nested(')')?;
+                    command.words.push(format!("{}...)", op));
+                    continue;
+                }
+                self.pos += op.chars().count();
+                self.skip_blanks();
+                let target = self.read_word()?;
+                let op = op.trim_start_matches(|c: char| c.is_ascii_digit()).to_string();
+                command.redirects.push(Redirect { op, target });
+                continue;
+            }
+
+            let word = self.read_word()?;
+            if word.is_empty() {
+                // An unsupported character, skip it rather than loop
+                self.pos += 1;
+                continue;
+            }
+            if command.words.is_empty() && is_assignment(&word) {
+                command.assignments.push(word);
+            } else {
+                command.words.push(word);
+            }
+        }
+
+        command.substitutions = std::mem::replace(&mut self.substitutions, saved);
+        Ok(command)
+    }
+
+    /// Redirect operator at the cursor, with its file descriptor prefix
+    fn redirect_op(&self) -> Option<String> {
+        let digits = self.chars[self.pos..].iter().take_while(|c| c.is_ascii_digit()).count();
+        let rest: String = self.chars[self.pos + digits..].iter().take(3).collect();
+        const OPS: &[&str] = &["&>>", "<<<", "<<-", "&>", ">>", ">|", ">&", "<<", "<&", "<>", "<(", ">(", ">", "<"];
+        let op = OPS.iter().find(|op| rest.starts_with(*op))?;
+        if digits > 0 && (op.starts_with('&') || op.ends_with('(')) {
+            return None;
+        }
+        let prefix: String = self.chars[self.pos..self.pos + digits].iter().collect();
+        Some(format!("{}{}", prefix, op))
+    }
+
+    /// Read one word, removing quotes and collecting substitutions
+    fn read_word(&mut self) -> Result<String> {
+        let mut word = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' | '\n' | ';' | '&' | '|' | '<' | '>' | '(' | ')' => break,
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\n') => self.pos += 1,
+                        Some(escaped) => {
+                            word.push(escaped);
+                            self.pos += 1;
+                        }
+                        None => {}
+                    }
+                }
+                '\'' => {
+                    self.pos += 1;
+                    loop {
+                        match self.peek() {
+                            Some('\'') => break,
+                            Some(c) => word.push(c),
+                            None => bail!("unterminated single quote"),
+                        }
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                }
+                '"' => {
+                    self.pos += 1;
+                    loop {
+                        match self.peek() {
+                            Some('"') => break,
+                            Some('\\') if self.peek_at(1).is_some_and(|n| "\"\\$`\n".contains(n)) => {
+                                if self.peek_at(1) != Some('\n') {
+                                    word.push(self.peek_at(1).unwrap());
+                                }
+                                self.pos += 2;
+                                continue;
+                            }
+                            Some('$') | Some('`') => {
+                                word.push_str(&self.read_dollar_or_backtick()?);
+                                continue;
+                            }
+                            Some(c) => word.push(c),
+                            None => bail!("unterminated double quote"),
+                        }
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                }
+                '$' | '`' => word.push_str(&self.read_dollar_or_backtick()?),
+                _ => {
+                    word.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        Ok(word)
+    }
+
+    /// Read `$...` or a backtick substitution at the cursor, returns its literal text
+    fn read_dollar_or_backtick(&mut self) -> Result<String> {
+        if self.peek() == Some('`') {
+            let start = self.pos + 1;
+            let mut end = start;
+            while end < self.chars.len() && self.chars[end] != '`' {
+                end += if self.chars[end] == '\\' { 2 } else { 1 };
+            }
+            if end >= self.chars.len() {
+                bail!("unterminated backtick");
+            }
+            let inner: String = self.chars[start..end].iter().collect();
+            self.pos = end + 1;
+            let node = Parser::new(&inner, self.depth + 1).parse_list(None)?;
+            self.substitutions.push(node);
+            return Ok(format!("`{}`", inner));
+        }
+
+        let start = self.pos;
+        if self.starts_with("$((") {
+            self.pos += 1;
+            self.skip_balanced('(', ')')?;
+        } else if self.starts_with("$(") {
+            self.pos += 2;
+            self.parse_nested(')')?;
+        } else if self.starts_with("${") {
+            self.pos += 1;
+            self.skip_balanced('{', '}')?;
+        } else {
+            self.pos += 1;
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Parse commands up to `closer` as a substitution of the current simple command
+    fn parse_nested(&mut self, closer: char) -> Result<()> {
+        let outer = std::mem::take(&mut self.substitutions);
+        self.depth += 1;
+        let node = self.parse_list(Some(closer));
+        self.depth -= 1;
+        self.substitutions = outer;
+        self.substitutions.push(node?);
+        Ok(())
+    }
+
+    /// Skip from an opening bracket at the cursor past its match, ignoring quotes inside
+    fn skip_balanced(&mut self, open: char, close: char) -> Result<()> {
+        let mut level = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == open {
+                level += 1;
+            } else if c == close {
+                level -= 1;
+                if level == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        Err(anyhow!("missing `{}`", close))
+    }
+}
+
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// `cwd` is where the command runs relative to the starting directory, `None` once a
+/// `cd` went somewhere that can't be told
+fn walk(node: &Node, cwd: &mut Option<PathBuf>, analysis: &mut CommandAnalysis, depth: usize) -> Result<()> {
+    match node {
+        Node::Simple(command) => {
+            for substitution in &command.substitutions {
+                walk(substitution, &mut cwd.clone(), analysis, depth + 1)?;
+            }
+            add_redirects(&command.redirects, cwd.as_deref(), analysis);
+
+            let words = strip_reserved(&command.words);
+            if words.first().map(String::as_str) == Some("cd") {
+                // Later redirects in the same list are relative to the new directory
+                *cwd = match words.get(1).map(String::as_str) {
+                    None => dirs::home_dir(),
+                    Some("-") => None,
+                    Some(dir) => expand_path(dir).and_then(|dir| cwd.as_ref().map(|cwd| cwd.join(dir))),
+                };
+            }
+            add_invocations(words, cwd.as_deref(), analysis, depth)?;
+        }
+        Node::Group(body, redirects) => {
+            walk(body, &mut cwd.clone(), analysis, depth + 1)?;
+            add_redirects(redirects, cwd.as_deref(), analysis);
+        }
+        Node::List(items, _) => {
+            for item in items {
+                walk(item, cwd, analysis, depth)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Drop keywords like `if` or `do` and the assignments after them in front of the program
+fn strip_reserved(words: &[String]) -> &[String] {
+    let mut words = words;
+    while let Some(first) = words.first() {
+        if RESERVED_PREFIXES.contains(&first.as_str()) || is_assignment(first) {
+            words = &words[1..];
+        } else if RESERVED_ALONE.contains(&first.as_str()) || first == "for" || first == "case" {
+            // Loop headers and case patterns run nothing themselves
+            return &[];
+        } else {
+            break;
+        }
+    }
+    words
+}
+
+fn add_redirects(redirects: &[Redirect], cwd: Option<&Path>, analysis: &mut CommandAnalysis) {
+    for redirect in redirects.iter().filter(|r| r.writes_file()) {
+        add_write_target(&redirect.target, cwd, analysis);
+    }
+}
+
+/// Record a file the command writes, or that it writes one nobody can locate
+fn add_write_target(word: &str, cwd: Option<&Path>, analysis: &mut CommandAnalysis) {
+    match (expand_path(word), cwd) {
+        (Some(path), _) if path.is_absolute() => analysis.write_targets.push(path),
+        (Some(path), Some(cwd)) => analysis.write_targets.push(cwd.join(path)),
+        _ => analysis.unknown_write = true,
+    }
+}
+
+/// Expand `~` and `$HOME`, `None` when other variables make the path unknowable
+fn expand_path(word: &str) -> Option<PathBuf> {
+    let home = || dirs::home_dir().map(|h| h.to_string_lossy().to_string());
+    let expanded = if word == "~" || word.starts_with("~/") {
+        format!("{}{}", home()?, &word[1..])
+    } else {
+        word.replace("${HOME}", &home().unwrap_or_default())
+            .replace("$HOME", &home().unwrap_or_default())
+    };
+    if expanded.contains('$') || expanded.contains('`') {
+        return None;
+    }
+    Some(PathBuf::from(expanded))
+}
+
+fn program_name(word: &str) -> String {
+    let name = word.rsplit(['/', '\\']).next().unwrap_or(word).to_lowercase();
+    name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+}
+
+/// Skip options of a wrapper, `with_value` lists the ones that take an argument
+fn skip_options<'a>(words: &'a [String], with_value: &[&str]) -> &'a [String] {
+    let mut rest = words;
+    while let Some(first) = rest.first() {
+        if first == "--" {
+            return &rest[1..];
+        }
+        if !first.starts_with('-') || first == "-" {
+            break;
+        }
+        let takes_value = with_value.contains(&first.as_str());
+        rest = &rest[if takes_value { 2 } else { 1 }.min(rest.len())..];
+    }
+    rest
+}
+
+fn add_invocations(words: &[String], cwd: Option<&Path>, analysis: &mut CommandAnalysis, depth: usize) -> Result<()> {
+    if depth > MAX_DEPTH {
+        bail!("command nested too deeply");
+    }
+    let Some(first) = words.first() else {
+        return Ok(());
+    };
+    if first.contains('$') || first.contains('`') {
+        analysis.unknown_program = true;
+    }
+    let program = program_name(first);
+    analysis.invocations.push(Invocation {
+        program: program.clone(),
+        words: words.to_vec(),
+    });
+    let args = &words[1..];
+
+    match program.as_str() {
+        "sudo" | "doas" => {
+            let rest = skip_options(args, &["-u", "-g", "-C", "-h", "-p", "-U", "-r", "-t", "-D"]);
+            add_invocations(rest, cwd, analysis, depth + 1)?;
+        }
+        "env" => {
+            let rest = skip_options(args, &["-u", "-C", "-S"]);
+            let skip = rest.iter().take_while(|w| is_assignment(w)).count();
+            add_invocations(&rest[skip..], cwd, analysis, depth + 1)?;
+        }
+        "nohup" | "command" | "exec" | "builtin" | "time" | "busybox" | "stdbuf" | "ionice" | "chronic" => {
+            let rest = skip_options(args, &["-o", "-e", "-i", "-c", "-n"]);
+            add_invocations(rest, cwd, analysis, depth + 1)?;
+        }
+        "nice" => {
+            let rest = skip_options(args, &["-n"]);
+            add_invocations(rest, cwd, analysis, depth + 1)?;
+        }
+        "timeout" => {
+            let rest = skip_options(args, &["-s", "-k", "--signal", "--kill-after"]);
+            add_invocations(rest.get(1..).unwrap_or(&[]), cwd, analysis, depth + 1)?;
+        }
+        "xargs" => {
+            let rest = skip_options(args, &["-I", "-n", "-P", "-d", "-E", "-L", "-s", "-a"]);
+            if rest.is_empty() {
+                add_invocations(&["echo".to_string()], cwd, analysis, depth + 1)?;
+            } else {
+                add_invocations(rest, cwd, analysis, depth + 1)?;
+            }
+        }
+        "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" | "eval" => {
+            let script = if program == "eval" {
+                Some(args.join(" "))
+            } else {
+                args.iter().position(|a| a == "-c" || (a.starts_with('-') && !a.starts_with("--") && a.ends_with('c')))
+                    .and_then(|i| args.get(i + 1).cloned())
+            };
+            if let Some(script) = script {
+                let node = Parser::new(&script, depth + 1).parse_list(None)?;
+                walk(&node, &mut cwd.map(Path::to_path_buf), analysis, depth + 1)?;
+            }
+        }
+        "find" => {
+            let mut i = 0;
+            while i < args.len() {
+                match args[i].as_str() {
+                    "-exec" | "-execdir" | "-ok" | "-okdir" => {
+                        let end = args[i + 1..]
+                            .iter()
+                            .position(|a| a == ";" || a == "+")
+                            .map_or(args.len(), |p| i + 1 + p);
+                        add_invocations(&args[i + 1..end], cwd, analysis, depth + 1)?;
+                        i = end;
+                    }
+                    "-delete" => analysis.invocations.push(Invocation {
+                        program: "rm".to_string(),
+                        words: vec!["find".to_string(), "-delete".to_string()],
+                    }),
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+        "tee" => {
+            for target in skip_options(args, &[]).iter() {
+                add_write_target(target, cwd, analysis);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn programs(command: &str) -> Vec<String> {
+        analyze(command)
+            .unwrap()
+            .invocations
+            .into_iter()
+            .map(|i| i.program)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_lists_and_groups() {
+        let node = parse("cd x && (ls | wc -l) > out.txt; echo 'a && b'").unwrap();
+        let Node::List(items, connectors) = node else {
+            panic!("expected a list");
+        };
+        assert_eq!(connectors, vec![Connector::And, Connector::Sequence]);
+        assert!(matches!(&items[1], Node::Group(_, redirects) if redirects[0].target == "out.txt"));
+        assert!(matches!(&items[2], Node::Simple(c) if c.words == ["echo", "a && b"]));
+    }
+
+    #[test]
+    fn test_finds_nested_programs() {
+        assert_eq!(programs("cd x && rm -rf /"), ["cd", "rm"]);
+        assert_eq!(programs("find . -name '*.o' -delete"), ["find", "rm"]);
+        assert_eq!(programs("find . -exec /bin/rm {} \\;"), ["find", "rm"]);
+        assert_eq!(programs("echo $(sudo -u root rm x) `del y`"), ["sudo", "rm", "del", "echo"]);
+        assert_eq!(programs("ls | xargs -n 1 rm"), ["ls", "xargs", "rm"]);
+        assert_eq!(programs("env FOO=1 bash -c \"git status; rmdir d\""), ["env", "bash", "git", "rmdir"]);
+        assert_eq!(programs("if true; then FORMAT=1 format c:; fi"), ["true", "format"]);
+        assert_eq!(programs("{ echo a; } 2>&1 | tee log"), ["echo", "tee"]);
+        assert_eq!(programs("echo \"rm -rf /\""), ["echo"]);
+    }
+
+    #[test]
+    fn test_write_targets() {
+        let analysis = analyze("echo a > a.txt 2>&1; cd sub && cat b >> ../c.log; echo x | tee d > /dev/null").unwrap();
+        assert_eq!(
+            analysis.write_targets,
+            [PathBuf::from("a.txt"), PathBuf::from("sub/../c.log"), PathBuf::from("sub/d")]
+        );
+        assert!(!analysis.unknown_program && !analysis.unknown_write);
+        assert!(analyze("echo 'unterminated").is_err());
+    }
+
+    #[test]
+    fn test_unexpanded_words() {
+        for command in ["$(echo rm) -rf x", "${X:-rm} x", "\"$CMD\" x", "sudo `which rm` x", "sh -c \"$SCRIPT\""] {
+            assert!(analyze(command).unwrap().unknown_program, "{}", command);
+        }
+        assert!(!analyze("echo $HOME \"$(date)\"").unwrap().unknown_program);
+
+        let analysis = analyze("echo KEY=1 > \"$PWD/.env\"; ls | tee ${LOG}").unwrap();
+        assert!(analysis.unknown_write && analysis.write_targets.is_empty());
+        // Relative targets after a `cd` to an unknown place are unknown too, absolute ones aren't
+        let analysis = analyze("cd $DIR && echo a > x && echo b > /tmp/y").unwrap();
+        assert!(analysis.unknown_write);
+        assert_eq!(analysis.write_targets, [PathBuf::from("/tmp/y")]);
+        assert!(!analyze("cd ~/src && echo a > x").unwrap().unknown_write);
+    }
+}
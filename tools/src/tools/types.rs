@@ -52,6 +52,14 @@ impl ToolResult {
             verification_message: None,
         }
     }
+
+    /// Output for the model, with the verification findings appended when there are any
+    pub fn content(&self) -> String {
+        match &self.verification_message {
+            Some(note) if self.verification_required => format!("{}\n\n{}", self.message, note),
+            _ => self.message.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,7 +1,7 @@
 pub mod ui;
 
 pub use ui::{
-    enhanced_output, extract_key_argument, get_i18n, print_model_list, prompt_approval,
-    select_model, set_jury_mode, set_review_handler, set_smart_approval_mode, show_detailed_content, ReviewRequest, Spinner,
-    ToolCallDisplay, ToolProgress,
+    denied_approvals, enhanced_output, events, output, extract_key_argument, get_i18n, is_headless_mode, lock_prompt, print_model_list, prompt_approval, report_auto_approval,
+    select_model, set_headless_mode, set_jury_mode, set_review_handler, set_smart_approval_mode, show_detailed_content, ReviewRequest, Spinner,
+    ToolCallDisplay, ToolProgress, ApprovalChoice, PlanDecision, prompt_plan_review,
 };
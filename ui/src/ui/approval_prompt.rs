@@ -4,6 +4,7 @@ use std::io::{self};
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use super::events::{emit, Event};
 use super::get_i18n;
 use i18n::I18n;
 
@@ -12,6 +13,8 @@ type ReviewHandler = dyn Fn(&ReviewRequest) -> io::Result<bool> + Send + Sync +
 static REVIEW_HANDLER: OnceLock<Box<ReviewHandler>> = OnceLock::new();
 static SMART_APPROVAL_MODE: AtomicBool = AtomicBool::new(false);
 static JURY_MODE: AtomicBool = AtomicBool::new(false);
+/// Held while a prompt waits for the user, subagents running side by side ask one at a time
+static PROMPT_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
 /// Review request
 pub struct ReviewRequest<'a> {
@@ -21,6 +24,25 @@ pub struct ReviewRequest<'a> {
     pub is_jury: bool,
 }
 
+/// What the user chose at an approval prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalChoice {
+    Approve,
+    /// Approve this action for the rest of the session
+    AlwaysForSession,
+    /// Approve and save an allow rule for the offered pattern
+    AlwaysForPattern,
+    /// Show the full content before deciding
+    ViewDetails,
+    Reject,
+}
+
+impl ApprovalChoice {
+    pub fn is_approved(self) -> bool {
+        !matches!(self, Self::Reject)
+    }
+}
+
 /// Register review handler
 pub fn set_review_handler<F>(handler: F)
 where
@@ -39,13 +61,32 @@ pub fn set_jury_mode(enabled: bool) {
     JURY_MODE.store(enabled, Ordering::Relaxed);
 }
 
+/// Wait until no other approval prompt is open, keep the guard while asking
+///
+/// Waiting yields to the runtime, unlike the prompt itself which blocks its thread.
+pub async fn lock_prompt() -> tokio::sync::MutexGuard<'static, ()> {
+    PROMPT_LOCK.lock().await
+}
+
+/// Report an action that ran without a prompt, approved by `reviewer` (`rule` or `auto`)
+pub fn report_auto_approval(action: &str, subject: &str, reviewer: &str) {
+    emit(&Event::Approval {
+        action,
+        subject,
+        approved: true,
+        reviewer,
+    });
+}
+
 /// User approval prompt
-/// Returns (approved, always, view_details)
+///
+/// With a `pattern` the prompt also offers to always allow it, the caller saves the rule.
 pub fn prompt_approval(
     action: &str,
     file_path: &str,
     content_preview: Option<&str>,
-) -> io::Result<(bool, bool, bool)> {
+    pattern: Option<&str>,
+) -> io::Result<ApprovalChoice> {
     use std::path::Path;
 
     let is_smart_mode = SMART_APPROVAL_MODE.load(Ordering::Relaxed);
@@ -68,11 +109,21 @@ pub fn prompt_approval(
                 println!("\n{}", get_i18n().get("approval_review_wait").yellow());
             }
 
-            match handler(&request) {
-                Ok(true) => return Ok((true, false, false)),
+            let result = handler(&request);
+            if let Ok(approved) = result {
+                emit(&Event::Approval {
+                    action,
+                    subject: file_path,
+                    approved,
+                    reviewer: if is_jury_mode { "jury" } else { "smart" },
+                });
+            }
+
+            match result {
+                Ok(true) => return Ok(ApprovalChoice::Approve),
                 Ok(false) => {
                     println!("{}", get_i18n().get("approval_rejected").red());
-                    return Ok((false, false, false));
+                    return Ok(ApprovalChoice::Reject);
                 }
                 Err(e) => {
                     println!("{} {}", "Review Error:".red(), e);
@@ -82,6 +133,24 @@ pub fn prompt_approval(
         }
     }
 
+    // Nobody can answer the prompt without a terminal, so fail closed
+    if super::is_headless_mode() {
+        super::headless::record_denied_approval();
+        emit(&Event::Approval {
+            action,
+            subject: file_path,
+            approved: false,
+            reviewer: "headless",
+        });
+        eprintln!(
+            "{} {} {}",
+            "[X]".red(),
+            get_i18n().get("headless_approval_denied"),
+            format!("{} {}", action, file_path).yellow()
+        );
+        return Ok(ApprovalChoice::Reject);
+    }
+
     let file_name = Path::new(file_path)
         .file_name()
         .and_then(|n| n.to_str())
@@ -119,28 +188,39 @@ pub fn prompt_approval(
     }
     println!();
 
-    let choices = vec![
-        i18n.get("approval_opt_approve"),
-        i18n.get("approval_opt_always"),
-        i18n.get("approval_opt_details"),
-        i18n.get("approval_opt_reject"),
+    let mut choices = vec![
+        (i18n.get("approval_opt_approve"), ApprovalChoice::Approve),
+        (i18n.get("approval_opt_always"), ApprovalChoice::AlwaysForSession),
     ];
+    if let Some(pattern) = pattern {
+        choices.push((
+            i18n.get("approval_opt_always_pattern").replace("{}", pattern),
+            ApprovalChoice::AlwaysForPattern,
+        ));
+    }
+    choices.push((i18n.get("approval_opt_details"), ApprovalChoice::ViewDetails));
+    choices.push((i18n.get("approval_opt_reject"), ApprovalChoice::Reject));
+    let labels: Vec<&str> = choices.iter().map(|(label, _)| label.as_str()).collect();
 
     loop {
         let selection = Select::with_theme(&ColorfulTheme::default())
             .with_prompt(i18n.get("approval_choice_prompt"))
-            .items(&choices)
+            .items(&labels)
             .default(0)
             .interact()
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
-        match selection {
-            0 => return Ok((true, false, false)), // Approve
-            1 => {
+        match choices[selection].1 {
+            ApprovalChoice::Approve => return Ok(ApprovalChoice::Approve),
+            ApprovalChoice::AlwaysForSession => {
                 println!("  {} {}", "✓".green(), i18n.get("approval_always_approved"));
-                return Ok((true, true, false)); // Always
+                return Ok(ApprovalChoice::AlwaysForSession);
+            }
+            ApprovalChoice::AlwaysForPattern => {
+                println!("  {} {}", "✓".green(), i18n.get("approval_pattern_saved"));
+                return Ok(ApprovalChoice::AlwaysForPattern);
             }
-            2 => {
+            ApprovalChoice::ViewDetails => {
                 // Check if we have a review handler
                 if REVIEW_HANDLER.get().is_some() {
                      let request = ReviewRequest {
@@ -162,15 +242,15 @@ pub fn prompt_approval(
 
                     // After review (or attempt), ask for final decision
                     let approved = prompt_review_decision(&i18n)?;
-                    return Ok((approved, false, false));
+                    return Ok(if approved { ApprovalChoice::Approve } else { ApprovalChoice::Reject });
                 } else {
                      // No review handler, treat as "Show Raw Details" request
-                     return Ok((true, false, true));
+                     return Ok(ApprovalChoice::ViewDetails);
                 }
             }
-            _ => {
+            ApprovalChoice::Reject => {
                 println!("  {} {}", "✗".red(), i18n.get("approval_rejected"));
-                return Ok((false, false, false)); // Reject
+                return Ok(ApprovalChoice::Reject);
             }
         }
     }
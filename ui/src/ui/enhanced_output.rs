@@ -7,7 +7,7 @@ use crossterm::{
 use std::io::{self, Write};
 use unicode_width::UnicodeWidthStr;
 
-use super::get_i18n;
+use super::{get_i18n, output};
 
 /// Box drawing characters
 pub mod box_chars {
@@ -32,7 +32,7 @@ pub fn print_separator(width: Option<u16>) -> io::Result<()> {
     
     let line = box_chars::HORIZONTAL.repeat(term_width as usize);
     execute!(
-        io::stdout(),
+        output::stdout(),
         SetForegroundColor(Color::DarkGrey),
         Print(&line),
         ResetColor,
@@ -50,7 +50,7 @@ pub fn print_section_header(title: &str) -> io::Result<()> {
     if total_content >= term_width {
         // Fallback for narrow terminals
         execute!(
-            io::stdout(),
+            output::stdout(),
             SetForegroundColor(Color::Cyan),
             Print(format!("─ {} ", title)),
             ResetColor,
@@ -64,7 +64,7 @@ pub fn print_section_header(title: &str) -> io::Result<()> {
     let right_line = box_chars::HORIZONTAL.repeat(remaining.saturating_sub(1));
     
     execute!(
-        io::stdout(),
+        output::stdout(),
         SetForegroundColor(Color::DarkGrey),
         Print(box_chars::TOP_LEFT),
         Print(&left_line),
@@ -86,7 +86,7 @@ pub fn print_section_footer() -> io::Result<()> {
     let line = box_chars::HORIZONTAL.repeat(term_width.saturating_sub(2));
     
     execute!(
-        io::stdout(),
+        output::stdout(),
         SetForegroundColor(Color::DarkGrey),
         Print(box_chars::BOTTOM_LEFT),
         Print(&line),
@@ -100,7 +100,7 @@ pub fn print_section_footer() -> io::Result<()> {
 pub fn print_ai_prefix() -> io::Result<()> {
     let i18n = get_i18n();
     execute!(
-        io::stdout(),
+        output::stdout(),
         Print("\n"),
         SetForegroundColor(Color::Cyan),
         Print("▍"),
@@ -113,7 +113,7 @@ pub fn print_ai_prefix() -> io::Result<()> {
 pub fn print_reasoning_prefix() -> io::Result<()> {
     let i18n = get_i18n();
     execute!(
-        io::stdout(),
+        output::stdout(),
         SetForegroundColor(Color::DarkGrey),
         Print(format!("\n  {} ", i18n.get("chat_think_label"))),
         ResetColor
@@ -123,7 +123,7 @@ pub fn print_reasoning_prefix() -> io::Result<()> {
 /// Print reasoning text (dim gray)
 pub fn print_reasoning_text(text: &str) -> io::Result<()> {
     execute!(
-        io::stdout(),
+        output::stdout(),
         SetForegroundColor(Color::DarkGrey),
         Print(text),
         ResetColor
@@ -132,14 +132,14 @@ pub fn print_reasoning_text(text: &str) -> io::Result<()> {
 
 /// Print normal content
 pub fn print_content(text: &str) -> io::Result<()> {
-    execute!(io::stdout(), Print(text))
+    execute!(output::stdout(), Print(text))
 }
 
 /// Print error message with icon
 pub fn print_error(message: &str) -> io::Result<()> {
     let i18n = get_i18n();
     execute!(
-        io::stdout(),
+        output::stdout(),
         Print("\n"),
         SetForegroundColor(Color::Red),
         Print("✗ "),
@@ -154,7 +154,7 @@ pub fn print_error(message: &str) -> io::Result<()> {
 /// Print warning message with icon
 pub fn print_warning(message: &str) -> io::Result<()> {
     execute!(
-        io::stdout(),
+        output::stdout(),
         Print("\n"),
         SetForegroundColor(Color::Yellow),
         Print("⚠ "),
@@ -167,7 +167,7 @@ pub fn print_warning(message: &str) -> io::Result<()> {
 /// Print success message with icon
 pub fn print_success(message: &str) -> io::Result<()> {
     execute!(
-        io::stdout(),
+        output::stdout(),
         Print("\n"),
         SetForegroundColor(Color::Green),
         Print("✓ "),
@@ -199,12 +199,14 @@ impl ToolProgress {
     pub fn start(&mut self) -> io::Result<()> {
         let i18n = get_i18n();
         
-        // Save starting position
-        let (col, _) = cursor::position()?;
-        self.line_start_col = col;
+        // Save starting position, buffered output has no cursor to ask about
+        if !output::is_buffered() {
+            let (col, _) = cursor::position()?;
+            self.line_start_col = col;
+        }
         
         execute!(
-            io::stdout(),
+            output::stdout(),
             Print("\n  "),
             SetForegroundColor(Color::DarkGrey),
             Print(SPINNER_FRAMES[0]),
@@ -221,7 +223,7 @@ impl ToolProgress {
         
         if let Some(arg) = &self.argument {
             execute!(
-                io::stdout(),
+                output::stdout(),
                 Print(" "),
                 SetForegroundColor(Color::DarkGrey),
                 Print(format!("({})", arg)),
@@ -229,7 +231,7 @@ impl ToolProgress {
             )?;
         }
         
-        io::stdout().flush()
+        output::stdout().flush()
     }
 
     /// Update the spinner animation
@@ -238,7 +240,7 @@ impl ToolProgress {
         
         // Move to the start of the line and redraw
         execute!(
-            io::stdout(),
+            output::stdout(),
             cursor::MoveToColumn(0),
             terminal::Clear(ClearType::CurrentLine),
             Print("  "),
@@ -257,7 +259,7 @@ impl ToolProgress {
         
         if let Some(arg) = &self.argument {
             execute!(
-                io::stdout(),
+                output::stdout(),
                 Print(" "),
                 SetForegroundColor(Color::DarkGrey),
                 Print(format!("({})", arg)),
@@ -265,7 +267,7 @@ impl ToolProgress {
             )?;
         }
         
-        io::stdout().flush()
+        output::stdout().flush()
     }
 
     /// Finish with success
@@ -273,7 +275,7 @@ impl ToolProgress {
         let i18n = get_i18n();
         
         execute!(
-            io::stdout(),
+            output::stdout(),
             cursor::MoveToColumn(0),
             terminal::Clear(ClearType::CurrentLine),
             Print("  "),
@@ -292,7 +294,7 @@ impl ToolProgress {
         
         if let Some(arg) = &self.argument {
             execute!(
-                io::stdout(),
+                output::stdout(),
                 Print(" "),
                 SetForegroundColor(Color::DarkGrey),
                 Print(format!("({})", arg)),
@@ -300,11 +302,11 @@ impl ToolProgress {
             )?;
         }
         
-        execute!(io::stdout(), Print("\n"))?;
+        execute!(output::stdout(), Print("\n"))?;
         
         if let Some(res) = result {
             execute!(
-                io::stdout(),
+                output::stdout(),
                 Print("    "),
                 SetForegroundColor(Color::DarkGrey),
                 Print(res),
@@ -313,7 +315,7 @@ impl ToolProgress {
             )?;
         }
         
-        io::stdout().flush()
+        output::stdout().flush()
     }
 
     /// Finish with error
@@ -321,7 +323,7 @@ impl ToolProgress {
         let i18n = get_i18n();
         
         execute!(
-            io::stdout(),
+            output::stdout(),
             cursor::MoveToColumn(0),
             terminal::Clear(ClearType::CurrentLine),
             Print("  "),
@@ -340,7 +342,7 @@ impl ToolProgress {
         
         if let Some(arg) = &self.argument {
             execute!(
-                io::stdout(),
+                output::stdout(),
                 Print(" "),
                 SetForegroundColor(Color::DarkGrey),
                 Print(format!("({})", arg)),
@@ -348,11 +350,11 @@ impl ToolProgress {
             )?;
         }
         
-        execute!(io::stdout(), Print("\n"))?;
+        execute!(output::stdout(), Print("\n"))?;
         
         if let Some(err) = error {
             execute!(
-                io::stdout(),
+                output::stdout(),
                 Print("    "),
                 SetForegroundColor(Color::Red),
                 Print(err),
@@ -361,7 +363,7 @@ impl ToolProgress {
             )?;
         }
         
-        io::stdout().flush()
+        output::stdout().flush()
     }
 }
 
@@ -376,7 +378,7 @@ pub fn print_tool_calls_box(tools: &[(String, Option<String>)]) -> io::Result<()
     
     for (name, arg) in tools {
         execute!(
-            io::stdout(),
+            output::stdout(),
             SetForegroundColor(Color::DarkGrey),
             Print(box_chars::VERTICAL),
             Print(" • "),
@@ -388,7 +390,7 @@ pub fn print_tool_calls_box(tools: &[(String, Option<String>)]) -> io::Result<()
         
         if let Some(a) = arg {
             execute!(
-                io::stdout(),
+                output::stdout(),
                 Print("  "),
                 SetForegroundColor(Color::DarkGrey),
                 Print(a),
@@ -396,7 +398,7 @@ pub fn print_tool_calls_box(tools: &[(String, Option<String>)]) -> io::Result<()
             )?;
         }
         
-        execute!(io::stdout(), Print("\n"))?;
+        execute!(output::stdout(), Print("\n"))?;
     }
     
     print_section_footer()
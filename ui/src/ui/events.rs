@@ -0,0 +1,133 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Destination of the NDJSON event stream, None unless `--output-format stream-json`
+static EVENT_SINK: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+/// Machine readable event, written as one JSON object per line
+///
+/// The `type` field names the event. Fields are only ever added, never renamed.
+/// Events of a subagent carry a `task` field with its id, subagents can run side by side.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// The run started
+    Start {
+        session_id: &'a str,
+        model: &'a str,
+        working_directory: &'a str,
+    },
+    /// Assistant text delta
+    Text { text: &'a str },
+    /// Reasoning (thinking) delta
+    Reasoning { text: &'a str },
+    /// A tool call is about to run
+    ToolCallStart {
+        id: &'a str,
+        name: &'a str,
+        arguments: Value,
+    },
+    /// A tool call finished
+    ToolCallEnd {
+        id: &'a str,
+        name: &'a str,
+        success: bool,
+        brief: &'a str,
+        output: &'a str,
+    },
+    /// An approval was decided without a human, `reviewer` is smart, jury or headless,
+    /// `rule` for an allow rule and `auto` for `--yolo`, `--allow` or a session approval
+    Approval {
+        action: &'a str,
+        subject: &'a str,
+        approved: bool,
+        reviewer: &'a str,
+    },
+    /// An API request failed and will be retried
+    Retry {
+        attempt: u32,
+        max_retries: u32,
+        delay_ms: u64,
+        error: &'a str,
+    },
+    /// The agent loop stopped on an error
+    Error { message: &'a str },
+    /// Final summary, always the last event
+    Result {
+        success: bool,
+        exit_code: i32,
+        text: &'a str,
+        session_id: Option<&'a str>,
+        duration_ms: u64,
+        denied_approvals: usize,
+        usage: Value,
+        cost_usd: Option<f64>,
+    },
+}
+
+/// An event with the subagent it comes from
+#[derive(Serialize)]
+struct Tagged<'a> {
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task: Option<&'a str>,
+}
+
+/// Send events to `sink` from now on
+pub fn set_event_sink(sink: Box<dyn Write + Send>) {
+    *EVENT_SINK.lock().unwrap() = Some(sink);
+}
+
+/// Whether events are being streamed
+pub fn is_event_stream() -> bool {
+    EVENT_SINK.lock().unwrap().is_some()
+}
+
+/// Write an event line if the event stream is enabled
+pub fn emit(event: &Event) {
+    let mut sink = EVENT_SINK.lock().unwrap();
+    let Some(writer) = sink.as_mut() else {
+        return;
+    };
+
+    let task = super::output::current_task();
+    let tagged = Tagged {
+        event,
+        task: task.as_deref(),
+    };
+    if let Ok(line) = serde_json::to_string(&tagged) {
+        // A closed pipe must not abort the run
+        let _ = writeln!(writer, "{}", line);
+        let _ = writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_format() {
+        let event = Event::ToolCallEnd {
+            id: "call_1",
+            name: "file_read",
+            success: true,
+            brief: "Read 3 lines",
+            output: "a\nb\nc",
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"tool_call_end","id":"call_1","name":"file_read","success":true,"brief":"Read 3 lines","output":"a\nb\nc"}"#
+        );
+
+        let text = Event::Text { text: "hi" };
+        let tagged = Tagged { event: &text, task: Some("subagent-1a2b3c4d") };
+        assert_eq!(
+            serde_json::to_string(&tagged).unwrap(),
+            r#"{"type":"text","text":"hi","task":"subagent-1a2b3c4d"}"#
+        );
+    }
+}
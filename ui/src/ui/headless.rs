@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static HEADLESS_MODE: AtomicBool = AtomicBool::new(false);
+static DENIED_APPROVALS: AtomicUsize = AtomicUsize::new(0);
+
+/// Set headless mode (no TTY: approval prompts are denied, no key polling)
+pub fn set_headless_mode(enabled: bool) {
+    HEADLESS_MODE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether Friendev runs without an interactive terminal
+pub fn is_headless_mode() -> bool {
+    HEADLESS_MODE.load(Ordering::Relaxed)
+}
+
+/// Count an approval prompt that was denied because nobody could answer it
+pub(crate) fn record_denied_approval() {
+    DENIED_APPROVALS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Number of approval prompts denied in headless mode
+pub fn denied_approvals() -> usize {
+    DENIED_APPROVALS.load(Ordering::Relaxed)
+}
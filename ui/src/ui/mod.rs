@@ -1,7 +1,11 @@
 mod approval_prompt;
+pub mod events;
+mod headless;
 mod spinner;
 mod tool_call_display;
 mod model_selector;
+pub mod output;
+mod plan_review;
 pub mod enhanced_output;
 
 use config::Config;
@@ -9,12 +13,15 @@ use i18n::I18n;
 
 // 重新导出主要的公共 API
 pub use approval_prompt::{
-    prompt_approval, set_jury_mode, set_review_handler, set_smart_approval_mode, show_detailed_content, ReviewRequest,
+    lock_prompt, prompt_approval, report_auto_approval, set_jury_mode, set_review_handler, set_smart_approval_mode, show_detailed_content, ApprovalChoice,
+    ReviewRequest,
 };
+pub use headless::{denied_approvals, is_headless_mode, set_headless_mode};
 pub use spinner::Spinner;
 pub use tool_call_display::{extract_key_argument, ToolCallDisplay};
 pub use enhanced_output::ToolProgress;
 pub use model_selector::{select_model, print_model_list};
+pub use plan_review::{prompt_plan_review, PlanDecision};
 
 /// 获取当前 UI 语言对应的 I18n 实例
 pub fn get_i18n() -> I18n {
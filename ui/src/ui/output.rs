@@ -0,0 +1,173 @@
+use std::future::Future;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use super::enhanced_output::{box_chars, print_section_footer, print_section_header};
+
+/// Lines of a buffered section shown when it is printed collapsed
+const COLLAPSED_LINES: usize = 12;
+
+tokio::task_local! {
+    /// Output of the subagent running in this task, printed once it finishes
+    static BUFFER: OutputBuffer;
+}
+
+/// Shared buffer collecting the terminal output of one subagent
+#[derive(Clone)]
+pub struct OutputBuffer {
+    /// Subagent the output comes from, also tags its events
+    task: Arc<str>,
+    bytes: Arc<Mutex<Vec<u8>>>,
+}
+
+/// Terminal output, written to the current task's buffer when there is one
+pub struct Output(Option<OutputBuffer>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &self.0 {
+            Some(buffer) => {
+                buffer.bytes.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            None => io::stdout().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &self.0 {
+            Some(_) => Ok(()),
+            None => io::stdout().flush(),
+        }
+    }
+}
+
+/// Writer to use instead of `io::stdout()` for output a subagent may produce
+pub fn stdout() -> Output {
+    Output(current_buffer())
+}
+
+/// Print text, into the current task's buffer when there is one
+pub fn print(text: &str) {
+    let mut out = stdout();
+    let _ = out.write_all(text.as_bytes());
+    let _ = out.flush();
+}
+
+/// Print a line, into the current task's buffer when there is one
+pub fn println(text: &str) {
+    print(&format!("{}\n", text));
+}
+
+/// Print an error line, into the current task's buffer when there is one
+pub fn eprintln(text: &str) {
+    match current_buffer() {
+        Some(_) => println(text),
+        None => std::eprintln!("{}", text),
+    }
+}
+
+/// Whether output of this task is being buffered
+pub fn is_buffered() -> bool {
+    current_buffer().is_some()
+}
+
+/// Subagent running in this task, `None` for the main agent
+pub fn current_task() -> Option<Arc<str>> {
+    BUFFER.try_with(|buffer| buffer.task.clone()).ok()
+}
+
+/// Buffer of this task, to carry into the tasks it spawns
+pub fn current_buffer() -> Option<OutputBuffer> {
+    BUFFER.try_with(|buffer| buffer.clone()).ok()
+}
+
+/// Run a future with `buffer` as its output buffer, or unbuffered when it is None
+pub async fn with_buffer<F: Future>(buffer: Option<OutputBuffer>, future: F) -> F::Output {
+    match buffer {
+        Some(buffer) => BUFFER.scope(buffer, future).await,
+        None => future.await,
+    }
+}
+
+/// Run a future as subagent `task` with its output collected, returned along with its result
+pub async fn buffered<F: Future>(task: &str, future: F) -> (F::Output, String) {
+    let buffer = OutputBuffer {
+        task: Arc::from(task),
+        bytes: Arc::default(),
+    };
+    let result = BUFFER.scope(buffer.clone(), future).await;
+    let bytes = std::mem::take(&mut *buffer.bytes.lock().unwrap());
+    (result, String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Print buffered output under a titled box, collapsed to its last lines
+///
+/// `footer` replaces the hidden part, e.g. where the full output was saved.
+pub fn print_collapsed_section(title: &str, output: &str, footer: Option<&str>) {
+    let lines = rendered_lines(output);
+    let hidden = lines.len().saturating_sub(COLLAPSED_LINES);
+
+    let mut out = stdout();
+    let _ = print_section_header(title);
+    if hidden > 0 {
+        let note = super::get_i18n().get("output_lines_hidden").replace("{}", &hidden.to_string());
+        let _ = writeln!(out, "{} \x1b[90m{}\x1b[0m", box_chars::VERTICAL, note);
+    }
+    for line in &lines[hidden..] {
+        let _ = writeln!(out, "{} {}\x1b[0m", box_chars::VERTICAL, line);
+    }
+    if let Some(footer) = footer {
+        let _ = writeln!(out, "{} \x1b[90m{}\x1b[0m", box_chars::VERTICAL, footer);
+    }
+    let _ = print_section_footer();
+    let _ = out.flush();
+}
+
+/// Lines as they ended up on screen: a return to column 0 overwrites what came before it
+/// on the line, and leading and trailing blank lines are dropped
+pub fn rendered_lines(output: &str) -> Vec<String> {
+    let lines: Vec<String> = output
+        .split('\n')
+        .map(|line| {
+            let line = line.rsplit(['\r']).next().unwrap_or(line);
+            let line = line.rsplit("\x1b[1G").next().unwrap_or(line);
+            line.replace("\x1b[2K", "")
+        })
+        .collect();
+    let is_blank = |line: &String| strip_ansi(line).trim().is_empty();
+    let start = lines.iter().position(|l| !is_blank(l)).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !is_blank(l)).map_or(start, |i| i + 1);
+    lines[start..end].to_vec()
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a CSI sequence up to its final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rendered_lines() {
+        let raw = "\n\n  ⠋ Using file_read\x1b[1G\x1b[2K  ✓ Used file_read\n    2 lines\n\x1b[0m\n";
+        assert_eq!(rendered_lines(raw), vec!["  ✓ Used file_read", "    2 lines"]);
+        assert_eq!(rendered_lines("progress 10%\rprogress 100%"), vec!["progress 100%"]);
+        assert!(rendered_lines("\n \n").is_empty());
+    }
+}
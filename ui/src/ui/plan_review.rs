@@ -0,0 +1,68 @@
+use colored::Colorize;
+use dialoguer::{theme::ColorfulTheme, Editor, Input, Select};
+use std::io;
+
+use super::get_i18n;
+
+/// What the user decided about a plan written in plan mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanDecision {
+    /// Leave plan mode and carry out the plan
+    Approve,
+    /// Carry out the user's edited version of the plan
+    Edit(String),
+    /// Stay in plan mode and have the plan revised with this feedback
+    Revise(String),
+    /// Drop the plan and stay in plan mode
+    Reject,
+}
+
+/// Ask the user to approve, edit, revise or reject a plan
+pub fn prompt_plan_review(plan: &str) -> io::Result<PlanDecision> {
+    let i18n = get_i18n();
+    let labels = [
+        i18n.get("plan_opt_approve"),
+        i18n.get("plan_opt_edit"),
+        i18n.get("plan_opt_revise"),
+        i18n.get("plan_opt_reject"),
+    ];
+
+    println!();
+    loop {
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(i18n.get("plan_review_prompt"))
+            .items(&labels)
+            .default(0)
+            .interact()
+            .map_err(io::Error::other)?;
+
+        match selection {
+            0 => return Ok(PlanDecision::Approve),
+            1 => {
+                let edited = Editor::new()
+                    .extension(".md")
+                    .edit(plan)
+                    .map_err(io::Error::other)?;
+                match edited {
+                    Some(text) if !text.trim().is_empty() => return Ok(PlanDecision::Edit(text)),
+                    // Closing the editor without saving goes back to the choices
+                    _ => println!("  {}", i18n.get("plan_edit_cancelled").yellow()),
+                }
+            }
+            2 => {
+                let feedback: String = Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt(i18n.get("plan_revise_prompt"))
+                    .allow_empty(true)
+                    .interact_text()
+                    .map_err(io::Error::other)?;
+                if !feedback.trim().is_empty() {
+                    return Ok(PlanDecision::Revise(feedback));
+                }
+            }
+            _ => {
+                println!("  {} {}", "✗".red(), i18n.get("plan_rejected"));
+                return Ok(PlanDecision::Reject);
+            }
+        }
+    }
+}